@@ -0,0 +1,143 @@
+//! Bodies of the admin instructions every program exposes: role rotation, pausing, the
+//! bankroll knobs and the resolver set. Each program keeps its account contexts and events and
+//! calls these for the role check and the state change, so a fix lands in every program at once.
+
+use anchor_lang::prelude::*;
+
+use crate::breaker::OutflowBreaker;
+use crate::cosign::{Cosigners, PendingCosigners};
+use crate::error::CommonError;
+use crate::house::{House, MAX_PAYOUT_BPS_CEILING};
+use crate::roles::{self, Roles};
+
+pub fn require_role(roles: &Roles, role: u8, by: &Pubkey) -> Result<()> {
+    require!(roles.is(role, by), CommonError::Unauthorized);
+    Ok(())
+}
+
+pub fn require_pauser(roles: &Roles, by: &Pubkey) -> Result<()> {
+    require!(roles.can_pause(by), CommonError::Unauthorized);
+    Ok(())
+}
+
+/// Super-admin queues `new_key` for `role`; returns when it may be applied.
+pub fn propose_role(roles: &mut Roles, by: &Pubkey, role: u8, new_key: Pubkey) -> Result<i64> {
+    require_role(roles, roles::SUPER_ADMIN, by)?;
+    Ok(roles.propose(role, new_key, Clock::get()?.unix_timestamp)?)
+}
+
+/// Super-admin drops a pending role change; returns the key that was proposed.
+pub fn cancel_role(roles: &mut Roles, by: &Pubkey, role: u8) -> Result<Pubkey> {
+    require_role(roles, roles::SUPER_ADMIN, by)?;
+    Ok(roles.cancel(role)?)
+}
+
+/// Installs a proposed key once its timelock has elapsed; returns `(previous, current)`.
+pub fn apply_role(roles: &mut Roles, role: u8) -> Result<(Pubkey, Pubkey)> {
+    Ok(roles.apply(role, Clock::get()?.unix_timestamp)?)
+}
+
+/// Pauser or super-admin halts locks for `mask`, and resolves too when `halt_resolve`.
+pub fn pause(roles: &Roles, by: &Pubkey, house: &mut House, mask: u8, halt_resolve: bool) -> Result<()> {
+    require_pauser(roles, by)?;
+    house.flags.pause(mask, halt_resolve);
+    Ok(())
+}
+
+pub fn unpause(roles: &Roles, by: &Pubkey, house: &mut House, mask: u8) -> Result<()> {
+    require_pauser(roles, by)?;
+    house.flags.unpause(mask);
+    Ok(())
+}
+
+/// Config manager sets the vault share one bet may win; returns the previous share.
+pub fn set_max_payout_bps(roles: &Roles, by: &Pubkey, house: &mut House, max_payout_bps: u16) -> Result<u16> {
+    require_role(roles, roles::CONFIG_MANAGER, by)?;
+    require!(max_payout_bps > 0 && max_payout_bps <= MAX_PAYOUT_BPS_CEILING, CommonError::BadParams);
+    Ok(std::mem::replace(&mut house.max_payout_bps, max_payout_bps))
+}
+
+/// Config manager sets the breaker's threshold (0 disables) and window.
+pub fn set_outflow_breaker(
+    roles: &Roles,
+    by: &Pubkey,
+    house: &mut House,
+    threshold: u64,
+    window_hours: u8,
+) -> Result<()> {
+    require_role(roles, roles::CONFIG_MANAGER, by)?;
+    require!(OutflowBreaker::valid_window(window_hours), CommonError::BadParams);
+    house.breaker.threshold = threshold;
+    house.breaker.window_hours = window_hours;
+    Ok(())
+}
+
+/// Super-admin builds a resolver set with every key enabled and nothing spent this epoch (the
+/// initial set, or the one [`propose_resolver_set`] queues).
+pub fn new_resolver_set(
+    roles: &Roles,
+    by: &Pubkey,
+    keys: &[Pubkey],
+    quorum: u8,
+    threshold: u64,
+    epoch_budget: u64,
+) -> Result<Cosigners> {
+    require_role(roles, roles::SUPER_ADMIN, by)?;
+    let mut cosigners = Cosigners::new(keys, quorum, threshold, epoch_budget).ok_or(CommonError::BadParams)?;
+    cosigners.epoch = Clock::get()?.epoch;
+    Ok(cosigners)
+}
+
+/// Super-admin queues a replacement resolver set (see [`PendingCosigners::propose`]); returns
+/// when it may be applied.
+pub fn propose_resolver_set(
+    roles: &Roles,
+    by: &Pubkey,
+    pending: &mut PendingCosigners,
+    keys: &[Pubkey],
+    quorum: u8,
+    threshold: u64,
+    epoch_budget: u64,
+) -> Result<i64> {
+    let set = new_resolver_set(roles, by, keys, quorum, threshold, epoch_budget)?;
+    Ok(pending.propose(set, Clock::get()?.unix_timestamp))
+}
+
+/// Super-admin drops the pending resolver-set change.
+pub fn cancel_resolver_set(roles: &Roles, by: &Pubkey, pending: &mut PendingCosigners) -> Result<()> {
+    require_role(roles, roles::SUPER_ADMIN, by)?;
+    Ok(pending.cancel()?)
+}
+
+/// Installs the pending resolver set once its timelock has elapsed.
+pub fn apply_resolver_set(live: &mut Cosigners, pending: &mut PendingCosigners) -> Result<()> {
+    Ok(pending.apply(live, Clock::get()?.unix_timestamp)?)
+}
+
+/// Super-admin sets one resolver key's enabled flag and per-epoch budget. Disabling it or
+/// lowering the budget applies at once (`None`); enabling it or raising the budget is queued
+/// behind the timelock (`Some(eta)`), see [`PendingCosigners::set_key`].
+pub fn set_resolver_key(
+    roles: &Roles,
+    by: &Pubkey,
+    live: &mut Cosigners,
+    pending: &mut PendingCosigners,
+    key: &Pubkey,
+    enabled: bool,
+    epoch_budget: u64,
+) -> Result<Option<i64>> {
+    require_role(roles, roles::SUPER_ADMIN, by)?;
+    Ok(pending.set_key(live, key, enabled, epoch_budget, Clock::get()?.unix_timestamp)?)
+}
+
+/// Pauser or super-admin disables one resolver key, live and pending; returns its epoch budget.
+pub fn disable_resolver_key(
+    roles: &Roles,
+    by: &Pubkey,
+    live: &mut Cosigners,
+    pending: &mut PendingCosigners,
+    key: &Pubkey,
+) -> Result<u64> {
+    require_pauser(roles, by)?;
+    Ok(pending.disable_key(live, key)?)
+}
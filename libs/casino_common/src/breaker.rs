@@ -0,0 +1,112 @@
+//! Net house-outflow circuit breaker.
+//!
+//! Every resolve records the stake it settles and the lamports it pays out of the vault in
+//! hourly [`RollingWindow`]s. Once payouts minus stakes over the last `window_hours` exceed
+//! `threshold`, the program pauses its locks and resolves; only a pauser or super-admin can
+//! resume them. A leaked resolver key can then drain roughly one threshold (plus the bet that
+//! trips the breaker) before settlement stops.
+
+use anchor_lang::prelude::*;
+
+use crate::window::{RollingWindow, BUCKETS};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct OutflowBreaker {
+    /// Lamports paid out of the vault by resolves.
+    pub paid: RollingWindow,
+    /// Stakes of the bets those resolves settled.
+    pub staked: RollingWindow,
+    /// Net outflow that trips the breaker; 0 disables it.
+    pub threshold: u64,
+    /// Hours the net outflow is summed over (`1..=BUCKETS`).
+    pub window_hours: u8,
+}
+
+impl OutflowBreaker {
+    pub const SIZE: usize = 2 * RollingWindow::SIZE + 8 + 1;
+
+    /// True if `window_hours` is a window the buckets can hold.
+    pub fn valid_window(window_hours: u8) -> bool {
+        (1..=BUCKETS).contains(&(window_hours as usize))
+    }
+
+    /// Payouts minus stakes over the window ending at `now` (0 while the house is ahead).
+    pub fn net_outflow(&mut self, now: i64) -> u64 {
+        let hours = self.window_hours as usize;
+        self.paid.total_last(now, hours).saturating_sub(self.staked.total_last(now, hours))
+    }
+
+    /// Record a settled bet. Returns the net outflow if it now exceeds the threshold; the
+    /// windows are then cleared so a resumed program starts counting from zero.
+    pub fn record(&mut self, now: i64, staked: u64, paid: u64) -> Option<u64> {
+        self.staked.add(now, staked);
+        self.paid.add(now, paid);
+        if self.threshold == 0 {
+            return None;
+        }
+        let net = self.net_outflow(now);
+        if net <= self.threshold {
+            return None;
+        }
+        self.paid = RollingWindow::default();
+        self.staked = RollingWindow::default();
+        Some(net)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T0: i64 = 1_700_000_000;
+
+    fn breaker(threshold: u64) -> OutflowBreaker {
+        OutflowBreaker { threshold, window_hours: 24, ..Default::default() }
+    }
+
+    #[test]
+    fn trips_only_above_the_threshold() {
+        let mut b = breaker(100);
+        assert_eq!(b.record(T0, 10, 110), None); // net 100: at the threshold
+        assert_eq!(b.record(T0, 0, 1), Some(101));
+    }
+
+    #[test]
+    fn stakes_offset_payouts() {
+        let mut b = breaker(100);
+        assert_eq!(b.record(T0, 500, 0), None);
+        assert_eq!(b.record(T0, 0, 600), None);
+        assert_eq!(b.record(T0, 0, 1), Some(101));
+    }
+
+    #[test]
+    fn trip_resets_the_windows() {
+        let mut b = breaker(100);
+        assert_eq!(b.record(T0, 0, 150), Some(150));
+        assert_eq!(b.net_outflow(T0), 0);
+        assert_eq!(b.record(T0, 0, 100), None);
+        assert_eq!(b.record(T0, 0, 1), Some(101));
+    }
+
+    #[test]
+    fn zero_threshold_never_trips() {
+        let mut b = breaker(0);
+        assert_eq!(b.record(T0, 0, u64::MAX), None);
+        assert_eq!(b.net_outflow(T0), u64::MAX);
+    }
+
+    #[test]
+    fn outflow_ages_out_of_the_window() {
+        let mut b = OutflowBreaker { threshold: 100, window_hours: 2, ..Default::default() };
+        assert_eq!(b.record(T0, 0, 80), None);
+        assert_eq!(b.record(T0 + 2 * 3_600, 0, 80), None);
+    }
+
+    #[test]
+    fn window_fits_the_buckets() {
+        assert!(OutflowBreaker::valid_window(1));
+        assert!(OutflowBreaker::valid_window(24));
+        assert!(!OutflowBreaker::valid_window(0));
+        assert!(!OutflowBreaker::valid_window(25));
+    }
+}
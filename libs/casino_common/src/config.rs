@@ -0,0 +1,58 @@
+//! Per-game limits kept on chain (one `GameConfig` PDA per game) instead of compile-time
+//! constants, so the config manager can change them without a program upgrade.
+//!
+//! `min_param`/`max_param` bound each game's main parameter: the dice target, the mines board
+//! side (rows and columns), the crash cash-out multiplier in bps, the plinko row count and the
+//! top slots line in bps (maximum only). Crash and slots also size a bet's exposure from
+//! `max_param`. `max_count` caps plinko balls per round. Coin flip uses neither.
+//!
+//! `house_edge_bps` is the edge every resolve prices its payout with on chain (see
+//! [`crate::payout`]); crash also derives its crash point from it. Plinko ignores it: its
+//! payout table carries the edge.
+//!
+//! Only locks read the config, `enabled` included. A lock copies what its resolve needs onto the
+//! pending account: the exposure, which was sized against the bankroll cap at lock, the house
+//! edge, and for crash the cash-out range. Disabling a game or changing its
+//! limits therefore never strands a bet that is already open.
+
+use anchor_lang::prelude::*;
+
+/// 100% in basis points; a house edge must stay below it.
+const BPS: u16 = 10_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct GameLimits {
+    /// Stake bounds in lamports (per ball for plinko, whose round total is also capped by
+    /// `max_bet`).
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub house_edge_bps: u16,
+    pub min_param: u32,
+    pub max_param: u32,
+    pub max_count: u16,
+}
+
+impl GameLimits {
+    pub const SIZE: usize = 8 + 8 + 2 + 4 + 4 + 2;
+
+    /// A non-empty stake range, an ordered parameter range and an edge below 100%.
+    pub fn is_valid(&self) -> bool {
+        self.min_bet > 0
+            && self.min_bet <= self.max_bet
+            && self.min_param <= self.max_param
+            && self.house_edge_bps < BPS
+    }
+
+    /// True if the parameter range lies within `lo..=hi`, the range the game's maths supports.
+    pub fn params_within(&self, lo: u32, hi: u32) -> bool {
+        self.min_param >= lo && self.max_param <= hi
+    }
+
+    pub fn bet_ok(&self, amount: u64) -> bool {
+        (self.min_bet..=self.max_bet).contains(&amount)
+    }
+
+    pub fn param_ok(&self, value: u32) -> bool {
+        (self.min_param..=self.max_param).contains(&value)
+    }
+}
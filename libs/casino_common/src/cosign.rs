@@ -0,0 +1,383 @@
+//! Resolver key registry with K-of-N co-signing for large payouts.
+//!
+//! Every backend signer instance has its own registered key, so instances no longer share one
+//! hot key. A key only counts while it is enabled and the payouts it signed this epoch stay
+//! within its budget; the payout is charged to every key counted for the resolve.
+//!
+//! A resolve paying at most `threshold` lamports needs one such key. Above it, the ed25519 run
+//! directly before the resolve (see [`crate::ed25519::load_preceding_signatures`]) must hold
+//! signatures over the same canonical message from at least `quorum` distinct keys, so one
+//! leaked backend key can no longer authorize a large win on its own.
+//!
+//! Changes that let the registry sign more (new keys, re-enabled keys, raised budgets, a new
+//! quorum or threshold) wait out [`ROLE_TIMELOCK_SECS`] in [`PendingCosigners`], like a role
+//! change. Disabling a key or lowering its budget takes effect at once, on the pending set too.
+
+use anchor_lang::prelude::*;
+
+use crate::ed25519::{self, SignedMessage};
+use crate::roles::ROLE_TIMELOCK_SECS;
+
+/// Most resolver keys a registry can hold.
+pub const MAX_COSIGNERS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CosignError {
+    /// No registered key signed the message (or the ed25519 run is unusable)
+    NotSigned,
+    /// The payout is above the threshold and fewer than `quorum` registered keys signed
+    NoQuorum,
+    /// Enough registered keys signed, but some are disabled or past their epoch budget
+    KeyUnavailable,
+    /// The key is not registered (in the set being changed)
+    UnknownKey,
+    /// No change pending, or its timelock has not elapsed
+    NotReady,
+}
+
+/// One backend signer instance.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct ResolverKey {
+    pub key: Pubkey,
+    pub enabled: bool,
+    /// Payout volume (lamports) the key may sign per epoch; 0 = unlimited.
+    pub epoch_budget: u64,
+    /// Payout volume signed in `Cosigners::epoch`.
+    pub spent: u64,
+}
+
+impl ResolverKey {
+    pub const SIZE: usize = 32 + 1 + 8 + 8;
+
+    /// True if the key may still sign a resolve paying `payout` this epoch.
+    pub fn can_sign(&self, payout: u64) -> bool {
+        self.enabled && (self.epoch_budget == 0 || self.spent.saturating_add(payout) <= self.epoch_budget)
+    }
+
+    /// True if `enabled` / `epoch_budget` would let the key sign more than it may now.
+    pub fn loosened_by(&self, enabled: bool, epoch_budget: u64) -> bool {
+        enabled && (!self.enabled || budget_raised(self.epoch_budget, epoch_budget))
+    }
+
+    /// Take over whichever of `enabled` / `epoch_budget` is stricter than the current setting.
+    fn tighten(&mut self, enabled: bool, epoch_budget: u64) {
+        self.enabled &= enabled;
+        if !budget_raised(self.epoch_budget, epoch_budget) {
+            self.epoch_budget = epoch_budget;
+        }
+    }
+}
+
+/// Budgets are lamports per epoch with 0 meaning unlimited.
+fn budget_raised(old: u64, new: u64) -> bool {
+    old != 0 && (new == 0 || new > old)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct Cosigners {
+    /// Registered resolver keys; the first `count` entries are used.
+    pub keys: [ResolverKey; MAX_COSIGNERS],
+    pub count: u8,
+    /// Distinct registered signatures a payout above `threshold` needs (K of `count`).
+    pub quorum: u8,
+    /// Largest payout (lamports) a single resolver key may still settle alone.
+    pub threshold: u64,
+    /// Epoch the `spent` counters belong to.
+    pub epoch: u64,
+}
+
+impl Cosigners {
+    pub const SIZE: usize = ResolverKey::SIZE * MAX_COSIGNERS + 1 + 1 + 8 + 8;
+
+    /// Build a registry of enabled `keys`, each with `epoch_budget`, or `None` if it is empty,
+    /// too large, holds duplicate or default keys, or `quorum` is not in `1..=keys.len()`.
+    pub fn new(keys: &[Pubkey], quorum: u8, threshold: u64, epoch_budget: u64) -> Option<Self> {
+        if keys.is_empty() || keys.len() > MAX_COSIGNERS {
+            return None;
+        }
+        if quorum == 0 || quorum as usize > keys.len() {
+            return None;
+        }
+        let mut set = Cosigners { count: keys.len() as u8, quorum, threshold, ..Default::default() };
+        for (i, key) in keys.iter().enumerate() {
+            if *key == Pubkey::default() || keys[..i].contains(key) {
+                return None;
+            }
+            set.keys[i] = ResolverKey { key: *key, enabled: true, epoch_budget, spent: 0 };
+        }
+        Some(set)
+    }
+
+    /// True if a resolve paying `payout` must be co-signed.
+    pub fn requires_quorum(&self, payout: u64) -> bool {
+        payout > self.threshold
+    }
+
+    /// Registered entry for `key`, if any.
+    pub fn entry_mut(&mut self, key: &Pubkey) -> Option<&mut ResolverKey> {
+        self.keys[..self.count as usize].iter_mut().find(|k| k.key == *key)
+    }
+
+    /// True if `key` is one of the registered resolver keys (enabled or not).
+    pub fn contains(&self, key: &Pubkey) -> bool {
+        self.keys[..self.count as usize].iter().any(|k| k.key == *key)
+    }
+
+    /// Start a new budget period if `epoch` moved on since the last charge.
+    pub fn roll_epoch(&mut self, epoch: u64) {
+        if epoch != self.epoch {
+            self.epoch = epoch;
+            for k in self.keys[..self.count as usize].iter_mut() {
+                k.spent = 0;
+            }
+        }
+    }
+
+    /// Check that enough usable keys signed exactly `message` in `sigs` for a resolve paying
+    /// `payout` (one key, or `quorum` above the threshold), then charge `payout` to each of them.
+    pub fn authorize(
+        &mut self,
+        sigs: &[SignedMessage],
+        message: &[u8],
+        payout: u64,
+        epoch: u64,
+    ) -> core::result::Result<(), CosignError> {
+        self.roll_epoch(epoch);
+        let needed = if self.requires_quorum(payout) { self.quorum as usize } else { 1 };
+        let count = self.count as usize;
+        let signed: Vec<usize> = (0..count)
+            .filter(|&i| ed25519::is_signed_by(sigs, &self.keys[i].key.to_bytes(), message))
+            .collect();
+        let usable: Vec<usize> = signed.iter().copied().filter(|&i| self.keys[i].can_sign(payout)).collect();
+        if needed == 0 || usable.len() < needed {
+            return Err(if signed.len() >= needed && needed > 0 {
+                CosignError::KeyUnavailable
+            } else if self.requires_quorum(payout) {
+                CosignError::NoQuorum
+            } else {
+                CosignError::NotSigned
+            });
+        }
+        for i in usable.into_iter().take(needed) {
+            self.keys[i].spent = self.keys[i].spent.saturating_add(payout);
+        }
+        Ok(())
+    }
+
+    /// Like [`Self::authorize`] for a registered key that signed the resolve transaction
+    /// itself. It counts as one signature, so it cannot settle a payout that needs a larger quorum.
+    pub fn authorize_signer(&mut self, key: &Pubkey, payout: u64, epoch: u64) -> core::result::Result<(), CosignError> {
+        self.roll_epoch(epoch);
+        if self.requires_quorum(payout) && self.quorum > 1 {
+            return Err(CosignError::NoQuorum);
+        }
+        let entry = self.entry_mut(key).ok_or(CosignError::NotSigned)?;
+        if !entry.can_sign(payout) {
+            return Err(CosignError::KeyUnavailable);
+        }
+        entry.spent = entry.spent.saturating_add(payout);
+        Ok(())
+    }
+}
+
+/// Registry change waiting out [`ROLE_TIMELOCK_SECS`]; `eta == 0` when nothing is pending.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct PendingCosigners {
+    pub set: Cosigners,
+    pub eta: i64,
+}
+
+impl PendingCosigners {
+    pub const SIZE: usize = Cosigners::SIZE + 8;
+
+    pub fn is_pending(&self) -> bool {
+        self.eta != 0
+    }
+
+    /// Queue `set` to replace the live registry (dropping any other pending change); returns the eta.
+    pub fn propose(&mut self, set: Cosigners, now: i64) -> i64 {
+        self.set = set;
+        self.eta = now.saturating_add(ROLE_TIMELOCK_SECS);
+        self.eta
+    }
+
+    /// Set `key`'s enabled flag and budget. A change that only restricts the key applies to
+    /// `live` at once (and to the pending set, so applying it cannot undo the restriction) and
+    /// returns `None`; one that lets the key sign more is queued on top of whatever is pending,
+    /// restarting the timelock, and returns `Some(eta)`.
+    pub fn set_key(
+        &mut self,
+        live: &mut Cosigners,
+        key: &Pubkey,
+        enabled: bool,
+        epoch_budget: u64,
+        now: i64,
+    ) -> core::result::Result<Option<i64>, CosignError> {
+        let entry = live.entry_mut(key).ok_or(CosignError::UnknownKey)?;
+        if !entry.loosened_by(enabled, epoch_budget) {
+            entry.enabled = enabled;
+            entry.epoch_budget = epoch_budget;
+            if let Some(queued) = self.queued_entry(key) {
+                queued.tighten(enabled, epoch_budget);
+            }
+            return Ok(None);
+        }
+        if !self.is_pending() {
+            self.set = *live;
+        }
+        let queued = self.set.entry_mut(key).ok_or(CosignError::UnknownKey)?;
+        queued.enabled = enabled;
+        queued.epoch_budget = epoch_budget;
+        self.eta = now.saturating_add(ROLE_TIMELOCK_SECS);
+        Ok(Some(self.eta))
+    }
+
+    /// Disable `key` in `live` and in the pending set; returns its live budget.
+    pub fn disable_key(&mut self, live: &mut Cosigners, key: &Pubkey) -> core::result::Result<u64, CosignError> {
+        let entry = live.entry_mut(key).ok_or(CosignError::UnknownKey)?;
+        entry.enabled = false;
+        let epoch_budget = entry.epoch_budget;
+        if let Some(queued) = self.queued_entry(key) {
+            queued.enabled = false;
+        }
+        Ok(epoch_budget)
+    }
+
+    pub fn cancel(&mut self) -> core::result::Result<(), CosignError> {
+        if !self.is_pending() {
+            return Err(CosignError::NotReady);
+        }
+        *self = Self::default();
+        Ok(())
+    }
+
+    /// Install the pending set into `live` once its timelock has elapsed. Keys that stay keep
+    /// what they already spent this epoch, so a change cannot reset a budget.
+    pub fn apply(&mut self, live: &mut Cosigners, now: i64) -> core::result::Result<(), CosignError> {
+        if !self.is_pending() || now < self.eta {
+            return Err(CosignError::NotReady);
+        }
+        let mut next = self.set;
+        next.epoch = live.epoch;
+        for k in next.keys[..next.count as usize].iter_mut() {
+            k.spent = live.entry_mut(&k.key).map_or(0, |e| e.spent);
+        }
+        *live = next;
+        *self = Self::default();
+        Ok(())
+    }
+
+    fn queued_entry(&mut self, key: &Pubkey) -> Option<&mut ResolverKey> {
+        if self.is_pending() {
+            self.set.entry_mut(key)
+        } else {
+            None
+        }
+    }
+}
+
+/// Authorize a resolve paying `payout` against the ed25519 run preceding the current
+/// instruction (see [`Cosigners::authorize`]); `message` is normally built with
+/// [`crate::message::resolve_message`]. Co-signatures may be spread over several ed25519
+/// instructions of that run.
+pub fn require_signers(
+    sys_ix_ai: &AccountInfo<'_>,
+    set: &mut Cosigners,
+    message: &[u8],
+    payout: u64,
+    epoch: u64,
+) -> core::result::Result<(), CosignError> {
+    let sigs = ed25519::load_preceding_signatures(sys_ix_ai).map_err(|_| CosignError::NotSigned)?;
+    set.authorize(&sigs, message, payout, epoch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(b: u8) -> Pubkey {
+        Pubkey::new_from_array([b; 32])
+    }
+
+    fn signed(by: u8, message: &[u8]) -> SignedMessage {
+        SignedMessage { ix_index: 0, pubkey: key(by), message: message.to_vec() }
+    }
+
+    #[test]
+    fn quorum_payout_needs_k_distinct_keys() {
+        let mut set = Cosigners::new(&[key(1), key(2), key(3)], 2, 100, 0).unwrap();
+        let one = [signed(1, b"m")];
+        assert_eq!(set.authorize(&one, b"m", 50, 0), Ok(()));
+        assert_eq!(set.authorize(&one, b"m", 101, 0), Err(CosignError::NoQuorum));
+        assert_eq!(set.authorize(&[signed(1, b"other")], b"m", 50, 0), Err(CosignError::NotSigned));
+        let two = [signed(1, b"m"), signed(3, b"m")];
+        assert_eq!(set.authorize(&two, b"m", 101, 0), Ok(()));
+    }
+
+    #[test]
+    fn direct_signer_is_one_signature() {
+        let mut set = Cosigners::new(&[key(1), key(2)], 2, 100, 150).unwrap();
+        assert_eq!(set.authorize_signer(&key(9), 10, 0), Err(CosignError::NotSigned));
+        assert_eq!(set.authorize_signer(&key(1), 101, 0), Err(CosignError::NoQuorum));
+        assert_eq!(set.authorize_signer(&key(1), 100, 0), Ok(()));
+        // 100 of the 150 budget spent this epoch; the next epoch starts from zero
+        assert_eq!(set.authorize_signer(&key(1), 100, 0), Err(CosignError::KeyUnavailable));
+        assert_eq!(set.authorize_signer(&key(1), 100, 1), Ok(()));
+        set.entry_mut(&key(2)).unwrap().enabled = false;
+        assert_eq!(set.authorize_signer(&key(2), 1, 1), Err(CosignError::KeyUnavailable));
+    }
+
+    const T0: i64 = 1_700_000_000;
+
+    #[test]
+    fn only_restrictions_apply_at_once() {
+        let mut live = Cosigners::new(&[key(1), key(2)], 1, 100, 500).unwrap();
+        let mut pending = PendingCosigners::default();
+        // lower a budget, disable a key: immediate
+        assert_eq!(pending.set_key(&mut live, &key(1), true, 300, T0), Ok(None));
+        assert_eq!(pending.disable_key(&mut live, &key(2)), Ok(500));
+        assert_eq!(live.keys[0].epoch_budget, 300);
+        assert!(!live.keys[1].enabled && !pending.is_pending());
+        // re-enable, raise a budget, lift a budget to unlimited: queued
+        let eta = T0 + ROLE_TIMELOCK_SECS;
+        assert_eq!(pending.set_key(&mut live, &key(2), true, 500, T0), Ok(Some(eta)));
+        assert_eq!(pending.set_key(&mut live, &key(1), true, 0, T0), Ok(Some(eta)));
+        assert!(!live.keys[1].enabled && live.keys[0].epoch_budget == 300);
+        assert_eq!(pending.set_key(&mut live, &key(9), false, 0, T0), Err(CosignError::UnknownKey));
+
+        assert_eq!(pending.apply(&mut live, eta - 1), Err(CosignError::NotReady));
+        assert_eq!(pending.apply(&mut live, eta), Ok(()));
+        assert!(live.keys[1].enabled && live.keys[0].epoch_budget == 0 && !pending.is_pending());
+    }
+
+    #[test]
+    fn restriction_reaches_the_pending_set() {
+        let mut live = Cosigners::new(&[key(1), key(2)], 1, 100, 500).unwrap();
+        let mut pending = PendingCosigners::default();
+        let next = Cosigners::new(&[key(1), key(2), key(3)], 2, 100, 1_000).unwrap();
+        let eta = pending.propose(next, T0);
+        assert_eq!(pending.disable_key(&mut live, &key(2)), Ok(500));
+        assert_eq!(pending.set_key(&mut live, &key(1), true, 200, T0), Ok(None));
+        assert_eq!(pending.apply(&mut live, eta), Ok(()));
+        assert_eq!(live.count, 3);
+        assert!(!live.keys[1].enabled);
+        assert_eq!(live.keys[0].epoch_budget, 200);
+        assert_eq!(live.keys[2].epoch_budget, 1_000);
+    }
+
+    #[test]
+    fn apply_keeps_what_was_spent() {
+        let mut live = Cosigners::new(&[key(1)], 1, 100, 150).unwrap();
+        live.authorize_signer(&key(1), 100, 7).unwrap();
+        let mut pending = PendingCosigners::default();
+        let eta = pending.propose(Cosigners::new(&[key(1), key(2)], 1, 100, 150).unwrap(), T0);
+        assert_eq!(pending.cancel(), Ok(()));
+        assert_eq!(pending.cancel(), Err(CosignError::NotReady));
+        assert_eq!(pending.apply(&mut live, eta), Err(CosignError::NotReady));
+
+        let eta = pending.propose(Cosigners::new(&[key(1), key(2)], 1, 100, 150).unwrap(), T0);
+        pending.apply(&mut live, eta).unwrap();
+        assert_eq!((live.epoch, live.keys[0].spent, live.keys[1].spent), (7, 100, 0));
+        assert_eq!(live.authorize_signer(&key(1), 100, 7), Err(CosignError::KeyUnavailable));
+    }
+}
@@ -0,0 +1,89 @@
+//! Who may run a program's one-time initializers (`init_admin`, vault creation).
+//!
+//! Either the compile-time [`DEPLOYER`] key, or the program's current upgrade authority as
+//! recorded in its `ProgramData` account. Anyone else racing the deployer right after a deploy
+//! is rejected, so the admin config can't be claimed by a stranger.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
+/// BPF upgradeable loader, owner of every ProgramData account.
+const UPGRADEABLE_LOADER_ID: Pubkey = pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+
+/// Deployer wallet allowed to initialize every casino program.
+pub const DEPLOYER: Pubkey = pubkey!("5jHZt8Jc6rahAdVVuwbBYRaNJ8XfN6g89jKP5jpvJq3");
+
+/// Upgrade authority recorded in `program_id`'s ProgramData, if `ai` is that account.
+pub fn upgrade_authority(program_id: &Pubkey, ai: &AccountInfo<'_>) -> Option<Pubkey> {
+    let (expected, _) = Pubkey::find_program_address(&[program_id.as_ref()], &UPGRADEABLE_LOADER_ID);
+    if *ai.key != expected || *ai.owner != UPGRADEABLE_LOADER_ID {
+        return None;
+    }
+    let data = ai.try_borrow_data().ok()?;
+    ProgramData::try_deserialize(&mut &data[..])
+        .ok()?
+        .upgrade_authority_address
+}
+
+/// True if `signer` is [`DEPLOYER`] or the upgrade authority shown by `program_data`.
+pub fn may_initialize(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    program_data: Option<&AccountInfo<'_>>,
+) -> bool {
+    if *signer == DEPLOYER {
+        return true;
+    }
+    program_data
+        .and_then(|ai| upgrade_authority(program_id, ai))
+        .is_some_and(|authority| authority == *signer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: Pubkey = Pubkey::new_from_array([1u8; 32]);
+    const AUTHORITY: Pubkey = Pubkey::new_from_array([2u8; 32]);
+
+    // UpgradeableLoaderState::ProgramData: tag 3 (u32) | slot u64 | Option<Pubkey> | program bytes
+    fn program_data(authority: Option<Pubkey>) -> Vec<u8> {
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&42u64.to_le_bytes());
+        match authority {
+            Some(key) => {
+                data.push(1);
+                data.extend_from_slice(key.as_ref());
+            }
+            None => data.push(0),
+        }
+        data
+    }
+
+    fn check(signer: &Pubkey, key: &Pubkey, owner: &Pubkey, mut data: Vec<u8>) -> bool {
+        let mut lamports = 0u64;
+        let ai = AccountInfo::new(key, false, false, &mut lamports, &mut data, owner, false, 0);
+        may_initialize(&PROGRAM, signer, Some(&ai))
+    }
+
+    #[test]
+    fn upgrade_authority_may_initialize() {
+        let (pd, _) = Pubkey::find_program_address(&[PROGRAM.as_ref()], &UPGRADEABLE_LOADER_ID);
+        let stranger = Pubkey::new_from_array([3u8; 32]);
+
+        assert!(check(&AUTHORITY, &pd, &UPGRADEABLE_LOADER_ID, program_data(Some(AUTHORITY))));
+        assert!(!check(&stranger, &pd, &UPGRADEABLE_LOADER_ID, program_data(Some(AUTHORITY))));
+        // immutable program: no authority left to match
+        assert!(!check(&AUTHORITY, &pd, &UPGRADEABLE_LOADER_ID, program_data(None)));
+        // another program's ProgramData, or a look-alike not owned by the loader
+        let (other_pd, _) = Pubkey::find_program_address(&[stranger.as_ref()], &UPGRADEABLE_LOADER_ID);
+        assert!(!check(&AUTHORITY, &other_pd, &UPGRADEABLE_LOADER_ID, program_data(Some(AUTHORITY))));
+        assert!(!check(&AUTHORITY, &pd, &PROGRAM, program_data(Some(AUTHORITY))));
+    }
+
+    #[test]
+    fn deployer_needs_no_program_data() {
+        assert!(may_initialize(&PROGRAM, &DEPLOYER, None));
+        assert!(!may_initialize(&PROGRAM, &AUTHORITY, None));
+    }
+}
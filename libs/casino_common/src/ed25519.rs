@@ -0,0 +1,278 @@
+//! Parser for ed25519 precompile pre-instructions.
+//!
+//! The ed25519 program verifies signatures over bytes that each offsets entry points at,
+//! and those bytes may live in *any* instruction of the transaction. Reading the pubkey and
+//! message out of the ed25519 instruction's own data is therefore only sound when every
+//! `*_instruction_index` field refers to that same instruction, which this parser enforces.
+//!
+//! Resolves consume the contiguous run of ed25519 instructions placed directly before them
+//! (see [`load_preceding_signatures`]). Two resolves in one transaction can never share that
+//! run, so each signature is consumed by at most one resolve.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    ed25519_program,
+    instruction::Instruction,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+
+/// `instruction_index` value meaning "this ed25519 instruction".
+pub const CURRENT_IX_INDEX: u16 = u16::MAX;
+
+const PUBKEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+// num_signatures u8 + padding u8
+const HEADER_LEN: usize = 2;
+// sig_off, sig_ix, pk_off, pk_ix, msg_off, msg_sz, msg_ix (u16 each)
+const OFFSETS_LEN: usize = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ed25519Error {
+    /// No ed25519 instruction where one was required
+    Missing,
+    /// Instruction data is truncated or an offset points outside of it
+    Malformed,
+    /// An offsets entry reads its data from another instruction
+    ForeignInstructionIndex,
+    /// No parsed signature matched the expected pubkey and message
+    NotSigned,
+}
+
+/// One signature entry of an ed25519 instruction: who signed, and what.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedMessage {
+    /// Index of the ed25519 instruction in the transaction
+    pub ix_index: u16,
+    pub pubkey: Pubkey,
+    pub message: Vec<u8>,
+}
+
+fn rd_u16(d: &[u8], off: usize) -> core::result::Result<u16, Ed25519Error> {
+    d.get(off..off + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or(Ed25519Error::Malformed)
+}
+
+fn slice(d: &[u8], off: u16, len: usize) -> core::result::Result<&[u8], Ed25519Error> {
+    let off = off as usize;
+    d.get(off..off + len).ok_or(Ed25519Error::Malformed)
+}
+
+/// Parse every signature entry of the ed25519 instruction `ix`, located at `ix_index`.
+///
+/// Entries whose signature, pubkey or message index is neither [`CURRENT_IX_INDEX`] nor
+/// `ix_index` are rejected, so the returned bytes are exactly the bytes that were verified.
+pub fn parse_ed25519_ix(
+    ix: &Instruction,
+    ix_index: u16,
+) -> core::result::Result<Vec<SignedMessage>, Ed25519Error> {
+    if ix.program_id != ed25519_program::id() {
+        return Err(Ed25519Error::Missing);
+    }
+    let d = &ix.data;
+    let num = *d.first().ok_or(Ed25519Error::Malformed)? as usize;
+    if num == 0 || d.len() < HEADER_LEN + num * OFFSETS_LEN {
+        return Err(Ed25519Error::Malformed);
+    }
+
+    let own = |idx: u16| idx == CURRENT_IX_INDEX || idx == ix_index;
+    let mut out = Vec::with_capacity(num);
+    for i in 0..num {
+        let base = HEADER_LEN + i * OFFSETS_LEN;
+        let sig_off = rd_u16(d, base)?;
+        let sig_ix = rd_u16(d, base + 2)?;
+        let pk_off = rd_u16(d, base + 4)?;
+        let pk_ix = rd_u16(d, base + 6)?;
+        let msg_off = rd_u16(d, base + 8)?;
+        let msg_sz = rd_u16(d, base + 10)?;
+        let msg_ix = rd_u16(d, base + 12)?;
+
+        if !(own(sig_ix) && own(pk_ix) && own(msg_ix)) {
+            return Err(Ed25519Error::ForeignInstructionIndex);
+        }
+        slice(d, sig_off, SIGNATURE_LEN)?;
+        let pk = slice(d, pk_off, PUBKEY_LEN)?;
+        let msg = slice(d, msg_off, msg_sz as usize)?;
+
+        out.push(SignedMessage {
+            ix_index,
+            pubkey: Pubkey::try_from(pk).map_err(|_| Ed25519Error::Malformed)?,
+            message: msg.to_vec(),
+        });
+    }
+    Ok(out)
+}
+
+/// Parse the contiguous run of ed25519 instructions directly before the current instruction.
+///
+/// This is the set of signatures the current instruction consumes; the run ends at the first
+/// non-ed25519 instruction, so it cannot overlap the run consumed by any other instruction.
+pub fn load_preceding_signatures(
+    sys_ix_ai: &AccountInfo<'_>,
+) -> core::result::Result<Vec<SignedMessage>, Ed25519Error> {
+    let cur = load_current_index_checked(sys_ix_ai).map_err(|_| Ed25519Error::Missing)?;
+    preceding_run(cur, |idx| {
+        load_instruction_at_checked(idx as usize, sys_ix_ai).map_err(|_| Ed25519Error::Missing)
+    })
+}
+
+/// [`load_preceding_signatures`] for the instruction at `cur`, with `load` returning the
+/// transaction's instruction at a given index.
+fn preceding_run(
+    cur: u16,
+    load: impl Fn(u16) -> core::result::Result<Instruction, Ed25519Error>,
+) -> core::result::Result<Vec<SignedMessage>, Ed25519Error> {
+    let mut out = Vec::new();
+    let mut idx = cur;
+    while idx > 0 {
+        idx -= 1;
+        let ix = load(idx)?;
+        if ix.program_id != ed25519_program::id() {
+            break;
+        }
+        out.extend(parse_ed25519_ix(&ix, idx)?);
+    }
+    if out.is_empty() {
+        return Err(Ed25519Error::Missing);
+    }
+    Ok(out)
+}
+
+/// True if `sigs` contains a signature by `pubkey` over exactly `message`.
+pub fn is_signed_by(sigs: &[SignedMessage], pubkey: &[u8; 32], message: &[u8]) -> bool {
+    sigs.iter()
+        .any(|s| s.pubkey.as_ref() == pubkey.as_slice() && s.message == message)
+}
+
+/// Require that the ed25519 run preceding the current instruction contains a signature by
+/// `pubkey` over exactly `message`.
+pub fn require_signed_by(
+    sys_ix_ai: &AccountInfo<'_>,
+    pubkey: &[u8; 32],
+    message: &[u8],
+) -> core::result::Result<(), Ed25519Error> {
+    let sigs = load_preceding_signatures(sys_ix_ai)?;
+    if is_signed_by(&sigs, pubkey, message) {
+        Ok(())
+    } else {
+        Err(Ed25519Error::NotSigned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(b: u8) -> Pubkey {
+        Pubkey::new_from_array([b; 32])
+    }
+
+    /// ed25519 instruction holding one entry per `(pubkey, message)`, laid out as
+    /// header | offsets... | (pubkey | signature | message)..., every index set to `ix_ref`.
+    fn ed_ix(entries: &[(Pubkey, &[u8])], ix_ref: u16) -> Instruction {
+        let mut offsets = vec![entries.len() as u8, 0];
+        let mut payload = Vec::new();
+        let start = HEADER_LEN + entries.len() * OFFSETS_LEN;
+        for (pubkey, message) in entries {
+            let pk_off = (start + payload.len()) as u16;
+            payload.extend_from_slice(pubkey.as_ref());
+            let sig_off = (start + payload.len()) as u16;
+            payload.extend_from_slice(&[0xee; SIGNATURE_LEN]);
+            let msg_off = (start + payload.len()) as u16;
+            payload.extend_from_slice(message);
+            for v in [sig_off, ix_ref, pk_off, ix_ref, msg_off, message.len() as u16, ix_ref] {
+                offsets.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        offsets.extend_from_slice(&payload);
+        Instruction { program_id: ed25519_program::id(), accounts: vec![], data: offsets }
+    }
+
+    fn other_ix() -> Instruction {
+        Instruction { program_id: Pubkey::new_unique(), accounts: vec![], data: vec![] }
+    }
+
+    /// Set the `field`-th u16 of the first offsets entry.
+    fn set_offset(ix: &mut Instruction, field: usize, v: u16) {
+        let at = HEADER_LEN + field * 2;
+        ix.data[at..at + 2].copy_from_slice(&v.to_le_bytes());
+    }
+
+    #[test]
+    fn accepts_own_index_and_current_marker() {
+        let own = parse_ed25519_ix(&ed_ix(&[(key(1), b"m")], 3), 3).unwrap();
+        let marker = parse_ed25519_ix(&ed_ix(&[(key(1), b"m")], CURRENT_IX_INDEX), 3).unwrap();
+        let expected = vec![SignedMessage { ix_index: 3, pubkey: key(1), message: b"m".to_vec() }];
+        assert_eq!(own, expected);
+        assert_eq!(marker, expected);
+    }
+
+    #[test]
+    fn rejects_foreign_instruction_index() {
+        // signature, pubkey and message index in turn point at instruction 0
+        for field in [1, 3, 6] {
+            let mut ix = ed_ix(&[(key(1), b"m")], CURRENT_IX_INDEX);
+            set_offset(&mut ix, field, 0);
+            assert_eq!(parse_ed25519_ix(&ix, 3), Err(Ed25519Error::ForeignInstructionIndex));
+        }
+    }
+
+    #[test]
+    fn returns_every_signature_as_a_pair() {
+        let ix = ed_ix(&[(key(1), b"first"), (key(2), b"second")], CURRENT_IX_INDEX);
+        let sigs = parse_ed25519_ix(&ix, 0).unwrap();
+        let pairs: Vec<(Pubkey, &[u8])> = sigs.iter().map(|s| (s.pubkey, s.message.as_slice())).collect();
+        assert_eq!(pairs, vec![(key(1), &b"first"[..]), (key(2), &b"second"[..])]);
+        assert!(is_signed_by(&sigs, &key(2).to_bytes(), b"second"));
+        assert!(!is_signed_by(&sigs, &key(1).to_bytes(), b"second"));
+    }
+
+    #[test]
+    fn rejects_truncated_or_out_of_range_data() {
+        let ix = ed_ix(&[(key(1), b"m")], CURRENT_IX_INDEX);
+        for len in [0, 1, HEADER_LEN + OFFSETS_LEN - 1, ix.data.len() - 1] {
+            let mut cut = ix.clone();
+            cut.data.truncate(len);
+            assert_eq!(parse_ed25519_ix(&cut, 0), Err(Ed25519Error::Malformed), "len {len}");
+        }
+        let mut zero = ix.clone();
+        zero.data[0] = 0;
+        assert_eq!(parse_ed25519_ix(&zero, 0), Err(Ed25519Error::Malformed));
+        // signature, pubkey and message offset in turn past the end; then an oversized message
+        for (field, v) in [(0, u16::MAX), (2, u16::MAX), (4, u16::MAX), (5, u16::MAX)] {
+            let mut bad = ix.clone();
+            set_offset(&mut bad, field, v);
+            assert_eq!(parse_ed25519_ix(&bad, 0), Err(Ed25519Error::Malformed), "field {field}");
+        }
+        assert_eq!(parse_ed25519_ix(&other_ix(), 0), Err(Ed25519Error::Missing));
+    }
+
+    #[test]
+    fn each_instruction_consumes_only_its_own_run() {
+        // 0: ed(A) | 1: other | 2: ed(B) | 3: ed(C) | 4: resolve | 5: ed(D) | 6: resolve | 7: resolve
+        let tx = [
+            ed_ix(&[(key(0xa), b"a")], CURRENT_IX_INDEX),
+            other_ix(),
+            ed_ix(&[(key(0xb), b"b")], CURRENT_IX_INDEX),
+            ed_ix(&[(key(0xc), b"c")], 3),
+            other_ix(),
+            ed_ix(&[(key(0xd), b"d")], CURRENT_IX_INDEX),
+            other_ix(),
+            other_ix(),
+        ];
+        let load = |i: u16| tx.get(i as usize).cloned().ok_or(Ed25519Error::Missing);
+        let signers = |cur: u16| -> Vec<Pubkey> {
+            preceding_run(cur, load).unwrap().iter().map(|s| s.pubkey).collect()
+        };
+        assert_eq!(signers(4), vec![key(0xc), key(0xb)]);
+        assert_eq!(signers(6), vec![key(0xd)]);
+        assert_eq!(preceding_run(7, load), Err(Ed25519Error::Missing));
+        assert_eq!(preceding_run(0, load), Err(Ed25519Error::Missing));
+
+        // a foreign index anywhere in the run fails the whole run
+        let mut bad = tx.clone();
+        set_offset(&mut bad[2], 6, 0);
+        let load_bad = |i: u16| bad.get(i as usize).cloned().ok_or(Ed25519Error::Missing);
+        assert_eq!(preceding_run(4, load_bad), Err(Ed25519Error::ForeignInstructionIndex));
+    }
+}
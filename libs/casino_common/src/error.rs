@@ -0,0 +1,55 @@
+//! Errors raised by the shared instruction logic ([`crate::admin`], [`crate::house`],
+//! [`crate::randomness::lock`]). Codes start at 7000, above every program's own `#[error_code]`
+//! range, so a client can tell them apart from game-specific failures.
+
+use anchor_lang::prelude::*;
+
+use crate::cosign::CosignError;
+use crate::randomness::RandomnessError;
+use crate::roles::RoleError;
+
+#[error_code(offset = 7000)]
+pub enum CommonError {
+    #[msg("Signer does not hold the required role")] Unauthorized,
+    #[msg("Bad params")] BadParams,
+    #[msg("Unknown role")] BadRole,
+    #[msg("No change pending or timelock not elapsed")] NotReady,
+    #[msg("Potential payout exceeds the bankroll limit")] PayoutLimit,
+    #[msg("Vault cannot cover this bet")] ExposureLimit,
+    #[msg("No server seed commitment posted")] NoSeedCommit,
+    #[msg("Server seed does not open the commitment")] BadReveal,
+    #[msg("Randomness not available for this bet")] RandomnessUnavailable,
+    #[msg("Invalid ed25519 pre-instruction")] InvalidEd25519,
+    #[msg("Payout needs more resolver co-signatures")] NotEnoughCosigners,
+    #[msg("Resolver key disabled or over its epoch budget")] ResolverKeyUnavailable,
+}
+
+impl From<RoleError> for Error {
+    fn from(e: RoleError) -> Self {
+        match e {
+            RoleError::BadRole => error!(CommonError::BadRole),
+            RoleError::NotReady => error!(CommonError::NotReady),
+        }
+    }
+}
+
+impl From<CosignError> for Error {
+    fn from(e: CosignError) -> Self {
+        match e {
+            CosignError::NotSigned => error!(CommonError::InvalidEd25519),
+            CosignError::NoQuorum => error!(CommonError::NotEnoughCosigners),
+            CosignError::KeyUnavailable => error!(CommonError::ResolverKeyUnavailable),
+            CosignError::UnknownKey => error!(CommonError::BadParams),
+            CosignError::NotReady => error!(CommonError::NotReady),
+        }
+    }
+}
+
+impl From<RandomnessError> for Error {
+    fn from(e: RandomnessError) -> Self {
+        match e {
+            RandomnessError::BadReveal => error!(CommonError::BadReveal),
+            _ => error!(CommonError::RandomnessUnavailable),
+        }
+    }
+}
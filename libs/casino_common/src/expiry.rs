@@ -0,0 +1,99 @@
+//! Bet deadlines checked against the on-chain clock.
+//!
+//! Every pending bet carries a unix deadline (`expiry_unix`) and an optional slot deadline
+//! (`expiry_slot`, [`NO_SLOT_DEADLINE`] when unused). A bet stops being resolvable as soon as
+//! either one has passed and becomes refundable once either one is past by its grace period,
+//! so drift between the cluster's unix timestamp and slot progress can neither strand nor
+//! extend a bet.
+
+use anchor_lang::prelude::*;
+
+/// `expiry_slot` value meaning "unix deadline only".
+pub const NO_SLOT_DEADLINE: u64 = 0;
+
+/// Lock-time check: `now < expiry_unix <= now + max_ttl_secs`, and the same against the
+/// current slot for a slot deadline when one is set.
+pub fn lock_window_ok(
+    clock: &Clock,
+    expiry_unix: i64,
+    max_ttl_secs: i64,
+    expiry_slot: u64,
+    max_ttl_slots: u64,
+) -> bool {
+    let now = clock.unix_timestamp;
+    let unix_ok = now < expiry_unix && expiry_unix <= now.saturating_add(max_ttl_secs);
+    let slot_ok = expiry_slot == NO_SLOT_DEADLINE
+        || (clock.slot < expiry_slot && expiry_slot <= clock.slot.saturating_add(max_ttl_slots));
+    unix_ok && slot_ok
+}
+
+/// True once either deadline has passed.
+pub fn is_expired(clock: &Clock, expiry_unix: i64, expiry_slot: u64) -> bool {
+    clock.unix_timestamp > expiry_unix
+        || (expiry_slot != NO_SLOT_DEADLINE && clock.slot > expiry_slot)
+}
+
+/// True once either deadline is past by its grace period.
+pub fn is_refundable(
+    clock: &Clock,
+    expiry_unix: i64,
+    expiry_slot: u64,
+    grace_secs: i64,
+    grace_slots: u64,
+) -> bool {
+    clock.unix_timestamp > expiry_unix.saturating_add(grace_secs)
+        || (expiry_slot != NO_SLOT_DEADLINE && clock.slot > expiry_slot.saturating_add(grace_slots))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+    const SLOT: u64 = 250_000_000;
+
+    fn clock(unix_timestamp: i64, slot: u64) -> Clock {
+        Clock { unix_timestamp, slot, ..Default::default() }
+    }
+
+    #[test]
+    fn lock_window_bounds() {
+        let c = clock(NOW, SLOT);
+        assert!(!lock_window_ok(&c, NOW, 300, NO_SLOT_DEADLINE, 0));
+        assert!(lock_window_ok(&c, NOW + 1, 300, NO_SLOT_DEADLINE, 0));
+        assert!(lock_window_ok(&c, NOW + 300, 300, NO_SLOT_DEADLINE, 0));
+        assert!(!lock_window_ok(&c, NOW + 301, 300, NO_SLOT_DEADLINE, 0));
+
+        assert!(!lock_window_ok(&c, NOW + 60, 300, SLOT, 750));
+        assert!(lock_window_ok(&c, NOW + 60, 300, SLOT + 1, 750));
+        assert!(lock_window_ok(&c, NOW + 60, 300, SLOT + 750, 750));
+        assert!(!lock_window_ok(&c, NOW + 60, 300, SLOT + 751, 750));
+        // a valid slot deadline does not rescue a bad unix deadline
+        assert!(!lock_window_ok(&c, NOW, 300, SLOT + 1, 750));
+    }
+
+    #[test]
+    fn expiry_at_either_deadline() {
+        assert!(!is_expired(&clock(NOW, SLOT), NOW, NO_SLOT_DEADLINE));
+        assert!(is_expired(&clock(NOW + 1, SLOT), NOW, NO_SLOT_DEADLINE));
+        // no slot deadline: the slot never expires the bet
+        assert!(!is_expired(&clock(NOW, u64::MAX), NOW, NO_SLOT_DEADLINE));
+
+        assert!(!is_expired(&clock(NOW, SLOT), NOW + 60, SLOT));
+        assert!(is_expired(&clock(NOW, SLOT + 1), NOW + 60, SLOT));
+    }
+
+    #[test]
+    fn refund_after_the_grace_period() {
+        let (grace_secs, grace_slots) = (600, 1_500);
+        let refundable = |unix, slot, expiry_slot| {
+            is_refundable(&clock(unix, slot), NOW, expiry_slot, grace_secs, grace_slots)
+        };
+        assert!(!refundable(NOW + 600, SLOT, NO_SLOT_DEADLINE));
+        assert!(refundable(NOW + 601, SLOT, NO_SLOT_DEADLINE));
+        assert!(!refundable(NOW, u64::MAX, NO_SLOT_DEADLINE));
+
+        assert!(!refundable(NOW, SLOT + 1_500, SLOT));
+        assert!(refundable(NOW, SLOT + 1_501, SLOT));
+    }
+}
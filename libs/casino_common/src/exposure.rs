@@ -0,0 +1,133 @@
+//! Open exposure: the most the house vault could still owe on bets that are locked but not
+//! yet resolved or refunded.
+//!
+//! A lock records a worst-case payout for its bet and adds it to a running total; resolve and
+//! refund release it. The bounds below are fair-odds multiples (the inverse chance of the best
+//! outcome), which no house-edged payout can exceed, except plinko, whose bound is the top
+//! multiplier of its payout table. Resolve pays the [`crate::payout`] value, which stays within.
+//! A lock is only accepted while the vault, less a reserve, covers every open bet plus the new
+//! one, and no single bet may stand to win more than a basis-point share of the vault
+//! (`max_payout`).
+
+const BPS: u128 = 10_000;
+
+fn scale(amount: u64, num: u128, den: u128) -> u64 {
+    (amount as u128).saturating_mul(num).div_ceil(den).min(u64::MAX as u128) as u64
+}
+
+/// Dice (`fair::dice_roll` in `1..=100`): under wins on `roll < target`, over on `roll > target`.
+pub fn dice(amount: u64, bet_type: u8, target: u8) -> u64 {
+    let wins = if bet_type == 0 { target.saturating_sub(1) } else { 100u8.saturating_sub(target) };
+    if wins == 0 {
+        return 0;
+    }
+    scale(amount, 100, wins as u128)
+}
+
+/// Even-money coin flip (against the house or a matched opponent entry).
+pub fn flip(amount: u64) -> u64 {
+    amount.saturating_mul(2)
+}
+
+/// Stake times a multiplier in bps (crash cash-out cap, top slots line).
+pub fn multiple(amount: u64, mult_bps: u32) -> u64 {
+    scale(amount, mult_bps as u128, BPS)
+}
+
+/// Mines: clearing every safe tile of a `cells`-tile board hiding `mines` mines has chance
+/// `1 / C(cells, mines)`, so no cash-out pays more than `C(cells, mines)` times the stake.
+pub fn mines(amount: u64, cells: u8, mines: u8) -> u64 {
+    let n = cells.min(64) as u128;
+    let k = (mines as u128).min(n);
+    let k = k.min(n - k);
+    // C(64, 32) < 2^61, so the running product never overflows u128
+    let c = (0..k).fold(1u128, |c, i| c * (n - i) / (i + 1));
+    scale(amount, c, 1)
+}
+
+/// Top (edge-bucket) multiplier in bps of the plinko payout table, indexed by `rows - 8` and
+/// difficulty (0 easy ..= 5 extreme); the rest of the table is [`crate::payout::PLINKO_INNER_MULT_BPS`].
+pub const PLINKO_MAX_MULT_BPS: [[u32; 6]; 9] = [
+    [56_000, 84_000, 130_000, 290_000, 600_000, 1_200_000],            // 8 rows
+    [56_000, 90_000, 180_000, 430_000, 900_000, 1_800_000],            // 9
+    [89_000, 120_000, 220_000, 760_000, 1_500_000, 3_000_000],         // 10
+    [84_000, 140_000, 240_000, 1_200_000, 2_400_000, 5_000_000],       // 11
+    [100_000, 180_000, 330_000, 1_700_000, 3_500_000, 7_000_000],      // 12
+    [81_000, 220_000, 430_000, 2_600_000, 5_200_000, 10_000_000],      // 13
+    [71_000, 260_000, 580_000, 4_200_000, 8_400_000, 16_000_000],      // 14
+    [150_000, 320_000, 880_000, 6_200_000, 12_000_000, 25_000_000],    // 15
+    [160_000, 400_000, 1_100_000, 10_000_000, 20_000_000, 40_000_000], // 16
+];
+
+/// Plinko: `total_stake` spread over any number of balls pays at most the table's top multiplier
+/// for `rows` and `difficulty`. Outside the table, the fair-odds bound `total_stake * 2^rows`
+/// (an edge bucket is reached with chance `2^-rows`).
+pub fn plinko(total_stake: u64, rows: u8, difficulty: u8) -> u64 {
+    let top = PLINKO_MAX_MULT_BPS
+        .get(rows.wrapping_sub(8) as usize)
+        .and_then(|by_difficulty| by_difficulty.get(difficulty as usize));
+    match top {
+        Some(&mult_bps) => multiple(total_stake, mult_bps),
+        None => scale(total_stake, 1u128 << rows.min(64), 1),
+    }
+}
+
+/// True if `vault_lamports`, less `reserve`, still covers `open` plus `add`.
+pub fn covers(vault_lamports: u64, reserve: u64, open: u64, add: u64) -> bool {
+    open.saturating_add(add) <= vault_lamports.saturating_sub(reserve)
+}
+
+/// Largest payout one bet may carry: `max_payout_bps` of the vault balance above `rent`.
+pub fn max_payout(vault_lamports: u64, rent: u64, max_payout_bps: u16) -> u64 {
+    ((vault_lamports.saturating_sub(rent) as u128) * max_payout_bps as u128 / BPS) as u64
+}
+
+/// Largest stake whose `worst(stake)` stays within `cap`. `worst` must be non-decreasing and
+/// at least the stake (true of every bound above), so the answer lies in `0..=cap`.
+pub fn max_stake(cap: u64, worst: impl Fn(u64) -> u64) -> u64 {
+    let (mut lo, mut hi) = (0u64, cap);
+    while lo < hi {
+        let mid = lo + (hi - lo).div_ceil(2);
+        if worst(mid) <= cap {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    lo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: u64 = 1_000_000_000;
+
+    #[test]
+    fn plinko_table_stays_under_fair_odds() {
+        for (i, by_difficulty) in PLINKO_MAX_MULT_BPS.iter().enumerate() {
+            let rows = 8 + i as u8;
+            for (difficulty, pair) in by_difficulty.windows(2).enumerate() {
+                assert!(pair[0] < pair[1], "rows {rows} difficulty {difficulty}");
+            }
+            for difficulty in 0..6 {
+                assert!(plinko(SOL, rows, difficulty) <= SOL << rows);
+            }
+        }
+        assert_eq!(plinko(SOL, 16, 2), 110 * SOL);
+        assert_eq!(plinko(SOL, 17, 0), SOL << 17);
+        assert_eq!(plinko(SOL, 8, 6), SOL << 8);
+    }
+
+    #[test]
+    fn normal_16_row_bet_fits_a_funded_vault() {
+        // 1000 SOL bankroll, 1% max payout per bet, 0.05 SOL over 10 balls on medium
+        let (vault, rent) = (1_000 * SOL, 1_000_000);
+        let cap = max_payout(vault, rent, 100);
+        let worst = plinko(SOL / 20, 16, 2);
+        assert!(worst <= cap);
+        assert!(covers(vault, 100 * SOL, 0, worst));
+        // the largest medium stake is close to cap / 110x
+        assert_eq!(max_stake(cap, |s| plinko(s, 16, 2)), cap / 110);
+    }
+}
@@ -0,0 +1,220 @@
+//! Commit-reveal ("provably fair") outcome derivation.
+//!
+//! Before a bet the backend publishes `commitment(server_seed)`; the lock copies that
+//! commitment onto the pending account together with the player's client seed and a recent
+//! SlotHashes entry, and resolve reveals `server_seed`. Every outcome is derived from
+//! [`outcome_hash`], so a player can recompute it from the revealed seed, their client seed,
+//! the slot hash and the bet nonce without trusting the backend's signed payout. Neither the
+//! backend (committed first) nor the player (seed fixed before the slot hash) can bias it alone.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{hash::hashv, sysvar::slot_hashes};
+
+/// Marks "no commitment posted" (or already consumed by a lock).
+pub const EMPTY_COMMITMENT: [u8; 32] = [0u8; 32];
+
+const BPS: u128 = 10_000;
+
+/// `sha256(server_seed)`
+pub fn commitment(server_seed: &[u8; 32]) -> [u8; 32] {
+    hashv(&[server_seed]).to_bytes()
+}
+
+/// True if `server_seed` opens `commit` (an empty commitment never opens).
+pub fn is_reveal_of(commit: &[u8; 32], server_seed: &[u8; 32]) -> bool {
+    *commit != EMPTY_COMMITMENT && commitment(server_seed) == *commit
+}
+
+/// `sha256(server_seed | client_seed | slot_hash | nonce_le)`; empty parts are simply omitted.
+pub fn outcome_hash(server_seed: &[u8; 32], client_seed: &[u8], slot_hash: &[u8], nonce: u64) -> [u8; 32] {
+    hashv(&[server_seed, client_seed, slot_hash, &nonce.to_le_bytes()]).to_bytes()
+}
+
+/// Most recent entry of the SlotHashes sysvar, read from raw account data
+/// (`len: u64 | (slot: u64, hash: [u8; 32])*`, newest first). `None` if `ai` is not the sysvar.
+pub fn latest_slot_hash(ai: &AccountInfo<'_>) -> Option<[u8; 32]> {
+    if *ai.key != slot_hashes::ID {
+        return None;
+    }
+    let data = ai.try_borrow_data().ok()?;
+    let len = u64::from_le_bytes(data.get(0..8)?.try_into().ok()?);
+    if len == 0 {
+        return None;
+    }
+    data.get(16..48)?.try_into().ok()
+}
+
+/// `i`-th 64-bit draw of an outcome: the first 8 bytes (LE) of `sha256(outcome | i_le)`.
+pub fn draw(outcome: &[u8; 32], i: u32) -> u64 {
+    let h = hashv(&[outcome, &i.to_le_bytes()]).to_bytes();
+    u64::from_le_bytes([h[0], h[1], h[2], h[3], h[4], h[5], h[6], h[7]])
+}
+
+/// Dice roll in `1..=100`.
+pub fn dice_roll(outcome: &[u8; 32]) -> u8 {
+    (draw(outcome, 0) % 100) as u8 + 1
+}
+
+/// Coin side: 0 = heads, 1 = tails.
+pub fn coin_side(outcome: &[u8; 32]) -> u8 {
+    (draw(outcome, 0) & 1) as u8
+}
+
+/// Mines layout as a bitmask over `cells` (row-major, at most 64) with `mines` bits set,
+/// picked by a partial Fisher-Yates shuffle using draws `0..mines`.
+pub fn mines_layout(outcome: &[u8; 32], cells: u8, mines: u8) -> u64 {
+    let cells = cells.min(64);
+    let mut idx = [0u8; 64];
+    for (i, v) in idx.iter_mut().enumerate().take(cells as usize) {
+        *v = i as u8;
+    }
+    let mut mask = 0u64;
+    for i in 0..mines.min(cells) {
+        let left = (cells - i) as u64;
+        let j = i as usize + (draw(outcome, i as u32) % left) as usize;
+        idx.swap(i as usize, j);
+        mask |= 1u64 << idx[i as usize];
+    }
+    mask
+}
+
+/// Plinko bucket (number of right bounces, `0..=rows`) of ball `ball`.
+/// Bit `r` of the ball's draw is the bounce at row `r` (1 = right).
+pub fn plinko_bucket(outcome: &[u8; 32], rows: u8, ball: u16) -> u8 {
+    let mask = if rows >= 64 { u64::MAX } else { (1u64 << rows) - 1 };
+    (draw(outcome, ball as u32) & mask).count_ones() as u8
+}
+
+/// Symbols on each slots reel.
+pub const SLOTS_SYMBOLS: u8 = 6;
+
+/// Slots reels, each in `0..SLOTS_SYMBOLS`, from draws `0..3`.
+pub fn slots_reels(outcome: &[u8; 32]) -> [u8; 3] {
+    [0, 1, 2].map(|i| (draw(outcome, i) % SLOTS_SYMBOLS as u64) as u8)
+}
+
+/// Crash point in bps (1.00x = 10_000), with `P(point >= x) = (1 - edge) / x` for `x >= 1`.
+pub fn crash_point_bps(outcome: &[u8; 32], house_edge_bps: u16) -> u32 {
+    crash_point(draw(outcome, 0) >> 12, house_edge_bps) // uniform in [0, 2^52)
+}
+
+/// `(1 - edge) / (1 - r / 2^52)` in bps, clamped to `[1.00x, u32::MAX]`; `r < 2^52`.
+fn crash_point(r: u64, house_edge_bps: u16) -> u32 {
+    let e = 1u128 << 52;
+    let edge = (house_edge_bps as u128).min(BPS);
+    let point = (BPS - edge) * e / (e - r as u128);
+    point.clamp(BPS, u32::MAX as u128) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex32(s: &str) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, b) in out.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    const SERVER_SEED: [u8; 32] = [1; 32];
+    const SLOT_HASH: [u8; 32] = [2; 32];
+
+    fn vector_outcome() -> [u8; 32] {
+        outcome_hash(&SERVER_SEED, b"client-seed", &SLOT_HASH, 7)
+    }
+
+    #[test]
+    fn commit_reveal() {
+        let zero = [0u8; 32];
+        let commit = commitment(&zero);
+        assert_eq!(commit, hex32("66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925"));
+        assert!(is_reveal_of(&commit, &zero));
+        assert!(!is_reveal_of(&commit, &SERVER_SEED));
+        // nothing opens an empty (or consumed) commitment
+        assert!(!is_reveal_of(&EMPTY_COMMITMENT, &zero));
+    }
+
+    #[test]
+    fn outcome_mixes_client_seed_and_slot_hash() {
+        let outcome = vector_outcome();
+        assert_eq!(outcome, hex32("8d8a778e9f34975665639ed984ab63690583f60e3ae917695384d9ee765c729d"));
+        // an empty client seed is omitted from the preimage
+        assert_eq!(
+            outcome_hash(&SERVER_SEED, &[], &SLOT_HASH, 7),
+            hex32("73706d10ec270635afb2c24226a9ffbf8a901156481e13539b626f3b974c7523")
+        );
+        assert_ne!(outcome_hash(&SERVER_SEED, b"client-seed", &[3; 32], 7), outcome);
+        assert_ne!(outcome_hash(&SERVER_SEED, b"client-seed", &SLOT_HASH, 8), outcome);
+    }
+
+    #[test]
+    fn derived_outcomes_match_vectors() {
+        let outcome = vector_outcome();
+        assert_eq!(dice_roll(&outcome), 93);
+        assert_eq!(coin_side(&outcome), 0);
+        assert_eq!(mines_layout(&outcome, 25, 3), 0x28001);
+        assert_eq!(crash_point_bps(&outcome, 0), 10_493);
+        assert_eq!(crash_point_bps(&outcome, 100), 10_388);
+    }
+
+    #[test]
+    fn dice_roll_covers_1_to_100() {
+        let mut seen = [false; 101];
+        for nonce in 0..2_000 {
+            let roll = dice_roll(&outcome_hash(&SERVER_SEED, &[], &[], nonce));
+            assert!((1..=100).contains(&roll));
+            seen[roll as usize] = true;
+        }
+        assert!(seen[1..].iter().all(|&s| s));
+    }
+
+    #[test]
+    fn mines_layout_sets_exactly_mines_bits_inside_the_board() {
+        for nonce in 0..50 {
+            let outcome = outcome_hash(&SERVER_SEED, &[], &[], nonce);
+            for (cells, mines) in [(4u8, 1u8), (25, 3), (25, 24), (64, 63), (9, 9)] {
+                let mask = mines_layout(&outcome, cells, mines);
+                assert_eq!(mask.count_ones(), mines as u32);
+                if cells < 64 {
+                    assert_eq!(mask >> cells, 0);
+                }
+            }
+            // more mines than cells fills the board; boards are capped at 64 cells
+            assert_eq!(mines_layout(&outcome, 9, 20), 0x1ff);
+            assert_eq!(mines_layout(&outcome, 100, 64), u64::MAX);
+        }
+    }
+
+    #[test]
+    fn plinko_bucket_is_within_rows() {
+        let outcome = vector_outcome();
+        for ball in 0..100 {
+            assert!(plinko_bucket(&outcome, 8, ball) <= 8);
+            assert!(plinko_bucket(&outcome, 16, ball) <= 16);
+        }
+    }
+
+    #[test]
+    fn slots_reels_stay_on_the_reel() {
+        let outcome = vector_outcome();
+        assert!(slots_reels(&outcome).iter().all(|&r| r < SLOTS_SYMBOLS));
+        assert_ne!(slots_reels(&outcome), slots_reels(&[0u8; 32]));
+    }
+
+    #[test]
+    fn crash_point_edges() {
+        let top = (1u64 << 52) - 1;
+        // r = 0 crashes at once: 1.00x without edge, and the edge cannot push it below 1.00x
+        assert_eq!(crash_point(0, 0), 10_000);
+        assert_eq!(crash_point(0, 100), 10_000);
+        // r = 2^51 is 2.00x less the edge
+        assert_eq!(crash_point(1 << 51, 0), 20_000);
+        assert_eq!(crash_point(1 << 51, 100), 19_800);
+        // the largest draw saturates at u32::MAX instead of overflowing
+        assert_eq!(crash_point(top, 0), u32::MAX);
+        // an edge above 100% is treated as 100% and never goes below 1.00x
+        assert_eq!(crash_point(1 << 51, u16::MAX), 10_000);
+    }
+}
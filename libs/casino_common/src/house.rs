@@ -0,0 +1,101 @@
+//! House-side state every program keeps in its `GameState`: the pause switches, the exposure
+//! still owed on open bets, the share of the vault one bet may win and the outflow breaker,
+//! with the lock and resolve bookkeeping on them (see [`crate::exposure`], [`crate::breaker`]).
+
+use anchor_lang::prelude::*;
+
+use crate::breaker::OutflowBreaker;
+use crate::error::CommonError;
+use crate::exposure;
+use crate::pause::{self, PauseFlags};
+
+/// Share of the vault (bps) one bet may win until the config manager changes it.
+pub const DEFAULT_MAX_PAYOUT_BPS: u16 = 100;
+/// Most the config manager may raise that share to.
+pub const MAX_PAYOUT_BPS_CEILING: u16 = 1_000;
+
+/// Returned by the programs' `*_limits` views.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct BetLimits {
+    pub max_payout: u64, // most one bet may win: max_payout_bps of the vault above rent
+    pub available: u64,  // vault less rent, the program's house reserve and open exposure
+    pub max_bet: u64,    // largest stake for the queried setting (the game config's min_bet still applies)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct House {
+    pub flags: PauseFlags,
+    pub open_exposure: u64,
+    pub max_payout_bps: u16,
+    pub breaker: OutflowBreaker,
+}
+
+impl House {
+    pub const SIZE: usize = PauseFlags::SIZE + 8 + 2 + OutflowBreaker::SIZE;
+
+    /// Unpaused, nothing open, default payout share and the given breaker.
+    pub fn new(outflow_threshold: u64, window_hours: u8) -> Self {
+        let mut house = Self { max_payout_bps: DEFAULT_MAX_PAYOUT_BPS, ..Default::default() };
+        house.breaker.threshold = outflow_threshold;
+        house.breaker.window_hours = window_hours;
+        house
+    }
+
+    /// Bankroll limits at the vault's current balance; `max_bet` is left at 0. `reserve` is
+    /// the program's house reserve, never promised to open bets.
+    pub fn limits(&self, vault: &AccountInfo<'_>, reserve: u64) -> Result<BetLimits> {
+        let rent = Rent::get()?.minimum_balance(0);
+        let balance = vault.lamports();
+        Ok(BetLimits {
+            max_payout: exposure::max_payout(balance, rent, self.max_payout_bps),
+            available: balance
+                .saturating_sub(rent.saturating_add(reserve))
+                .saturating_sub(self.open_exposure),
+            max_bet: 0,
+        })
+    }
+
+    /// Bankroll limits plus the largest stake (within `max_bet`) whose `worst` payout fits them.
+    pub fn limits_for(
+        &self,
+        vault: &AccountInfo<'_>,
+        reserve: u64,
+        max_bet: u64,
+        worst: impl Fn(u64) -> u64,
+    ) -> Result<BetLimits> {
+        let mut limits = self.limits(vault, reserve)?;
+        limits.max_bet = exposure::max_stake(limits.max_payout.min(limits.available), worst).min(max_bet);
+        Ok(limits)
+    }
+
+    /// Record `worst` (the most the bet's resolve may pay) as open and return it. The bet may
+    /// not stand to win more than `max_payout_bps` of the vault, and the vault less its rent
+    /// minimum and `reserve` must still cover every open bet plus this one. Call after the stake
+    /// has moved into the vault.
+    pub fn reserve(&mut self, vault: &AccountInfo<'_>, reserve: u64, worst: u64) -> Result<u64> {
+        require!(worst <= self.limits(vault, reserve)?.max_payout, CommonError::PayoutLimit);
+        let reserve = Rent::get()?.minimum_balance(0).saturating_add(reserve);
+        require!(
+            exposure::covers(vault.lamports(), reserve, self.open_exposure, worst),
+            CommonError::ExposureLimit
+        );
+        self.open_exposure = self.open_exposure.saturating_add(worst);
+        Ok(worst)
+    }
+
+    /// Release a settled or refunded bet's exposure.
+    pub fn release(&mut self, exposure: u64) {
+        self.open_exposure = self.open_exposure.saturating_sub(exposure);
+    }
+
+    /// Feed a resolved bet's stake and payout into the outflow breaker. If it trips, every lock
+    /// and resolve is paused (this resolve still settles) until a pauser unpauses; returns the
+    /// net outflow that tripped it so the program can emit its `BreakerTripped` event.
+    pub fn record_outflow(&mut self, staked: u64, paid: u64) -> Result<Option<u64>> {
+        let tripped = self.breaker.record(Clock::get()?.unix_timestamp, staked, paid);
+        if tripped.is_some() {
+            self.flags.pause(pause::GLOBAL, true);
+        }
+        Ok(tripped)
+    }
+}
@@ -0,0 +1,19 @@
+//! Helpers shared by every casino program in this workspace.
+
+pub mod admin;
+pub mod breaker;
+pub mod config;
+pub mod cosign;
+pub mod deploy;
+pub mod ed25519;
+pub mod error;
+pub mod expiry;
+pub mod exposure;
+pub mod fair;
+pub mod house;
+pub mod message;
+pub mod pause;
+pub mod payout;
+pub mod randomness;
+pub mod roles;
+pub mod window;
@@ -0,0 +1,70 @@
+//! Canonical resolve message every casino program asks its resolver key(s) to sign.
+//!
+//! `domain_tag | program_id | vault | player | pending | body | payout_le | nonce_le | expiry_le`
+//!
+//! `body` holds the game-specific bytes (bet params, then the derived outcome) and is written
+//! part by part in the given order. The off-chain signer builds the same bytes, so the layout is
+//! pinned by the test below.
+
+use anchor_lang::prelude::*;
+
+/// Build the message a resolve's ed25519 signatures must cover.
+#[allow(clippy::too_many_arguments)]
+pub fn resolve_message(
+    domain_tag: &[u8],
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    player: &Pubkey,
+    pending: &Pubkey,
+    body: &[&[u8]],
+    payout: u64,
+    nonce: u64,
+    expiry_unix: i64,
+) -> Vec<u8> {
+    let body_len: usize = body.iter().map(|part| part.len()).sum();
+    let mut v = Vec::with_capacity(domain_tag.len() + 32 * 4 + body_len + 8 + 8 + 8);
+    v.extend_from_slice(domain_tag);
+    v.extend_from_slice(program_id.as_ref());
+    v.extend_from_slice(vault.as_ref());
+    v.extend_from_slice(player.as_ref());
+    v.extend_from_slice(pending.as_ref());
+    for part in body {
+        v.extend_from_slice(part);
+    }
+    v.extend_from_slice(&payout.to_le_bytes());
+    v.extend_from_slice(&nonce.to_le_bytes());
+    v.extend_from_slice(&expiry_unix.to_le_bytes());
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_is_pinned() {
+        let key = |b: u8| Pubkey::new_from_array([b; 32]);
+        let msg = resolve_message(
+            b"TAG_V1",
+            &key(1),
+            &key(2),
+            &key(3),
+            &key(4),
+            &[&[0xa0, 0xa1], &[], &[0xb0]],
+            0x0102_0304_0506_0708,
+            7,
+            -2,
+        );
+
+        let mut expected = b"TAG_V1".to_vec();
+        for b in 1..=4u8 {
+            expected.extend_from_slice(&[b; 32]);
+        }
+        expected.extend_from_slice(&[0xa0, 0xa1, 0xb0]);
+        expected.extend_from_slice(&[0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]);
+        expected.extend_from_slice(&[7, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(msg, expected);
+        assert_eq!(msg.len(), 6 + 128 + 3 + 24);
+    }
+}
@@ -0,0 +1,74 @@
+//! Incident pause switches.
+//!
+//! One bit per game id plus [`GLOBAL`], kept separately for locks and resolves so a pause can
+//! stop new bets while letting the backend settle what is already open. Single-game programs
+//! only pause [`GLOBAL`] and check [`SOLE_GAME`]. A pause only ever sets bits, so it cannot lift
+//! a resolve halt left by an earlier pause or the outflow breaker; only [`PauseFlags::unpause`]
+//! clears them. Withdrawals and expired-bet refunds never consult these flags, so players can
+//! always exit.
+
+use anchor_lang::prelude::*;
+
+/// Bit that pauses every game at once.
+pub const GLOBAL: u8 = 1 << 7;
+
+/// Game id single-game programs pass to the checks.
+pub const SOLE_GAME: u8 = 0;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct PauseFlags {
+    pub locks: u8,
+    pub resolves: u8,
+}
+
+impl PauseFlags {
+    pub const SIZE: usize = 1 + 1;
+
+    pub fn lock_paused(&self, game: u8) -> bool {
+        self.locks & (GLOBAL | 1 << game) != 0
+    }
+
+    pub fn resolve_paused(&self, game: u8) -> bool {
+        self.resolves & (GLOBAL | 1 << game) != 0
+    }
+
+    /// Pause locks for the games in `mask` (and their resolves too when `halt_resolve`).
+    pub fn pause(&mut self, mask: u8, halt_resolve: bool) {
+        self.locks |= mask;
+        if halt_resolve {
+            self.resolves |= mask;
+        }
+    }
+
+    /// Clear both lock and resolve pauses for the games in `mask`.
+    pub fn unpause(&mut self, mask: u8) {
+        self.locks &= !mask;
+        self.resolves &= !mask;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_never_lifts_a_resolve_halt() {
+        let mut flags = PauseFlags::default();
+        flags.pause(GLOBAL, true);
+        flags.pause(GLOBAL, false);
+        assert!(flags.lock_paused(SOLE_GAME) && flags.resolve_paused(SOLE_GAME));
+
+        flags.unpause(GLOBAL);
+        assert!(!flags.lock_paused(SOLE_GAME) && !flags.resolve_paused(SOLE_GAME));
+    }
+
+    #[test]
+    fn game_bits_are_independent() {
+        let mut flags = PauseFlags::default();
+        flags.pause(1 << 2, false);
+        assert!(flags.lock_paused(2) && !flags.resolve_paused(2));
+        assert!(!flags.lock_paused(3));
+        flags.unpause(1 << 3);
+        assert!(flags.lock_paused(2));
+    }
+}
@@ -0,0 +1,310 @@
+//! Payout each game's resolve must settle, derived on chain from the locked bet, its provably
+//! fair outcome (see [`crate::fair`]) and the edge, multiplier or payout table fixed at lock.
+//!
+//! The resolver still signs the payout it settles, and every resolve requires it to equal the
+//! value below, so a resolver key attests an outcome but never prices it. All amounts are gross
+//! (stake included) and round down; none exceeds the matching [`crate::exposure`] bound.
+
+use crate::exposure::PLINKO_MAX_MULT_BPS;
+use crate::fair;
+
+const BPS: u128 = 10_000;
+
+/// `amount * mult_bps / 10_000`, rounded down and saturating at `u64::MAX`.
+fn scale(amount: u64, mult_bps: u128) -> u64 {
+    ((amount as u128).saturating_mul(mult_bps) / BPS).min(u64::MAX as u128) as u64
+}
+
+/// Share of a fair payout left after the house edge, in bps.
+fn after_edge(house_edge_bps: u16) -> u128 {
+    BPS.saturating_sub(house_edge_bps as u128)
+}
+
+/// Dice: a winning roll pays the stake at fair odds (`100 / winning rolls`) less the edge.
+pub fn dice(amount: u64, bet_type: u8, target: u8, roll: u8, house_edge_bps: u16) -> u64 {
+    let (win, wins) = if bet_type == 0 {
+        (roll < target, target.saturating_sub(1))
+    } else {
+        (roll > target, 100u8.saturating_sub(target))
+    };
+    if !win || wins == 0 {
+        return 0;
+    }
+    let paid = (amount as u128) * after_edge(house_edge_bps) / (100 * wins as u128);
+    paid.min(u64::MAX as u128) as u64
+}
+
+/// Coin flip: the winning side pays twice the stake less the edge.
+pub fn flip(amount: u64, side: u8, winner_side: u8, house_edge_bps: u16) -> u64 {
+    if side != winner_side {
+        return 0;
+    }
+    scale(amount.saturating_mul(2), after_edge(house_edge_bps))
+}
+
+/// Crash: a cash-out at or below the crash point pays the stake times the cash-out multiplier.
+pub fn crash(amount: u64, multiplier_bps: u32, crash_point_bps: u32) -> u64 {
+    if multiplier_bps > crash_point_bps {
+        return 0;
+    }
+    scale(amount, multiplier_bps as u128)
+}
+
+/// Mines: `picks` (a bitmask over the board, like the layout) are the tiles the player opened
+/// before cashing out. Hitting a mine pays nothing; clearing `k` safe tiles pays the inverse of
+/// their chance, `C(cells, k) / C(cells - mines, k)`, less the edge. `None` if a pick lies off
+/// the board or the product overflows (only for boards far beyond any config's range).
+pub fn mines(amount: u64, cells: u8, mines: u8, layout: u64, picks: u64, house_edge_bps: u16) -> Option<u64> {
+    let cells = cells.min(64);
+    if cells < 64 && picks >> cells != 0 {
+        return None;
+    }
+    if picks & layout != 0 {
+        return Some(0);
+    }
+    let k = picks.count_ones() as u128;
+    let safe = (cells as u128).saturating_sub(mines as u128);
+    let paid = (amount as u128)
+        .checked_mul(binomial(cells as u128, k))?
+        .checked_mul(after_edge(house_edge_bps))?
+        / (binomial(safe, k).max(1) * BPS);
+    Some(paid.min(u64::MAX as u128) as u64)
+}
+
+fn binomial(n: u128, k: u128) -> u128 {
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    (0..k).fold(1u128, |c, i| c * (n - i) / (i + 1))
+}
+
+/// Plinko buckets between the edge bucket and the centre, in bps, indexed by `rows - 8` and
+/// difficulty (0 easy ..= 5 extreme); entry `i` pays buckets `i + 1` and `rows - i - 1`, and
+/// unused entries are 0. The edge buckets pay [`PLINKO_MAX_MULT_BPS`]. Every table returns
+/// between 98% and 99% of the stake.
+pub const PLINKO_INNER_MULT_BPS: [[[u32; 8]; 6]; 9] = [
+    [ // 8 rows
+        [30_500, 14_600, 6_800, 4_900, 0, 0, 0, 0],
+        [38_800, 14_800, 5_500, 4_100, 0, 0, 0, 0],
+        [49_400, 14_300, 4_100, 3_100, 0, 0, 0, 0],
+        [64_700, 9_300, 2_200, 2_000, 0, 0, 0, 0],
+        [49_500, 3_300, 1_900, 1_900, 0, 0, 0, 0],
+        [4_100, 200, 200, 200, 0, 0, 0, 0],
+    ],
+    [ // 9
+        [34_300, 19_000, 9_500, 5_400, 0, 0, 0, 0],
+        [47_600, 21_500, 8_400, 4_200, 0, 0, 0, 0],
+        [72_700, 23_000, 5_900, 2_900, 0, 0, 0, 0],
+        [106_700, 17_900, 2_900, 2_000, 0, 0, 0, 0],
+        [102_900, 7_400, 2_100, 2_000, 0, 0, 0, 0],
+        [26_400, 2_000, 2_000, 2_000, 0, 0, 0, 0],
+    ],
+    [ // 10
+        [50_900, 26_000, 11_900, 5_900, 4_800, 0, 0, 0],
+        [64_600, 30_200, 11_900, 4_800, 3_800, 0, 0, 0],
+        [96_000, 34_200, 9_800, 3_600, 3_100, 0, 0, 0],
+        [179_700, 29_400, 4_000, 2_000, 2_000, 0, 0, 0],
+        [194_200, 15_500, 2_200, 1_900, 1_900, 0, 0, 0],
+        [100_600, 3_200, 2_000, 2_000, 2_000, 0, 0, 0],
+    ],
+    [ // 11
+        [52_800, 30_500, 16_000, 8_100, 5_200, 0, 0, 0],
+        [78_500, 39_100, 16_800, 6_800, 4_100, 0, 0, 0],
+        [118_000, 49_500, 16_800, 5_100, 3_000, 0, 0, 0],
+        [296_000, 52_600, 6_800, 2_100, 2_000, 0, 0, 0],
+        [351_200, 33_200, 3_100, 1_900, 1_900, 0, 0, 0],
+        [253_300, 7_900, 2_000, 1_900, 1_900, 0, 0, 0],
+    ],
+    [ // 12
+        [64_100, 38_100, 20_700, 10_500, 5_900, 5_000, 0, 0],
+        [102_200, 52_100, 23_200, 9_300, 4_700, 4_100, 0, 0],
+        [163_600, 70_300, 24_900, 7_500, 3_300, 3_000, 0, 0],
+        [458_900, 93_600, 13_500, 2_600, 2_000, 2_000, 0, 0],
+        [598_700, 70_500, 6_200, 2_000, 2_000, 2_000, 0, 0],
+        [599_000, 31_300, 2_500, 1_900, 1_900, 1_900, 0, 0],
+    ],
+    [ // 13
+        [57_600, 38_800, 24_400, 14_300, 8_000, 5_200, 0, 0],
+        [128_500, 68_200, 32_100, 13_300, 5_800, 4_100, 0, 0],
+        [218_300, 97_600, 36_800, 11_600, 4_100, 3_100, 0, 0],
+        [731_800, 160_700, 25_400, 3_700, 1_900, 1_900, 0, 0],
+        [996_400, 138_400, 13_300, 2_400, 2_000, 2_000, 0, 0],
+        [1_141_600, 85_900, 5_200, 2_000, 2_000, 2_000, 0, 0],
+    ],
+    [ // 14
+        [53_500, 38_700, 26_600, 17_200, 10_500, 6_500, 5_200, 0],
+        [157_800, 88_200, 44_200, 19_400, 7_900, 4_200, 3_800, 0],
+        [300_300, 138_700, 54_900, 18_000, 5_600, 3_000, 2_900, 0],
+        [1_187_900, 267_900, 44_600, 6_000, 2_100, 1_900, 1_900, 0],
+        [1_692_000, 255_600, 26_800, 3_100, 1_900, 1_900, 1_900, 0],
+        [2_061_000, 184_100, 11_300, 2_100, 1_900, 1_900, 1_900, 0],
+    ],
+    [ // 15
+        [103_400, 67_600, 41_300, 23_300, 12_300, 6_700, 4_900, 0],
+        [195_500, 110_800, 56_900, 26_000, 10_800, 5_200, 4_100, 0],
+        [450_500, 207_000, 82_300, 27_200, 7_800, 3_200, 2_800, 0],
+        [1_838_400, 445_700, 82_400, 11_300, 2_400, 1_800, 1_800, 0],
+        [2_634_300, 450_000, 55_400, 5_700, 2_100, 2_000, 2_000, 0],
+        [3_572_200, 370_100, 26_000, 2_700, 2_000, 2_000, 2_000, 0],
+    ],
+    [ // 16
+        [113_000, 76_200, 48_500, 28_800, 15_900, 8_600, 5_500, 4_900],
+        [248_800, 144_500, 76_800, 36_500, 15_500, 6_600, 4_100, 3_900],
+        [573_300, 271_000, 112_800, 39_900, 12_100, 4_400, 3_200, 3_100],
+        [2_968_000, 731_300, 140_700, 20_100, 3_200, 1_900, 1_900, 1_900],
+        [4_483_900, 799_200, 105_300, 10_300, 2_200, 1_800, 1_800, 1_800],
+        [6_170_000, 714_600, 57_400, 4_400, 2_000, 1_900, 1_900, 1_900],
+    ],
+];
+
+/// Multiplier in bps of plinko bucket `bucket` (`0..=rows`), or `None` outside the table.
+pub fn plinko_multiplier_bps(rows: u8, difficulty: u8, bucket: u8) -> Option<u32> {
+    let i = rows.checked_sub(8)? as usize;
+    let edge = *PLINKO_MAX_MULT_BPS.get(i)?.get(difficulty as usize)?;
+    if bucket > rows {
+        return None;
+    }
+    let from_edge = bucket.min(rows - bucket);
+    if from_edge == 0 {
+        return Some(edge);
+    }
+    Some(PLINKO_INNER_MULT_BPS[i][difficulty as usize][from_edge as usize - 1])
+}
+
+/// Plinko: every ball pays `unit_amount` times the multiplier of the bucket
+/// [`fair::plinko_bucket`] drops it in. `None` if `rows` or `difficulty` has no table.
+pub fn plinko(unit_amount: u64, balls: u16, rows: u8, difficulty: u8, outcome: &[u8; 32]) -> Option<u64> {
+    let mut total_bps = 0u128;
+    for ball in 0..balls {
+        let bucket = fair::plinko_bucket(outcome, rows, ball);
+        total_bps += plinko_multiplier_bps(rows, difficulty, bucket)? as u128;
+    }
+    Some(scale(unit_amount, total_bps))
+}
+
+/// Slots pay for any two matching reels, in bps.
+pub const SLOTS_PAIR_BPS: u32 = 15_000;
+/// Slots pay for three of symbol `s` ([`fair::slots_reels`]), in bps. With the pair line these
+/// return exactly the stake before the edge: `(90 * 1.5 + 5 + 8 + 10 + 14 + 19 + 25) / 216`.
+pub const SLOTS_TRIPLE_BPS: [u32; fair::SLOTS_SYMBOLS as usize] = [50_000, 80_000, 100_000, 140_000, 190_000, 250_000];
+/// Top slots line; a slots config's `max_param` (which sizes exposure) must cover it.
+pub const SLOTS_TOP_LINE_BPS: u32 = 250_000;
+
+/// Slots: three of a kind pays its line, any two matching reels the pair line, less the edge.
+pub fn slots(amount: u64, reels: [u8; 3], house_edge_bps: u16) -> u64 {
+    let [a, b, c] = reels;
+    let line_bps = if a == b && b == c {
+        SLOTS_TRIPLE_BPS.get(a as usize).copied().unwrap_or(0)
+    } else if a == b || b == c || a == c {
+        SLOTS_PAIR_BPS
+    } else {
+        0
+    };
+    scale(amount, line_bps as u128 * after_edge(house_edge_bps) / BPS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exposure;
+
+    const SOL: u64 = 1_000_000_000;
+
+    #[test]
+    fn dice_matches_fair_odds_less_edge() {
+        // under 50 wins on 49 rolls: 100/49 less 1%
+        assert_eq!(dice(SOL, 0, 50, 49, 100), SOL * 9_900 / 4_900);
+        assert_eq!(dice(SOL, 0, 50, 50, 100), 0);
+        assert_eq!(dice(SOL, 1, 50, 51, 100), SOL * 9_900 / 5_000);
+        assert_eq!(dice(SOL, 1, 50, 50, 100), 0);
+        for target in 2..=98 {
+            for bet_type in 0..2 {
+                for roll in 1..=100 {
+                    assert!(dice(SOL, bet_type, target, roll, 0) <= exposure::dice(SOL, bet_type, target));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn flip_and_crash() {
+        assert_eq!(flip(SOL, 1, 1, 0), 2 * SOL);
+        assert_eq!(flip(SOL, 1, 1, 250), 2 * SOL * 9_750 / 10_000);
+        assert_eq!(flip(SOL, 0, 1, 0), 0);
+        assert_eq!(crash(SOL, 23_400, 23_400), SOL * 234 / 100);
+        assert_eq!(crash(SOL, 23_401, 23_400), 0);
+        assert!(crash(SOL, 50_000, u32::MAX) <= exposure::multiple(SOL, 50_000));
+    }
+
+    #[test]
+    fn mines_pays_inverse_chance_of_the_opened_tiles() {
+        let layout = 0b1; // mine on tile 0 of a 25-tile board
+        // one safe tile out of 24 safe of 25: 25/24
+        assert_eq!(mines(24 * SOL, 25, 1, layout, 0b10, 0), Some(25 * SOL));
+        assert_eq!(mines(24 * SOL, 25, 1, layout, 0b11, 0), Some(0));
+        assert_eq!(mines(SOL, 25, 1, layout, 0, 0), Some(SOL));
+        assert_eq!(mines(SOL, 25, 1, layout, 1 << 25, 0), None);
+        // clearing every safe tile pays the exposure bound, C(cells, mines), at most
+        let all_safe = ((1u64 << 25) - 1) & !layout;
+        assert_eq!(mines(SOL, 25, 1, layout, all_safe, 0), Some(exposure::mines(SOL, 25, 1)));
+        let full_board = mines(SOL, 64, 32, u64::MAX << 32, u32::MAX as u64, 0).unwrap();
+        assert!(full_board <= exposure::mines(SOL, 64, 32));
+    }
+
+    #[test]
+    fn plinko_tables_are_symmetric_and_under_the_stake() {
+        for rows in 8..=16u8 {
+            for difficulty in 0..6 {
+                let m = |b| plinko_multiplier_bps(rows, difficulty, b).unwrap() as u128;
+                let mut rtp = 0u128;
+                for b in 0..=rows {
+                    assert_eq!(m(b), m(rows - b));
+                    if b > 0 && b <= rows / 2 {
+                        assert!(m(b) > 0 && m(b) <= m(b - 1), "rows {rows} difficulty {difficulty}");
+                    }
+                    rtp += m(b) * binomial(rows as u128, b as u128);
+                }
+                let rtp_bps = rtp >> rows;
+                assert!((9_800..10_000).contains(&rtp_bps), "rows {rows} difficulty {difficulty}: {rtp_bps}");
+            }
+        }
+        assert_eq!(plinko_multiplier_bps(16, 2, 0), Some(1_100_000));
+        assert_eq!(plinko_multiplier_bps(16, 2, 17), None);
+        assert_eq!(plinko_multiplier_bps(17, 0, 0), None);
+        assert_eq!(plinko_multiplier_bps(8, 6, 0), None);
+    }
+
+    #[test]
+    fn plinko_sums_every_ball_within_exposure() {
+        let outcome = [9u8; 32];
+        let paid = plinko(SOL, 10, 16, 2, &outcome).unwrap();
+        let by_ball: u64 = (0..10)
+            .map(|ball| {
+                let bucket = fair::plinko_bucket(&outcome, 16, ball);
+                SOL / 10_000 * plinko_multiplier_bps(16, 2, bucket).unwrap() as u64
+            })
+            .sum();
+        assert_eq!(paid, by_ball);
+        assert!(paid <= exposure::plinko(10 * SOL, 16, 2));
+        assert_eq!(plinko(SOL, 1, 8, 6, &outcome), None);
+    }
+
+    #[test]
+    fn slots_lines_return_the_stake_before_edge() {
+        let mut total = 0u128;
+        for a in 0..fair::SLOTS_SYMBOLS {
+            for b in 0..fair::SLOTS_SYMBOLS {
+                for c in 0..fair::SLOTS_SYMBOLS {
+                    total += slots(10_000, [a, b, c], 0) as u128;
+                }
+            }
+        }
+        assert_eq!(total, 10_000 * 216);
+        assert_eq!(slots(SOL, [5, 5, 5], 0), 25 * SOL);
+        assert_eq!(slots(SOL, [5, 5, 5], 100), 25 * SOL * 99 / 100);
+        assert_eq!(slots(SOL, [0, 1, 0], 0), SOL * 3 / 2);
+        assert_eq!(slots(SOL, [0, 1, 2], 0), 0);
+        assert_eq!(SLOTS_TRIPLE_BPS.iter().max(), Some(&SLOTS_TOP_LINE_BPS));
+    }
+}
@@ -0,0 +1,301 @@
+//! Pluggable source for the seed fed into [`crate::fair::outcome_hash`].
+//!
+//! * Backend mode (default): the resolver commits to `sha256(server_seed)` before the lock and
+//!   reveals `server_seed` at resolve, as described in [`crate::fair`].
+//! * Oracle mode: the lock CPIs `request_randomness` on the configured oracle program with the
+//!   pending account's key as the request seed and records the request account on the pending
+//!   bet. Resolve reads the fulfilled request and uses its randomness in place of the server seed.
+//!
+//! The mode and oracle program live in each game's config ([`RandomnessConfig`]) and are set by
+//! the super admin; the transaction builder cannot pick them. Pending accounts store
+//! [`NO_ORACLE`] in backend mode. The oracle is driven through its raw instruction/account
+//! layout, so an oracle program must implement `mock_oracle`'s interface backed by a real VRF.
+//! Only programs on the build's allow-list ([`ORACLE_PROGRAM_IDS`]) are accepted, so the super
+//! admin can switch a game between vetted sources but never to a program of their own, and
+//! adding an oracle takes a program upgrade. `mock_oracle` fulfils deterministically and
+//! permissionlessly, so it is only accepted in builds with the `localnet` feature.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    pubkey,
+};
+use anchor_lang::system_program;
+
+use crate::error::CommonError;
+use crate::fair;
+
+/// `mock_oracle`: test-only oracle, refused unless built with the `localnet` feature.
+pub const MOCK_ORACLE_PROGRAM_ID: Pubkey = pubkey!("3VszGE37nGnEcxPjrTSai77v6w8SFrgEeZdfzB5gAA7S");
+
+/// Oracle programs a game may take randomness from (besides `mock_oracle` in `localnet` builds).
+pub const ORACLE_PROGRAM_IDS: &[Pubkey] = &[];
+
+/// `oracle_request` value of a pending bet resolved in backend mode.
+pub const NO_ORACLE: Pubkey = Pubkey::new_from_array([0u8; 32]);
+
+/// [`RandomnessConfig::mode`]: resolver commit/reveal.
+pub const MODE_BACKEND: u8 = 0;
+/// [`RandomnessConfig::mode`]: randomness requested from [`RandomnessConfig::oracle_program`].
+pub const MODE_ORACLE: u8 = 1;
+
+const REQUEST_SEED: &[u8] = b"request";
+
+// RandomnessRequest: disc 8 | requester 32 | seed 32 | randomness 32 | request_slot 8 | fulfilled 1 | bump 1
+const SEED_RANGE: std::ops::Range<usize> = 40..72;
+const RANDOMNESS_RANGE: std::ops::Range<usize> = 72..104;
+const FULFILLED_OFFSET: usize = 112;
+const BUMP_OFFSET: usize = 113;
+const REQUEST_ACCOUNT_LEN: usize = 114;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomnessError {
+    /// Backend mode: the revealed server seed does not open the commitment
+    BadReveal,
+    /// Oracle mode: request account missing or not the one recorded at lock
+    WrongRequest,
+    /// Oracle mode: request exists but has not been fulfilled yet
+    NotFulfilled,
+    /// Oracle mode: account is not a request owned by an accepted oracle
+    Malformed,
+}
+
+/// Randomness source of a game, stored in its config.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct RandomnessConfig {
+    /// [`MODE_BACKEND`] or [`MODE_ORACLE`]
+    pub mode: u8,
+    /// Oracle mode only; default key in backend mode
+    pub oracle_program: Pubkey,
+}
+
+impl RandomnessConfig {
+    pub const SIZE: usize = 1 + 32;
+
+    pub fn is_valid(&self) -> bool {
+        match self.mode {
+            MODE_BACKEND => self.oracle_program == Pubkey::default(),
+            MODE_ORACLE => oracle_allowed(&self.oracle_program),
+            _ => false,
+        }
+    }
+}
+
+/// Whether `program` may serve as the oracle: one of [`ORACLE_PROGRAM_IDS`], or `mock_oracle` in
+/// `localnet` builds.
+pub fn oracle_allowed(program: &Pubkey) -> bool {
+    ORACLE_PROGRAM_IDS.contains(program) || (cfg!(feature = "localnet") && *program == MOCK_ORACLE_PROGRAM_ID)
+}
+
+/// Request PDA `oracle_program` derives for `seed`.
+pub fn request_address(oracle_program: &Pubkey, seed: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[REQUEST_SEED, seed], oracle_program).0
+}
+
+/// CPI `request_randomness(seed)` on the configured `oracle`; `payer` must be a signer of the
+/// outer instruction.
+pub fn request<'info>(
+    oracle: &Pubkey,
+    oracle_program: &AccountInfo<'info>,
+    request: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    seed: [u8; 32],
+) -> std::result::Result<(), ProgramError> {
+    if oracle_program.key != oracle || !oracle_allowed(oracle) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if *request.key != request_address(oracle, &seed) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let mut data = Vec::with_capacity(8 + 32);
+    data.extend_from_slice(&hash(b"global:request_randomness").to_bytes()[..8]);
+    data.extend_from_slice(&seed);
+    let ix = Instruction {
+        program_id: *oracle,
+        accounts: vec![
+            AccountMeta::new(*payer.key, true),
+            AccountMeta::new(*request.key, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    };
+    invoke(
+        &ix,
+        &[payer.clone(), request.clone(), system_program.clone(), oracle_program.clone()],
+    )
+}
+
+/// Randomness for a new bet from the source `source` selects. Oracle mode requests it from the
+/// configured oracle with the pending account's key as seed (both oracle accounts required);
+/// backend mode consumes the resolver's seed commitment (oracle accounts omitted). Returns the
+/// commitment to record (empty in oracle mode) and the oracle request key.
+pub fn lock<'info>(
+    source: &RandomnessConfig,
+    oracle_program: Option<&AccountInfo<'info>>,
+    oracle_request: Option<&AccountInfo<'info>>,
+    seed_commit: Option<&mut [u8; 32]>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    pending: &Pubkey,
+) -> Result<([u8; 32], Pubkey)> {
+    match (source.mode, oracle_program, oracle_request) {
+        (MODE_ORACLE, Some(program), Some(request)) => {
+            self::request(&source.oracle_program, program, request, payer, system_program, pending.to_bytes())?;
+            Ok((fair::EMPTY_COMMITMENT, *request.key))
+        }
+        (MODE_BACKEND, None, None) => {
+            let slot = seed_commit.ok_or(CommonError::NoSeedCommit)?;
+            let commit = std::mem::replace(slot, fair::EMPTY_COMMITMENT);
+            require!(commit != fair::EMPTY_COMMITMENT, CommonError::NoSeedCommit);
+            Ok((commit, NO_ORACLE))
+        }
+        _ => err!(CommonError::RandomnessUnavailable),
+    }
+}
+
+/// Randomness of a fulfilled oracle request.
+///
+/// The account must be the owner's own request PDA for the seed it stores, so a request keeps
+/// resolving after the config switches oracles, while no other program can stand in for it.
+pub fn fulfilled_randomness(ai: &AccountInfo<'_>) -> std::result::Result<[u8; 32], RandomnessError> {
+    if !oracle_allowed(ai.owner) {
+        return Err(RandomnessError::Malformed);
+    }
+    request_randomness(ai)
+}
+
+/// Randomness of a fulfilled request, whichever program owns it.
+fn request_randomness(ai: &AccountInfo<'_>) -> std::result::Result<[u8; 32], RandomnessError> {
+    let data = ai.try_borrow_data().map_err(|_| RandomnessError::Malformed)?;
+    if data.len() < REQUEST_ACCOUNT_LEN
+        || data[..8] != hash(b"account:RandomnessRequest").to_bytes()[..8]
+    {
+        return Err(RandomnessError::Malformed);
+    }
+    let pda = Pubkey::create_program_address(
+        &[REQUEST_SEED, &data[SEED_RANGE], &[data[BUMP_OFFSET]]],
+        ai.owner,
+    );
+    if pda.as_ref() != Ok(ai.key) {
+        return Err(RandomnessError::Malformed);
+    }
+    if data[FULFILLED_OFFSET] != 1 {
+        return Err(RandomnessError::NotFulfilled);
+    }
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&data[RANDOMNESS_RANGE]);
+    Ok(out)
+}
+
+/// Seed to feed [`fair::outcome_hash`] at resolve, for whichever mode the bet was locked in.
+///
+/// `oracle_request` is the key recorded on the pending account; `request_ai` the optional
+/// account passed to resolve. In backend mode `server_seed` must open `server_commit`.
+pub fn resolve_seed(
+    oracle_request: &Pubkey,
+    request_ai: Option<&AccountInfo<'_>>,
+    server_commit: &[u8; 32],
+    server_seed: &[u8; 32],
+) -> std::result::Result<[u8; 32], RandomnessError> {
+    if *oracle_request == NO_ORACLE {
+        if !fair::is_reveal_of(server_commit, server_seed) {
+            return Err(RandomnessError::BadReveal);
+        }
+        return Ok(*server_seed);
+    }
+    let ai = request_ai.ok_or(RandomnessError::WrongRequest)?;
+    if ai.key != oracle_request {
+        return Err(RandomnessError::WrongRequest);
+    }
+    fulfilled_randomness(ai)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORACLE: Pubkey = Pubkey::new_from_array([7u8; 32]);
+
+    fn request_data(seed: &[u8; 32], bump: u8, fulfilled: bool) -> Vec<u8> {
+        let mut data = hash(b"account:RandomnessRequest").to_bytes()[..8].to_vec();
+        data.extend_from_slice(&[1u8; 32]);
+        data.extend_from_slice(seed);
+        data.extend_from_slice(&[9u8; 32]);
+        data.extend_from_slice(&5u64.to_le_bytes());
+        data.extend_from_slice(&[fulfilled as u8, bump]);
+        data
+    }
+
+    fn read(key: &Pubkey, owner: &Pubkey, mut data: Vec<u8>) -> std::result::Result<[u8; 32], RandomnessError> {
+        let mut lamports = 0u64;
+        let ai = AccountInfo::new(key, false, false, &mut lamports, &mut data, owner, false, 0);
+        request_randomness(&ai)
+    }
+
+    #[test]
+    fn config_modes() {
+        let cfg = |mode, oracle_program| RandomnessConfig { mode, oracle_program };
+        assert!(RandomnessConfig::default().is_valid());
+        assert!(!cfg(MODE_BACKEND, ORACLE).is_valid());
+        for id in ORACLE_PROGRAM_IDS {
+            assert!(cfg(MODE_ORACLE, *id).is_valid());
+        }
+        // a program off the allow-list, even one implementing the oracle interface
+        assert!(!cfg(MODE_ORACLE, ORACLE).is_valid());
+        assert!(!cfg(MODE_ORACLE, Pubkey::default()).is_valid());
+        assert!(!cfg(2, ORACLE).is_valid());
+        assert_eq!(cfg(MODE_ORACLE, MOCK_ORACLE_PROGRAM_ID).is_valid(), cfg!(feature = "localnet"));
+    }
+
+    #[test]
+    fn reads_requests_of_listed_oracles_only() {
+        let seed = [3u8; 32];
+        let (key, bump) = Pubkey::find_program_address(&[REQUEST_SEED, &seed], &ORACLE);
+        let mut lamports = 0u64;
+        let mut data = request_data(&seed, bump, true);
+        let ai = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &ORACLE, false, 0);
+        assert_eq!(fulfilled_randomness(&ai), Err(RandomnessError::Malformed));
+    }
+
+    #[test]
+    fn reads_only_the_owners_own_request() {
+        let seed = [3u8; 32];
+        let (key, bump) = Pubkey::find_program_address(&[REQUEST_SEED, &seed], &ORACLE);
+        assert_eq!(key, request_address(&ORACLE, &seed));
+
+        assert_eq!(read(&key, &ORACLE, request_data(&seed, bump, true)), Ok([9u8; 32]));
+        assert_eq!(
+            read(&key, &ORACLE, request_data(&seed, bump, false)),
+            Err(RandomnessError::NotFulfilled)
+        );
+        // same bytes at another address, or owned by another program
+        let other = Pubkey::new_from_array([8u8; 32]);
+        assert_eq!(read(&other, &ORACLE, request_data(&seed, bump, true)), Err(RandomnessError::Malformed));
+        assert_eq!(read(&key, &other, request_data(&seed, bump, true)), Err(RandomnessError::Malformed));
+        // a request for a different seed than its address
+        assert_eq!(
+            read(&key, &ORACLE, request_data(&[4u8; 32], bump, true)),
+            Err(RandomnessError::Malformed)
+        );
+        assert_eq!(
+            read(&key, &ORACLE, request_data(&seed, bump, true)[..REQUEST_ACCOUNT_LEN - 1].to_vec()),
+            Err(RandomnessError::Malformed)
+        );
+    }
+
+    #[test]
+    fn backend_seed_must_open_the_commit() {
+        let seed = [5u8; 32];
+        let commit = fair::commitment(&seed);
+        assert_eq!(resolve_seed(&NO_ORACLE, None, &commit, &seed), Ok(seed));
+        assert_eq!(resolve_seed(&NO_ORACLE, None, &commit, &[6u8; 32]), Err(RandomnessError::BadReveal));
+        assert_eq!(
+            resolve_seed(&Pubkey::new_from_array([1u8; 32]), None, &commit, &seed),
+            Err(RandomnessError::WrongRequest)
+        );
+    }
+}
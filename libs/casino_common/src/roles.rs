@@ -0,0 +1,167 @@
+//! Role-based admin config shared by every program's `AdminConfig`.
+//!
+//! Each role holds one key. A change is proposed by the super-admin, can be cancelled while
+//! pending, and only takes effect once [`ROLE_TIMELOCK_SECS`] have passed, so a leaked or
+//! lost key can be rotated on-chain without redeploying the program.
+//!
+//! There is no in-place migration from the old single-key `AdminConfig { admin_pubkey }`: the
+//! account grew and nothing reallocates it, so an existing deployment must be redeployed under a
+//! fresh program id (new `[b"admin"]` PDA) and set up again with `init_admin`.
+
+use anchor_lang::prelude::*;
+
+/// Backend key that posts seed commitments; resolves are signed by the resolver set (see
+/// [`crate::cosign`]).
+pub const RESOLVER: u8 = 0;
+/// Proposes and cancels role changes (including its own).
+pub const SUPER_ADMIN: u8 = 1;
+/// Sets up the house-vault withdrawal multisig (withdrawals themselves need M-of-N owners).
+pub const TREASURER: u8 = 2;
+/// Pauses and unpauses games.
+pub const PAUSER: u8 = 3;
+/// Updates game limits and parameters.
+pub const CONFIG_MANAGER: u8 = 4;
+pub const ROLE_COUNT: usize = 5;
+
+/// Delay between proposing a role change and being able to apply it.
+pub const ROLE_TIMELOCK_SECS: i64 = 48 * 3_600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoleError {
+    /// Role id out of range
+    BadRole,
+    /// No change pending for the role, or its timelock has not elapsed
+    NotReady,
+}
+
+/// Proposed key for one role; `eta == 0` when nothing is pending.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PendingRole {
+    pub key: Pubkey,
+    pub eta: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct Roles {
+    pub keys: [Pubkey; ROLE_COUNT],
+    pub pending: [PendingRole; ROLE_COUNT],
+}
+
+impl Roles {
+    pub const SIZE: usize = 32 * ROLE_COUNT + (32 + 8) * ROLE_COUNT;
+
+    /// Fresh config: `resolver` posts seed commitments, `admin` holds every other role.
+    pub fn new(resolver: Pubkey, admin: Pubkey) -> Self {
+        let mut keys = [admin; ROLE_COUNT];
+        keys[RESOLVER as usize] = resolver;
+        Self { keys, pending: [PendingRole::default(); ROLE_COUNT] }
+    }
+
+    pub fn key(&self, role: u8) -> Pubkey {
+        self.keys[role as usize]
+    }
+
+    /// Resolver key as raw ed25519 bytes (what the signature checks compare against).
+    pub fn resolver_key(&self) -> [u8; 32] {
+        self.keys[RESOLVER as usize].to_bytes()
+    }
+
+    pub fn is(&self, role: u8, key: &Pubkey) -> bool {
+        (role as usize) < ROLE_COUNT && self.keys[role as usize] == *key
+    }
+
+    /// Pause switches may be flipped by the pauser or the super-admin.
+    pub fn can_pause(&self, key: &Pubkey) -> bool {
+        self.is(PAUSER, key) || self.is(SUPER_ADMIN, key)
+    }
+
+    /// Queue `new_key` for `role` (replacing any pending proposal); returns the eta.
+    pub fn propose(&mut self, role: u8, new_key: Pubkey, now: i64) -> std::result::Result<i64, RoleError> {
+        let slot = self.pending.get_mut(role as usize).ok_or(RoleError::BadRole)?;
+        let eta = now.saturating_add(ROLE_TIMELOCK_SECS);
+        *slot = PendingRole { key: new_key, eta };
+        Ok(eta)
+    }
+
+    /// Drop the pending proposal for `role`; returns the key that was proposed.
+    pub fn cancel(&mut self, role: u8) -> std::result::Result<Pubkey, RoleError> {
+        let slot = self.pending.get_mut(role as usize).ok_or(RoleError::BadRole)?;
+        if slot.eta == 0 {
+            return Err(RoleError::NotReady);
+        }
+        let proposed = slot.key;
+        *slot = PendingRole::default();
+        Ok(proposed)
+    }
+
+    /// Install the pending key for `role` once its timelock has elapsed; returns `(old, new)`.
+    pub fn apply(&mut self, role: u8, now: i64) -> std::result::Result<(Pubkey, Pubkey), RoleError> {
+        let slot = self.pending.get_mut(role as usize).ok_or(RoleError::BadRole)?;
+        if slot.eta == 0 || now < slot.eta {
+            return Err(RoleError::NotReady);
+        }
+        let new_key = slot.key;
+        *slot = PendingRole::default();
+        let old = std::mem::replace(&mut self.keys[role as usize], new_key);
+        Ok((old, new_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T0: i64 = 1_700_000_000;
+
+    fn key(b: u8) -> Pubkey {
+        Pubkey::new_from_array([b; 32])
+    }
+
+    #[test]
+    fn apply_waits_for_the_timelock() {
+        let mut roles = Roles::new(key(1), key(2));
+        assert_eq!(roles.apply(TREASURER, T0), Err(RoleError::NotReady));
+
+        let eta = roles.propose(TREASURER, key(3), T0).unwrap();
+        assert_eq!(eta, T0 + ROLE_TIMELOCK_SECS);
+        assert_eq!(roles.apply(TREASURER, eta - 1), Err(RoleError::NotReady));
+        assert!(roles.is(TREASURER, &key(2)));
+
+        assert_eq!(roles.apply(TREASURER, eta), Ok((key(2), key(3))));
+        assert!(roles.is(TREASURER, &key(3)));
+        // the proposal is consumed
+        assert_eq!(roles.apply(TREASURER, eta + 1), Err(RoleError::NotReady));
+    }
+
+    #[test]
+    fn cancel_needs_a_pending_change() {
+        let mut roles = Roles::new(key(1), key(2));
+        assert_eq!(roles.cancel(PAUSER), Err(RoleError::NotReady));
+
+        let eta = roles.propose(PAUSER, key(3), T0).unwrap();
+        assert_eq!(roles.cancel(PAUSER), Ok(key(3)));
+        assert_eq!(roles.apply(PAUSER, eta), Err(RoleError::NotReady));
+        assert!(roles.is(PAUSER, &key(2)));
+    }
+
+    #[test]
+    fn bad_role_is_rejected() {
+        let mut roles = Roles::new(key(1), key(2));
+        let bad = ROLE_COUNT as u8;
+        assert_eq!(roles.propose(bad, key(3), T0), Err(RoleError::BadRole));
+        assert_eq!(roles.cancel(bad), Err(RoleError::BadRole));
+        assert_eq!(roles.apply(bad, T0), Err(RoleError::BadRole));
+        assert!(!roles.is(bad, &key(2)));
+    }
+
+    #[test]
+    fn reproposal_resets_the_eta() {
+        let mut roles = Roles::new(key(1), key(2));
+        let first = roles.propose(SUPER_ADMIN, key(3), T0).unwrap();
+        let second = roles.propose(SUPER_ADMIN, key(4), T0 + 3_600).unwrap();
+        assert_eq!(second, first + 3_600);
+
+        assert_eq!(roles.apply(SUPER_ADMIN, first), Err(RoleError::NotReady));
+        assert_eq!(roles.apply(SUPER_ADMIN, second), Ok((key(2), key(4))));
+    }
+}
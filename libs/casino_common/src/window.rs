@@ -0,0 +1,103 @@
+//! Rolling 24h totals kept in hourly buckets.
+//!
+//! Bucket `h % BUCKETS` holds the amount recorded during hour `h`; buckets older than the window
+//! are zeroed as time advances, so [`RollingWindow::total`] is the sum over the last 24 hours
+//! (at one-hour granularity) without keeping a per-transfer history.
+
+use anchor_lang::prelude::*;
+
+pub const BUCKET_SECS: i64 = 3_600;
+pub const BUCKETS: usize = 24;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RollingWindow {
+    pub buckets: [u64; BUCKETS],
+    /// Hour index (`unix / BUCKET_SECS`) of the most recent bucket written.
+    pub head: i64,
+}
+
+impl RollingWindow {
+    pub const SIZE: usize = 8 * BUCKETS + 8;
+
+    /// Drop buckets that fell out of the window ending at `now`.
+    fn roll(&mut self, now: i64) {
+        let hour = now.div_euclid(BUCKET_SECS);
+        if hour <= self.head {
+            return;
+        }
+        if hour - self.head >= BUCKETS as i64 {
+            self.buckets = [0; BUCKETS];
+        } else {
+            for h in self.head + 1..=hour {
+                self.buckets[h.rem_euclid(BUCKETS as i64) as usize] = 0;
+            }
+        }
+        self.head = hour;
+    }
+
+    /// Sum recorded over the 24 hours ending at `now`.
+    pub fn total(&mut self, now: i64) -> u64 {
+        self.total_last(now, BUCKETS)
+    }
+
+    /// Sum recorded over the last `hours` buckets (the current hour included, at most 24)
+    /// ending at `now`.
+    pub fn total_last(&mut self, now: i64, hours: usize) -> u64 {
+        self.roll(now);
+        (0..hours.min(BUCKETS) as i64).fold(0u64, |acc, back| {
+            acc.saturating_add(self.buckets[(self.head - back).rem_euclid(BUCKETS as i64) as usize])
+        })
+    }
+
+    pub fn add(&mut self, now: i64, amount: u64) {
+        self.roll(now);
+        let i = now.div_euclid(BUCKET_SECS).rem_euclid(BUCKETS as i64) as usize;
+        self.buckets[i] = self.buckets[i].saturating_add(amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const H: i64 = BUCKET_SECS;
+    const T0: i64 = 1_700_000_000 / H * H;
+
+    #[test]
+    fn short_gap_keeps_the_buckets_still_in_the_window() {
+        let mut w = RollingWindow::default();
+        w.add(T0, 5);
+        w.add(T0 + 10 * H, 7);
+        assert_eq!(w.total(T0 + 10 * H), 12);
+        // hour T0 falls out once 24 hours have passed since it
+        assert_eq!(w.total(T0 + 23 * H + H - 1), 12);
+        assert_eq!(w.total(T0 + 24 * H), 7);
+    }
+
+    #[test]
+    fn gap_of_a_day_or_more_clears_everything() {
+        let mut w = RollingWindow::default();
+        w.add(T0, 5);
+        w.add(T0 + H, 7);
+        assert_eq!(w.total(T0 + 25 * H), 0);
+
+        let mut w = RollingWindow::default();
+        w.add(T0, 5);
+        w.add(T0 + 30 * H, 3);
+        assert_eq!(w.total(T0 + 30 * H), 3);
+    }
+
+    #[test]
+    fn total_last_counts_only_recent_hours() {
+        let mut w = RollingWindow::default();
+        for h in 0..5 {
+            w.add(T0 + h * H, 1 << h);
+        }
+        let now = T0 + 4 * H;
+        assert_eq!(w.total_last(now, 1), 16);
+        assert_eq!(w.total_last(now, 2), 16 + 8);
+        assert_eq!(w.total_last(now, 5), 31);
+        assert_eq!(w.total_last(now, 100), 31);
+        assert_eq!(w.total_last(now, 0), 0);
+    }
+}
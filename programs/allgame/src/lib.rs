@@ -1,59 +1,208 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program::System;
 use anchor_lang::solana_program::{
-    ed25519_program,
     program::{invoke, invoke_signed},
     system_instruction,
-    sysvar::instructions::{
-        load_current_index_checked,
-        load_instruction_at_checked,
-        ID as SYSVAR_INSTRUCTIONS_ID,
-    },
+    sysvar::{instructions::ID as SYSVAR_INSTRUCTIONS_ID, slot_hashes},
 };
+use casino_common::config::GameLimits;
+use casino_common::cosign::{self, Cosigners, PendingCosigners};
+use casino_common::deploy;
+use casino_common::house::{BetLimits, House};
+use casino_common::randomness::RandomnessConfig;
+use casino_common::roles::{self, Roles};
+use casino_common::window::RollingWindow;
+use casino_common::{admin, ed25519, expiry, exposure, fair, message, pause, payout, randomness};
 
 declare_id!("5vgLU8GyehUkziMaKHCtyPu6YZgo11wct8rTHLdz4z1"); // ← your deployed ID
 
 // ---- constants ----
-const MAX_PAYOUT_LAMPORTS: u64 = 5_000_000_000_000; // 5 SOL max payout
-const MIN_BET_LAMPORTS: u64  = 50_000;              // 0.00005 SOL
-const MAX_BET_LAMPORTS: u64  = 5_000_000_000;       // 5 SOL
-const FEE_REIMBURSE_LAMPORTS: u64 = 1_400_000;      // unchanged
+const DEFAULT_FEE_REIMBURSE_LAMPORTS: u64 = 1_400_000; // user_vault → fee payer per lock, until reconfigured
+const MAX_FEE_REIMBURSE_LAMPORTS: u64 = 5_000_000;  // most a lock may charge for fees (0.005 SOL)
+const HOUSE_RESERVE_LAMPORTS: u64 = 1_000_000_000; // bankroll never promised to open bets (1 SOL)
+const DEFAULT_OUTFLOW_THRESHOLD: u64 = 500_000_000_000; // net house outflow that trips the breaker (500 SOL)
+const DEFAULT_OUTFLOW_WINDOW_HOURS: u8 = 1;      // hours of outflow the breaker sums
+const REFUND_GRACE_SECS: i64 = 600;              // after expiry_unix, before anyone may refund a pending bet
+const REFUND_GRACE_SLOTS: u64 = 1_500;           // same grace past an optional expiry_slot (~10 min)
+// Longest lock → expiry window per game, indexed by GAME_* id
+const MAX_TTL_SECS: [i64; 6]  = [300, 3_600, 600, 600, 300, 300];
+const MAX_TTL_SLOTS: [u64; 6] = [750, 9_000, 1_500, 1_500, 750, 750];
+const MAX_MULTISIG_OWNERS: usize = 10;           // house-withdraw multisig owner set (N ≤ 10)
+const MAX_PROPOSAL_TTL_SECS: i64 = 7 * 86_400;   // withdraw proposals expire within a week
+const MAX_TREASURY_DESTINATIONS: usize = 8;      // house-withdraw allow-list size
+const TREASURY_TIMELOCK_SECS: i64 = 48 * 3_600;  // new destinations / higher daily limits take effect after this
+const LARGE_WITHDRAW_DELAY_SECS: i64 = 24 * 3_600; // withdraws over the rolling 24h limit wait this long
 // user_vault → server fee payer (set 0 to disable)
 
-// Hard-coded admin pubkey (Base58: 5jHZt8Jc6rahAdVVuwbBYRaNJ8XfN6g89jKP5jpvJq3)
-const ADMIN_PUBKEY_BYTES: [u8; 32] = [
-    1, 54, 34, 193, 9, 155, 8, 216,
-    107, 220, 252, 98, 107, 138, 215, 172,
-    230, 182, 102, 126, 251, 3, 32, 49,
-    224, 149, 75, 152, 12, 204, 228, 190,
-];
+// Canonical domain tags for off-chain signing (one per game)
+const DICE_DOMAIN_TAG: &[u8]   = b"CASINO_DICE_V1";
+const MINES_DOMAIN_TAG: &[u8]  = b"CASINO_MINES_V1";
+const FLIP_DOMAIN_TAG: &[u8]   = b"CASINO_FLIP_V1";
+const CRASH_DOMAIN_TAG: &[u8]  = b"CASINO_CRASH_V1";
+const PLINKO_DOMAIN_TAG: &[u8] = b"CASINO_PLINKO_V1";
+const SLOTS_DOMAIN_TAG: &[u8]  = b"CASINO_SLOTS_V1";
+// Player-signed bet intent (lets a relayer lock from a UserVault without the player signing the tx)
+const INTENT_DOMAIN_TAG: &[u8] = b"CASINO_INTENT_V1";
+
+// Game ids (bet intents, per-game settings)
+const GAME_DICE: u8   = 0;
+const GAME_MINES: u8  = 1;
+const GAME_FLIP: u8   = 2;
+const GAME_CRASH: u8  = 3;
+const GAME_PLINKO: u8 = 4;
+const GAME_SLOTS: u8  = 5;
+const ALL_GAMES_MASK: u8 = (1 << (GAME_SLOTS + 1)) - 1;
+const GAME_COUNT: usize = GAME_SLOTS as usize + 1;
 
 #[error_code]
 pub enum CasinoErr {
-    #[msg("Invalid ed25519 pre-instruction")] InvalidEd25519,
     #[msg("Expired signature/nonce")]        Expired,
     #[msg("Bad params")]                     BadParams,
     #[msg("Vault mismatch")]                 VaultMismatch,
     #[msg("Insufficient vault balance")]     InsufficientVault,
+    #[msg("Debit would leave the vault below its rent-exempt minimum")] RentReserve,
     #[msg("Payout sanity check failed")]     BadPayout,
     #[msg("Already settled or not found")]   BadPending,
+    #[msg("Player did not authorize this bet")] NoConsent,
+    #[msg("Session key not valid for this bet")] BadSession,
+    #[msg("Session key expired")]            SessionExpired,
+    #[msg("Bet exceeds session allowance")]  SessionLimit,
+    #[msg("Signer not authorized")]          Unauthorized,
+    #[msg("Bet not yet refundable")]         NotRefundable,
+    #[msg("Expiry outside the allowed window")] BadExpiry,
+    #[msg("Nonce must exceed the last one used")] StaleNonce,
+    #[msg("Only the upgrade authority or deployer may initialize")] NotDeployer,
+    #[msg("Game is paused")]                 Paused,
+    #[msg("Withdraw proposal expired")]      ProposalExpired,
+    #[msg("Not enough multisig approvals")]  NotEnoughApprovals,
+    #[msg("Destination not on the treasury allow-list")] DestinationNotAllowed,
+    #[msg("Queued withdraw still in its delay")] WithdrawDelayed,
+    #[msg("Game is disabled")]               GameDisabled,
 }
 
 // ---- accounts ----
+/// Role keys (resolver, super-admin, treasurer, pauser, config-manager) with timelocked rotation.
 #[account]
-pub struct AdminConfig { pub admin_pubkey: [u8; 32] }
+pub struct AdminConfig { pub roles: Roles }
+impl AdminConfig { pub const LEN: usize = 8 + Roles::SIZE; }
+
+/// Incident pause switches, the worst-case payout still owed on open bets, the bankroll share
+/// one bet may win and the net-outflow breaker that pauses every game (see casino_common::house),
+/// plus each game's lock fee reimbursement; read by every lock and resolve.
+#[account]
+pub struct GameState {
+    pub house: House,
+    pub bump: u8,
+    pub fee_reimburse: [u64; GAME_COUNT], // lamports a lock moves from the user vault to the fee payer, by game id
+}
+impl GameState { pub const LEN: usize = 8 + House::SIZE + 1 + 8 * GAME_COUNT; }
+
+/// Resolver key registry, one key per backend signer instance, each with an enabled flag and a
+/// per-epoch payout budget; a payout above the threshold needs K-of-N co-signatures, smaller
+/// payouts any one usable key (see casino_common::cosign).
+#[account]
+pub struct ResolverSet { pub cosigners: Cosigners, pub bump: u8, pub pending: PendingCosigners }
+impl ResolverSet { pub const LEN: usize = 8 + Cosigners::SIZE + 1 + PendingCosigners::SIZE; }
+
+/// One game's bet limits, house edge, on/off switch (see casino_common::config) and randomness
+/// source (see casino_common::randomness) at `[b"game_config", &[game]]`; `version` counts
+/// updates. Read by that game's locks.
+#[account]
+pub struct GameConfig { pub game: u8, pub enabled: bool, pub version: u32, pub limits: GameLimits, pub randomness: RandomnessConfig, pub bump: u8 }
+impl GameConfig { pub const LEN: usize = 8 + 1 + 1 + 4 + GameLimits::SIZE + RandomnessConfig::SIZE + 1; }
+
+/// M-of-N owner set whose approvals are required to move funds out of the house vault.
+#[account]
+pub struct Multisig {
+    pub owners: [Pubkey; MAX_MULTISIG_OWNERS], // first `owner_count` entries are used
+    pub owner_count: u8,
+    pub threshold: u8,
+    pub next_proposal: u64,                    // id (PDA seed) of the next WithdrawProposal
+    pub bump: u8,
+}
+impl Multisig {
+    pub const LEN: usize = 8 + 32 * MAX_MULTISIG_OWNERS + 1 + 1 + 8 + 1;
+
+    fn owner_bit(&self, key: &Pubkey) -> Option<u16> {
+        self.owners[..self.owner_count as usize].iter().position(|o| o == key).map(|i| 1 << i)
+    }
+}
+
+/// Pending house-vault withdrawal; `approvals` has bit `i` set once `owners[i]` approved.
+/// Closed (rent back to the proposer) when executed or after it expires. A withdraw that would
+/// exceed the treasury's rolling 24h limit is queued instead: `release_at` is set and it may be
+/// executed (still needing the threshold) once that time has passed.
+#[account]
+pub struct WithdrawProposal {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub expires_at: i64,
+    pub approvals: u16,
+    pub release_at: i64, // 0 = not queued
+    pub bump: u8,
+}
+impl WithdrawProposal { pub const LEN: usize = 8 + 8 + 32 + 32 + 8 + 8 + 2 + 8 + 1; }
+
+/// Allow-listed house-withdraw destination, usable from `active_at`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct Destination { pub key: Pubkey, pub active_at: i64 }
+
+/// Where and how fast the house vault may be drained (managed by the treasurer role).
+#[account]
+pub struct Treasury {
+    pub destinations: [Destination; MAX_TREASURY_DESTINATIONS], // first `destination_count` used
+    pub destination_count: u8,
+    pub daily_limit: u64,      // max executed withdraws per rolling 24h before queueing
+    pub pending_limit: u64,    // higher limit waiting for `limit_eta` (0 eta = none)
+    pub limit_eta: i64,
+    pub outflow: RollingWindow,
+    pub bump: u8,
+}
+impl Treasury {
+    pub const LEN: usize = 8 + (32 + 8) * MAX_TREASURY_DESTINATIONS + 1 + 8 + 8 + 8 + RollingWindow::SIZE + 1;
+
+    fn destinations(&self) -> &[Destination] {
+        &self.destinations[..self.destination_count as usize]
+    }
+
+    fn is_listed(&self, key: &Pubkey) -> bool {
+        self.destinations().iter().any(|d| d.key == *key)
+    }
+
+    fn is_active(&self, key: &Pubkey, now: i64) -> bool {
+        self.destinations().iter().any(|d| d.key == *key && now >= d.active_at)
+    }
+}
 
 #[account]
 pub struct UserVault {
     pub owner: Pubkey,
     pub bump:  u8,
-    pub _r1:   [u8; 7],
-    pub _r2:   [u8; 32],
+    pub fee_waived: bool,      // VIP: locks skip the fee reimbursement
+    pub _r1:   [u8; 6],
+    pub seed_commit: [u8; 32], // sha256(server_seed) for the next bet; zeroed when a lock consumes it
     pub _r3:   i64,
-    pub _r4:   u64,
+    pub last_nonce: u64,       // highest bet nonce locked from this vault (any game)
     pub _r5:   u64,
 }
-impl UserVault { pub const LEN: usize = 8 + 32 + 1 + 7 + 32 + 8 + 8 + 8; }
+impl UserVault { pub const LEN: usize = 8 + 32 + 1 + 1 + 6 + 32 + 8 + 8 + 8; }
+
+/// Owner-approved session key: `delegate` may place bets from the owner's UserVault
+/// until `expires_at`, within `max_bet` per bet and `remaining` in total, for the games
+/// whose bit (`1 << GAME_*`) is set in `games_mask`.
+#[account]
+pub struct SessionKey {
+    pub owner:      Pubkey,
+    pub delegate:   Pubkey,
+    pub expires_at: i64,
+    pub max_bet:    u64,
+    pub remaining:  u64,
+    pub games_mask: u8,
+    pub bump:       u8,
+}
+impl SessionKey { pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 1; }
 
 // ---- pending accounts per game ----
 #[account]
@@ -64,9 +213,17 @@ pub struct PendingDice {
     pub target:  u8,  // 2..98
     pub nonce:   u64,
     pub expiry_unix: i64,
+    pub server_commit: [u8; 32], // seed commitment consumed at lock, opened at resolve
+    pub client_seed: [u8; 32],   // player-chosen entropy
+    pub slot_hash: [u8; 32],     // latest SlotHashes entry at lock time
+    pub oracle_request: Pubkey,  // oracle-mode request account; randomness::NO_ORACLE otherwise
+    pub expiry_slot: u64,        // optional slot deadline; expiry::NO_SLOT_DEADLINE if unused
+    pub exposure: u64,           // worst-case payout reserved at lock; released on resolve/refund
+    pub rent_payer: Pubkey,      // fee_payer that funded this account at lock; its rent goes back there
+    pub house_edge_bps: u16,     // game config's edge at lock; prices the resolve payout
     pub settled: bool,
 }
-impl PendingDice { pub const LEN: usize = 8 + 32 + 8 + 1 + 1 + 8 + 8 + 1; }
+impl PendingDice { pub const LEN: usize = 8 + 32 + 8 + 1 + 1 + 8 + 8 + 32 + 32 + 32 + 32 + 8 + 8 + 32 + 2 + 1; }
 
 #[account]
 pub struct PendingMines {
@@ -77,9 +234,17 @@ pub struct PendingMines {
     pub mines: u8,
     pub nonce: u64,
     pub expiry_unix: i64,
+    pub server_commit: [u8; 32], // seed commitment consumed at lock, opened at resolve
+    pub client_seed: [u8; 32],   // player-chosen entropy
+    pub slot_hash: [u8; 32],     // latest SlotHashes entry at lock time
+    pub oracle_request: Pubkey,  // oracle-mode request account; randomness::NO_ORACLE otherwise
+    pub expiry_slot: u64,        // optional slot deadline; expiry::NO_SLOT_DEADLINE if unused
+    pub exposure: u64,           // worst-case payout reserved at lock; released on resolve/refund
+    pub rent_payer: Pubkey,      // fee_payer that funded this account at lock; its rent goes back there
+    pub house_edge_bps: u16,     // game config's edge at lock; prices the resolve payout
     pub settled: bool,
 }
-impl PendingMines { pub const LEN: usize = 8 + 32 + 8 + 1 + 1 + 1 + 8 + 8 + 1; }
+impl PendingMines { pub const LEN: usize = 8 + 32 + 8 + 1 + 1 + 1 + 8 + 8 + 32 + 32 + 32 + 32 + 8 + 8 + 32 + 2 + 1; }
 
 #[account]
 pub struct PendingFlip {
@@ -88,9 +253,17 @@ pub struct PendingFlip {
     pub side: u8,        // 0=heads,1=tails (player pick)
     pub nonce: u64,
     pub expiry_unix: i64,
+    pub server_commit: [u8; 32], // seed commitment consumed at lock, opened at resolve
+    pub client_seed: [u8; 32],   // player-chosen entropy
+    pub slot_hash: [u8; 32],     // latest SlotHashes entry at lock time
+    pub oracle_request: Pubkey,  // oracle-mode request account; randomness::NO_ORACLE otherwise
+    pub expiry_slot: u64,        // optional slot deadline; expiry::NO_SLOT_DEADLINE if unused
+    pub exposure: u64,           // worst-case payout reserved at lock; released on resolve/refund
+    pub rent_payer: Pubkey,      // fee_payer that funded this account at lock; its rent goes back there
+    pub house_edge_bps: u16,     // game config's edge at lock; prices the resolve payout
     pub settled: bool,
 }
-impl PendingFlip { pub const LEN: usize = 8 + 32 + 8 + 1 + 8 + 8 + 1; }
+impl PendingFlip { pub const LEN: usize = 8 + 32 + 8 + 1 + 8 + 8 + 32 + 32 + 32 + 32 + 8 + 8 + 32 + 2 + 1; }
 
 #[account]
 pub struct PendingCrash {
@@ -98,9 +271,19 @@ pub struct PendingCrash {
     pub amount: u64,
     pub nonce: u64,
     pub expiry_unix: i64,
+    pub server_commit: [u8; 32], // seed commitment consumed at lock, opened at resolve
+    pub client_seed: [u8; 32],   // player-chosen entropy
+    pub slot_hash: [u8; 32],     // latest SlotHashes entry at lock time
+    pub oracle_request: Pubkey,  // oracle-mode request account; randomness::NO_ORACLE otherwise
+    pub expiry_slot: u64,        // optional slot deadline; expiry::NO_SLOT_DEADLINE if unused
+    pub exposure: u64,           // worst-case payout reserved at lock; released on resolve/refund
+    pub rent_payer: Pubkey,      // fee_payer that funded this account at lock; its rent goes back there
+    pub house_edge_bps: u16,     // crash config's edge at lock; fixes the derived crash point
+    pub min_multiplier_bps: u32, // crash config's cash-out range at lock; bounds the resolve
+    pub max_multiplier_bps: u32,
     pub settled: bool,
 }
-impl PendingCrash { pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 1; }
+impl PendingCrash { pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 32 + 32 + 32 + 32 + 8 + 8 + 32 + 2 + 4 + 4 + 1; }
 
 #[account]
 pub struct PendingPlinko {
@@ -111,9 +294,16 @@ pub struct PendingPlinko {
     pub difficulty: u8,   // 0..5 (easy→extreme)
     pub nonce: u64,
     pub expiry_unix: i64,
+    pub server_commit: [u8; 32], // seed commitment consumed at lock, opened at resolve
+    pub client_seed: [u8; 32],   // player-chosen entropy
+    pub slot_hash: [u8; 32],     // latest SlotHashes entry at lock time
+    pub oracle_request: Pubkey,  // oracle-mode request account; randomness::NO_ORACLE otherwise
+    pub expiry_slot: u64,        // optional slot deadline; expiry::NO_SLOT_DEADLINE if unused
+    pub exposure: u64,           // worst-case payout reserved at lock; released on resolve/refund
+    pub rent_payer: Pubkey,      // fee_payer that funded this account at lock; its rent goes back there
     pub settled: bool,
 }
-impl PendingPlinko { pub const LEN: usize = 8 + 32 + 8 + 2 + 1 + 1 + 8 + 8 + 1; }
+impl PendingPlinko { pub const LEN: usize = 8 + 32 + 8 + 2 + 1 + 1 + 8 + 8 + 32 + 32 + 32 + 32 + 8 + 8 + 32 + 1; }
 
 #[account]
 pub struct PendingSlots {
@@ -121,77 +311,294 @@ pub struct PendingSlots {
     pub amount: u64,
     pub nonce: u64,
     pub expiry_unix: i64,
+    pub server_commit: [u8; 32], // seed commitment consumed at lock, opened at resolve
+    pub client_seed: [u8; 32],   // player-chosen entropy
+    pub slot_hash: [u8; 32],     // latest SlotHashes entry at lock time
+    pub oracle_request: Pubkey,  // oracle-mode request account; randomness::NO_ORACLE otherwise
+    pub expiry_slot: u64,        // optional slot deadline; expiry::NO_SLOT_DEADLINE if unused
+    pub exposure: u64,           // worst-case payout reserved at lock; released on resolve/refund
+    pub rent_payer: Pubkey,      // fee_payer that funded this account at lock; its rent goes back there
+    pub house_edge_bps: u16,     // game config's edge at lock; prices the resolve payout
     pub settled: bool,
 }
-impl PendingSlots { pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 1; }
+impl PendingSlots { pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 32 + 32 + 32 + 32 + 8 + 8 + 32 + 2 + 1; }
 
 // ---- events ----
-#[event] pub struct DiceLocked   { pub player: Pubkey, pub amount: u64, pub bet_type: u8, pub target: u8, pub nonce: u64 }
+#[event] pub struct DiceLocked   { pub player: Pubkey, pub amount: u64, pub bet_type: u8, pub target: u8, pub nonce: u64, pub slot_hash: [u8; 32] }
 #[event] pub struct DiceResolved { pub player: Pubkey, pub win: bool,   pub roll: u8,    pub payout: u64, pub nonce: u64 }
 
-#[event] pub struct MinesLocked   { pub player: Pubkey, pub amount: u64, pub rows: u8, pub cols: u8, pub mines: u8, pub nonce: u64 }
-#[event] pub struct MinesResolved { pub player: Pubkey, pub payout: u64, pub checksum: u8, pub nonce: u64 }
+#[event] pub struct MinesLocked   { pub player: Pubkey, pub amount: u64, pub rows: u8, pub cols: u8, pub mines: u8, pub nonce: u64, pub slot_hash: [u8; 32] }
+#[event] pub struct MinesResolved { pub player: Pubkey, pub payout: u64, pub mines_layout: u64, pub nonce: u64 }
 
-#[event] pub struct FlipLocked   { pub player: Pubkey, pub amount: u64, pub side: u8, pub nonce: u64 }
+#[event] pub struct FlipLocked   { pub player: Pubkey, pub amount: u64, pub side: u8, pub nonce: u64, pub slot_hash: [u8; 32] }
 #[event] pub struct FlipResolved { pub player: Pubkey, pub winner_side: u8, pub payout: u64, pub nonce: u64 }
 
-#[event] pub struct CrashLocked   { pub player: Pubkey, pub amount: u64, pub nonce: u64 }
-#[event] pub struct CrashResolved { pub player: Pubkey, pub multiplier_bps: u32, pub payout: u64, pub nonce: u64 }
-
-#[event] pub struct PlinkoLocked   { pub player: Pubkey, pub unit_amount: u64, pub balls: u16, pub rows: u8, pub difficulty: u8, pub nonce: u64 }
-#[event] pub struct PlinkoResolved { pub player: Pubkey, pub total_payout: u64, pub checksum: u8, pub nonce: u64 }
-
-#[event] pub struct SlotsLocked    { pub player: Pubkey, pub amount: u64, pub nonce: u64 }
-#[event] pub struct SlotsResolved  { pub player: Pubkey, pub payout: u64, pub checksum: u8, pub nonce: u64 }
-
-// NEW: admin house vault withdraw event
+#[event] pub struct CrashLocked   { pub player: Pubkey, pub amount: u64, pub nonce: u64, pub slot_hash: [u8; 32] }
+#[event] pub struct CrashResolved { pub player: Pubkey, pub multiplier_bps: u32, pub crash_point_bps: u32, pub payout: u64, pub nonce: u64 }
+
+#[event] pub struct PlinkoLocked   { pub player: Pubkey, pub unit_amount: u64, pub balls: u16, pub rows: u8, pub difficulty: u8, pub nonce: u64, pub slot_hash: [u8; 32] }
+#[event] pub struct PlinkoResolved { pub player: Pubkey, pub total_payout: u64, pub outcome: [u8; 32], pub nonce: u64 }
+
+#[event] pub struct SlotsLocked    { pub player: Pubkey, pub amount: u64, pub nonce: u64, pub slot_hash: [u8; 32] }
+#[event] pub struct SlotsResolved  { pub player: Pubkey, pub payout: u64, pub outcome: [u8; 32], pub nonce: u64 }
+
+#[event] pub struct BetRefunded      { pub player: Pubkey, pub game: u8, pub amount: u64, pub nonce: u64 }
+
+#[event] pub struct SeedCommitted    { pub player: Pubkey, pub commitment: [u8; 32] }
+
+#[event] pub struct SessionCreated   { pub owner: Pubkey, pub delegate: Pubkey, pub expires_at: i64, pub max_bet: u64, pub total_cap: u64, pub games_mask: u8 }
+#[event] pub struct SessionToppedUp  { pub owner: Pubkey, pub delegate: Pubkey, pub expires_at: i64, pub remaining: u64 }
+#[event] pub struct SessionRevoked   { pub owner: Pubkey, pub delegate: Pubkey, pub unspent: u64 }
+
+#[event] pub struct RoleChangeProposed  { pub role: u8, pub current: Pubkey, pub proposed: Pubkey, pub eta: i64 }
+#[event] pub struct RoleChangeCancelled { pub role: u8, pub proposed: Pubkey }
+#[event] pub struct RoleChanged         { pub role: u8, pub previous: Pubkey, pub current: Pubkey }
+#[event] pub struct GamePaused          { pub by: Pubkey, pub games_mask: u8, pub halt_resolve: bool, pub reason: u16 }
+#[event] pub struct GameUnpaused        { pub by: Pubkey, pub games_mask: u8, pub reason: u16 }
+#[event] pub struct MaxPayoutChanged    { pub by: Pubkey, pub previous_bps: u16, pub current_bps: u16 }
+#[event] pub struct BreakerChanged      { pub by: Pubkey, pub threshold: u64, pub window_hours: u8 }
+#[event] pub struct BreakerTripped      { pub net_outflow: u64, pub threshold: u64, pub window_hours: u8 }
+#[event] pub struct FeeReimburseChanged { pub by: Pubkey, pub games_mask: u8, pub lamports: u64 }
+#[event] pub struct FeeWaiverChanged    { pub by: Pubkey, pub owner: Pubkey, pub waived: bool }
+#[event] pub struct GameConfigChanged   { pub by: Pubkey, pub game: u8, pub version: u32, pub enabled: bool, pub limits: GameLimits }
+#[event] pub struct RandomnessChanged   { pub by: Pubkey, pub game: u8, pub version: u32, pub randomness: RandomnessConfig }
+#[event] pub struct ResolverSetChanged  { pub by: Pubkey, pub keys: Vec<Pubkey>, pub quorum: u8, pub threshold: u64, pub epoch_budget: u64 }
+#[event] pub struct ResolverKeyChanged  { pub by: Pubkey, pub key: Pubkey, pub enabled: bool, pub epoch_budget: u64 }
+#[event] pub struct ResolverSetProposed { pub by: Pubkey, pub keys: Vec<Pubkey>, pub quorum: u8, pub threshold: u64, pub epoch_budget: u64, pub eta: i64 }
+#[event] pub struct ResolverSetCancelled { pub by: Pubkey }
+#[event] pub struct ResolverSetApplied  { pub keys: Vec<Pubkey>, pub quorum: u8, pub threshold: u64 }
+#[event] pub struct ResolverKeyChangeProposed { pub by: Pubkey, pub key: Pubkey, pub enabled: bool, pub epoch_budget: u64, pub eta: i64 }
+
+#[event] pub struct MultisigCreated         { pub owners: Vec<Pubkey>, pub threshold: u8 }
+#[event] pub struct HouseWithdrawProposed   { pub id: u64, pub proposer: Pubkey, pub destination: Pubkey, pub amount: u64, pub expires_at: i64 }
+#[event] pub struct HouseWithdrawApproved   { pub id: u64, pub owner: Pubkey, pub approvals: u8 }
+#[event] pub struct HouseWithdrawRevoked    { pub id: u64, pub owner: Pubkey, pub approvals: u8 }
+#[event] pub struct HouseWithdrawDiscarded  { pub id: u64 }
+#[event] pub struct HouseWithdrawQueued     { pub id: u64, pub amount: u64, pub release_at: i64 }
+
+#[event] pub struct DestinationAdded        { pub key: Pubkey, pub active_at: i64 }
+#[event] pub struct DestinationRemoved      { pub key: Pubkey }
+#[event] pub struct DailyLimitProposed      { pub limit: u64, pub eta: i64 }
+#[event] pub struct DailyLimitChanged       { pub previous: u64, pub current: u64 }
+
+// house vault withdraw, emitted when a proposal is executed
 #[event]
 pub struct HouseWithdrawn {
+    pub id: u64,
     pub to: Pubkey,
     pub amount: u64,
+    pub approvals: u16, // owner bits that approved
 }
 
 // ---- utils ----
+/// Lamports `ai` can give up while staying rent-exempt for its current data size.
+fn withdrawable(ai: &AccountInfo<'_>) -> Result<u64> {
+    let reserve = Rent::get()?.minimum_balance(ai.data_len());
+    Ok(ai.lamports().saturating_sub(reserve))
+}
+
+/// Every vault debit must leave the account at or above its rent-exempt minimum.
+fn require_debit(from: &AccountInfo<'_>, amount: u64) -> Result<()> {
+    require!(from.lamports() >= amount, CasinoErr::InsufficientVault);
+    require!(amount <= withdrawable(from)?, CasinoErr::RentReserve);
+    Ok(())
+}
+
 fn safe_move_lamports(from: &AccountInfo<'_>, to: &AccountInfo<'_>, amount: u64) -> Result<()> {
     require!(amount > 0, CasinoErr::BadParams);
+    require_debit(from, amount)?;
     let mut from_lamports = from.try_borrow_mut_lamports()?;
     let mut to_lamports   = to.try_borrow_mut_lamports()?;
-    require!(**from_lamports >= amount, CasinoErr::InsufficientVault);
     **from_lamports -= amount;
     **to_lamports   += amount;
     Ok(())
 }
 
-fn require_ed25519_present(sys_ix_ai: &AccountInfo<'_>, hinted_idx: u8) -> Result<()> {
-    let hinted_ok = load_instruction_at_checked(hinted_idx as usize, sys_ix_ai)
-        .map(|ix| ix.program_id == ed25519_program::id())
-        .unwrap_or(false);
-    if hinted_ok { return Ok(()); }
-    let cur_idx = load_current_index_checked(sys_ix_ai)?;
-    for i in 0..cur_idx {
-        if let Ok(ix) = load_instruction_at_checked(i as usize, sys_ix_ai) {
-            if ix.program_id == ed25519_program::id() { return Ok(()); }
+/// Pending PDAs are closed on resolve, so a vault's bet nonces must strictly increase
+/// or an old `(player, nonce)` (and any resolver signature over it) could be re-opened.
+fn advance_nonce(user_vault: &mut UserVault, nonce: u64) -> Result<()> {
+    require!(nonce > user_vault.last_nonce, CasinoErr::StaleNonce);
+    user_vault.last_nonce = nonce;
+    Ok(())
+}
+
+/// Fee reimbursement a lock of `game` moves from the user vault to the fee payer (none for
+/// fee-waived vaults).
+fn lock_fee(game_state: &GameState, user_vault: &UserVault, game: u8) -> u64 {
+    if user_vault.fee_waived { 0 } else { game_state.fee_reimburse[game as usize] }
+}
+
+/// Lock-time deadline check against the game's maximum TTL.
+fn require_lock_window(game: u8, expiry_unix: i64, expiry_slot: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let g = game as usize;
+    require!(
+        expiry::lock_window_ok(&clock, expiry_unix, MAX_TTL_SECS[g], expiry_slot, MAX_TTL_SLOTS[g]),
+        CasinoErr::BadExpiry
+    );
+    Ok(())
+}
+
+/// Pending bets become refundable once a deadline is past by its grace period.
+fn require_refundable(settled: bool, expiry_unix: i64, expiry_slot: u64) -> Result<()> {
+    require!(!settled, CasinoErr::BadPending);
+    let clock = Clock::get()?;
+    require!(
+        expiry::is_refundable(&clock, expiry_unix, expiry_slot, REFUND_GRACE_SECS, REFUND_GRACE_SLOTS),
+        CasinoErr::NotRefundable
+    );
+    Ok(())
+}
+
+/// Limits a new bet must meet; fails while the game is disabled.
+fn lock_limits(cfg: &GameConfig) -> Result<GameLimits> {
+    require!(cfg.enabled, CasinoErr::GameDisabled);
+    Ok(cfg.limits)
+}
+
+/// Limits of a config passed to a view, checked to be `game`'s.
+fn view_limits(cfg: &GameConfig, game: u8) -> Result<GameLimits> {
+    require!(cfg.game == game, CasinoErr::BadParams);
+    Ok(cfg.limits)
+}
+
+/// A game's config must keep its parameter range within what the game's fairness and
+/// exposure maths support.
+fn limits_supported(game: u8, limits: &GameLimits) -> bool {
+    limits.is_valid()
+        && match game {
+            GAME_DICE => limits.params_within(2, 98),
+            GAME_MINES => limits.params_within(2, 8),
+            GAME_FLIP => true,
+            GAME_CRASH => limits.params_within(10_000, u32::MAX),
+            GAME_PLINKO => limits.params_within(8, 16) && limits.max_count >= 1,
+            GAME_SLOTS => limits.max_param >= payout::SLOTS_TOP_LINE_BPS,
+            _ => false,
         }
+}
+
+/// Feed a resolved bet's stake and payout into the outflow breaker. If it trips, every game's
+/// locks and resolves are paused (this resolve still settles) until a pauser unpauses.
+fn record_outflow(game_state: &mut GameState, staked: u64, paid: u64) -> Result<()> {
+    if let Some(net_outflow) = game_state.house.record_outflow(staked, paid)? {
+        emit!(BreakerTripped {
+            net_outflow,
+            threshold: game_state.house.breaker.threshold,
+            window_hours: game_state.house.breaker.window_hours,
+        });
     }
-    err!(CasinoErr::InvalidEd25519)
+    Ok(())
+}
+
+/// Return an expired bet's principal from the house vault to the player's user_vault.
+fn refund_principal<'info>(
+    house_vault: &SystemAccount<'info>,
+    user_vault: &Account<'info, UserVault>,
+    system_program: &Program<'info, System>,
+    house_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let ix = system_instruction::transfer(&house_vault.key(), &user_vault.key(), amount);
+    invoke_signed(
+        &ix,
+        &[
+            house_vault.to_account_info(),
+            user_vault.to_account_info(),
+            system_program.to_account_info(),
+        ],
+        &[&[b"vault", &[house_bump]]],
+    )?;
+    Ok(())
+}
+
+/// A bet drawn from a UserVault needs the player's consent, in order of precedence:
+/// - the player signs the lock itself;
+/// - a live session key of the player signs it (`stake` is charged to the session allowance);
+/// - the ed25519 run directly before it holds the player's signature over the intent
+///   `CASINO_INTENT_V1 | program_id | player | game | params | client_seed | nonce | expiry`.
+#[allow(clippy::too_many_arguments)]
+fn require_player_consent(
+    sys_ix_ai: &AccountInfo<'_>,
+    player: &AccountInfo<'_>,
+    session: Option<&mut Account<'_, SessionKey>>,
+    session_signer: Option<&Signer<'_>>,
+    program_id: &Pubkey,
+    game: u8,
+    params: &[u8],
+    client_seed: &[u8; 32],
+    stake: u64,
+    nonce: u64,
+    expiry_unix: i64,
+) -> Result<()> {
+    if player.is_signer { return Ok(()); }
+
+    if let Some(session) = session {
+        let signer = session_signer.ok_or(error!(CasinoErr::BadSession))?;
+        require!(session.owner == *player.key, CasinoErr::BadSession);
+        require!(session.delegate == signer.key(), CasinoErr::BadSession);
+        require!(session.games_mask & (1 << game) != 0, CasinoErr::BadSession);
+        require!(Clock::get()?.unix_timestamp < session.expires_at, CasinoErr::SessionExpired);
+        require!(stake <= session.max_bet, CasinoErr::SessionLimit);
+        session.remaining = session.remaining.checked_sub(stake).ok_or(CasinoErr::SessionLimit)?;
+        return Ok(());
+    }
+
+    let mut intent = Vec::with_capacity(INTENT_DOMAIN_TAG.len() + 32 * 3 + 1 + params.len() + 8 + 8);
+    intent.extend_from_slice(INTENT_DOMAIN_TAG);
+    intent.extend_from_slice(program_id.as_ref());
+    intent.extend_from_slice(player.key.as_ref());
+    intent.push(game);
+    intent.extend_from_slice(params);
+    intent.extend_from_slice(client_seed);
+    intent.extend_from_slice(&nonce.to_le_bytes());
+    intent.extend_from_slice(&expiry_unix.to_le_bytes());
+
+    ed25519::require_signed_by(sys_ix_ai, &player.key.to_bytes(), &intent)
+        .map_err(|_| error!(CasinoErr::NoConsent))
+}
+
+// ---- canonical bet params per game (shared by bet intents and resolve messages) ----
+fn dice_params(amount: u64, bet_type: u8, target: u8) -> Vec<u8> {
+    [&amount.to_le_bytes()[..], &[bet_type, target]].concat()
+}
+fn mines_params(amount: u64, rows: u8, cols: u8, mines: u8) -> Vec<u8> {
+    [&amount.to_le_bytes()[..], &[rows, cols, mines]].concat()
+}
+fn flip_params(amount: u64, side: u8) -> Vec<u8> {
+    [&amount.to_le_bytes()[..], &[side]].concat()
+}
+fn crash_params(amount: u64) -> Vec<u8> {
+    amount.to_le_bytes().to_vec()
+}
+fn plinko_params(unit_amount: u64, balls: u16, rows: u8, difficulty: u8) -> Vec<u8> {
+    [&unit_amount.to_le_bytes()[..], &(balls as u32).to_le_bytes(), &[rows, difficulty]].concat()
+}
+fn slots_params(amount: u64) -> Vec<u8> {
+    amount.to_le_bytes().to_vec()
 }
 
 // ---- contexts (shared) ----
 #[derive(Accounts)]
 pub struct InitAdmin<'info> {
     #[account(mut, signer)] pub authority: SystemAccount<'info>,
-    #[account(init, payer=authority, space=8+32, seeds=[b"admin"], bump)]
+    #[account(init, payer=authority, space=AdminConfig::LEN, seeds=[b"admin"], bump)]
     pub admin_config: Account<'info, AdminConfig>,
     pub system_program: Program<'info, System>,
+    /// CHECK: this program's ProgramData (upgrade-authority check); optional when the deployer key signs
+    pub program_data: Option<UncheckedAccount<'info>>,
 }
 
-// NEW: update an existing admin_config without re-init
+/// Super-admin proposes or cancels a role change
 #[derive(Accounts)]
-pub struct UpdateAdmin<'info> {
-    /// Hard-coded admin must sign to rotate admin_config
-    #[account(mut, signer)]
-    pub authority: SystemAccount<'info>,
+pub struct ManageRole<'info> {
+    #[account(signer)] pub authority: SystemAccount<'info>,
+    #[account(mut, seeds=[b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+}
 
+/// Anyone may apply a proposed role change once its timelock has elapsed
+#[derive(Accounts)]
+pub struct ApplyRole<'info> {
     #[account(mut, seeds=[b"admin"], bump)]
     pub admin_config: Account<'info, AdminConfig>,
 }
@@ -203,6 +610,8 @@ pub struct InitHouseVault<'info> {
     /// CHECK: system-owned lamports PDA
     pub house_vault: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
+    /// CHECK: this program's ProgramData (upgrade-authority check); optional when the deployer key signs
+    pub program_data: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
@@ -229,24 +638,229 @@ pub struct WithdrawFromVault<'info> {
     pub system_program: Program<'info, System>,
 }
 
-// NEW: house vault withdraw context (admin → any destination)
+/// Read-only: a user vault's withdrawable balance (simulate `vault_balance` to read it)
 #[derive(Accounts)]
-pub struct HouseWithdraw<'info> {
-    /// Admin signer (must match hard-coded ADMIN_PUBKEY_BYTES)
-    #[account(mut, signer)]
-    pub admin: SystemAccount<'info>,
+pub struct QueryUserVault<'info> {
+    #[account(seeds=[b"user_vault", user_vault.owner.as_ref()], bump=user_vault.bump)]
+    pub user_vault: Account<'info, UserVault>,
+}
+
+/// Treasurer sets up the house-withdraw owner set (once)
+#[derive(Accounts)]
+pub struct InitMultisig<'info> {
+    #[account(mut, signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(init, payer=authority, space=Multisig::LEN, seeds=[b"multisig"], bump)]
+    pub multisig: Account<'info, Multisig>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Multisig owner opens a house vault withdraw proposal (counts as their approval)
+#[derive(Accounts)]
+pub struct ProposeHouseWithdraw<'info> {
+    #[account(mut, signer)] pub proposer: SystemAccount<'info>,
+    #[account(mut, seeds=[b"multisig"], bump=multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(init, payer=proposer, space=WithdrawProposal::LEN, seeds=[b"withdraw", &multisig.next_proposal.to_le_bytes()], bump)]
+    pub proposal: Account<'info, WithdrawProposal>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds=[b"treasury"], bump=treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+}
 
+/// Multisig owner approves or revokes their approval
+#[derive(Accounts)]
+pub struct VoteHouseWithdraw<'info> {
+    #[account(signer)] pub owner: SystemAccount<'info>,
+    #[account(seeds=[b"multisig"], bump=multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut, seeds=[b"withdraw", &proposal.id.to_le_bytes()], bump=proposal.bump)]
+    pub proposal: Account<'info, WithdrawProposal>,
+}
+
+/// Any multisig owner executes (or, over the daily limit, queues) a proposal that reached the
+/// threshold (house vault → allow-listed destination)
+#[derive(Accounts)]
+pub struct HouseWithdraw<'info> {
+    #[account(signer)] pub owner: SystemAccount<'info>,
+    #[account(seeds=[b"multisig"], bump=multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    // closed by hand once executed (a queued proposal stays open)
+    #[account(mut, has_one=proposer, has_one=destination, seeds=[b"withdraw", &proposal.id.to_le_bytes()], bump=proposal.bump)]
+    pub proposal: Account<'info, WithdrawProposal>,
+    #[account(mut)] pub proposer: SystemAccount<'info>,
     /// House vault PDA
     #[account(mut, seeds = [b"vault"], bump)]
     pub house_vault: SystemAccount<'info>,
-
-    /// Where funds go – can be ANY wallet
+    /// Where funds go, as fixed in the proposal
     #[account(mut)]
     pub destination: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(mut, seeds=[b"treasury"], bump=treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+}
 
+/// Treasurer creates the treasury allow-list / limits (once)
+#[derive(Accounts)]
+pub struct InitTreasury<'info> {
+    #[account(mut, signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(init, payer=authority, space=Treasury::LEN, seeds=[b"treasury"], bump)]
+    pub treasury: Account<'info, Treasury>,
     pub system_program: Program<'info, System>,
 }
 
+/// Treasurer edits the allow-list or daily limit
+#[derive(Accounts)]
+pub struct ManageTreasury<'info> {
+    #[account(signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(mut, seeds=[b"treasury"], bump=treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+}
+
+/// Anyone may apply a proposed daily-limit increase once its timelock has elapsed
+#[derive(Accounts)]
+pub struct ApplyDailyLimit<'info> {
+    #[account(mut, seeds=[b"treasury"], bump=treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+}
+
+/// Anyone may discard a dead proposal (rent back to the proposer)
+#[derive(Accounts)]
+pub struct DiscardHouseWithdraw<'info> {
+    #[account(seeds=[b"multisig"], bump=multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut, close=proposer, has_one=proposer, seeds=[b"withdraw", &proposal.id.to_le_bytes()], bump=proposal.bump)]
+    pub proposal: Account<'info, WithdrawProposal>,
+    #[account(mut)] pub proposer: SystemAccount<'info>,
+}
+
+/// Pauser or super-admin creates the (unpaused) GameState
+#[derive(Accounts)]
+pub struct InitGameState<'info> {
+    #[account(mut, signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(init, payer=authority, space=GameState::LEN, seeds=[b"game_state"], bump)]
+    pub game_state: Account<'info, GameState>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Pauser or super-admin flips pause switches
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    #[account(signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+}
+
+/// Config manager sets bankroll limits
+#[derive(Accounts)]
+pub struct SetLimits<'info> {
+    #[account(signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+}
+
+/// Read-only: bankroll limits for a game setting (simulate the instruction to read them)
+#[derive(Accounts)]
+pub struct QueryLimits<'info> {
+    #[account(seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
+    #[account(seeds=[b"game_state"], bump=game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+    #[account(seeds=[b"game_config", game_config.game.to_le_bytes().as_ref()], bump=game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
+}
+
+/// Config manager creates one game's config (once per game)
+#[derive(Accounts)]
+#[instruction(game: u8)]
+pub struct InitGameConfig<'info> {
+    #[account(mut, signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(init, payer=authority, space=GameConfig::LEN, seeds=[b"game_config", game.to_le_bytes().as_ref()], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Config manager updates one game's config (super admin: its randomness source)
+#[derive(Accounts)]
+pub struct UpdateGameConfig<'info> {
+    #[account(signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(mut, seeds=[b"game_config", game_config.game.to_le_bytes().as_ref()], bump=game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
+}
+
+/// Config manager waives or restores a player's lock fee reimbursement
+#[derive(Accounts)]
+pub struct SetFeeWaiver<'info> {
+    #[account(signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(mut, seeds=[b"user_vault", user_vault.owner.as_ref()], bump=user_vault.bump)]
+    pub user_vault: Account<'info, UserVault>,
+}
+
+/// Super-admin creates the resolver key registry (once)
+#[derive(Accounts)]
+pub struct InitResolverSet<'info> {
+    #[account(mut, signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(init, payer=authority, space=ResolverSet::LEN, seeds=[b"resolvers"], bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Super-admin replaces the resolver registry or changes one key; pauser may disable a key
+#[derive(Accounts)]
+pub struct SetResolverSet<'info> {
+    #[account(signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(mut, seeds=[b"resolvers"], bump=resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+}
+
+/// Anyone may apply a pending resolver-set change once its timelock has elapsed
+#[derive(Accounts)]
+pub struct ApplyResolverSet<'info> {
+    #[account(mut, seeds=[b"resolvers"], bump=resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+}
+
+#[derive(Accounts)]
+pub struct CommitSeed<'info> {
+    /// Resolver wallet (must hold admin_config's resolver role)
+    #[account(signer)] pub resolver: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(mut, seeds=[b"user_vault", user_vault.owner.as_ref()], bump=user_vault.bump)]
+    pub user_vault: Account<'info, UserVault>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: SessionArgs)]
+pub struct SessionCreate<'info> {
+    #[account(mut, signer)] pub owner: SystemAccount<'info>,
+    #[account(init, payer=owner, space=SessionKey::LEN, seeds=[b"session", owner.key().as_ref(), args.delegate.as_ref()], bump)]
+    pub session: Account<'info, SessionKey>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SessionTopUp<'info> {
+    #[account(signer)] pub owner: SystemAccount<'info>,
+    #[account(mut, has_one=owner, seeds=[b"session", owner.key().as_ref(), session.delegate.as_ref()], bump=session.bump)]
+    pub session: Account<'info, SessionKey>,
+}
+
+#[derive(Accounts)]
+pub struct SessionRevoke<'info> {
+    #[account(mut, signer)] pub owner: SystemAccount<'info>,
+    #[account(mut, has_one=owner, close=owner, seeds=[b"session", owner.key().as_ref(), session.delegate.as_ref()], bump=session.bump)]
+    pub session: Account<'info, SessionKey>,
+}
+
 // ---- args ----
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct ActivateArgs { pub initial_deposit: u64 }
@@ -255,10 +869,34 @@ pub struct DepositArgs  { pub amount: u64 }
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct WithdrawArgs { pub amount: u64 }
 
-// NEW: args for admin house vault withdraw
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SessionArgs {
+    pub delegate:   Pubkey,
+    pub expires_at: i64,
+    pub max_bet:    u64,
+    pub total_cap:  u64,
+    pub games_mask: u8,   // bit (1 << GAME_*) per allowed game
+}
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SessionTopUpArgs {
+    pub amount:     u64,  // added to the remaining allowance
+    pub expires_at: i64,  // 0 keeps the current expiry
+}
+
+// user vault balance (returned by `vault_balance`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct VaultBalance {
+    pub balance: u64,
+    pub rent_reserve: u64, // rent-exempt minimum every debit leaves in place
+    pub withdrawable: u64, // balance less rent_reserve
+}
+
+// house vault withdraw proposal
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct HouseWithdrawArgs {
     pub amount: u64,
+    pub destination: Pubkey, // must be on the treasury allow-list
+    pub expires_at: i64,     // at most MAX_PROPOSAL_TTL_SECS ahead
 }
 
 // dice
@@ -269,13 +907,13 @@ pub struct DiceLockArgs {
     pub target:  u8,       // 2..98
     pub nonce: u64,
     pub expiry_unix: i64,
-    pub ed25519_instr_index: u8,
+    pub client_seed: [u8; 32],
+    pub expiry_slot: u64, // 0 = unix deadline only
 }
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct DiceResolveArgs {
-    pub roll: u8,
+    pub server_seed: [u8; 32], // opens pending.server_commit (zeroes for oracle-mode bets)
     pub payout: u64,
-    pub ed25519_instr_index: u8,
 }
 
 // mines
@@ -285,13 +923,14 @@ pub struct MinesLockArgs {
     pub rows: u8, pub cols: u8, pub mines: u8,
     pub nonce: u64,
     pub expiry_unix: i64,
-    pub ed25519_instr_index: u8,
+    pub client_seed: [u8; 32],
+    pub expiry_slot: u64, // 0 = unix deadline only
 }
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct MinesResolveArgs {
-    pub checksum: u8,
+    pub server_seed: [u8; 32], // opens pending.server_commit (zeroes for oracle-mode bets)
+    pub picks: u64,            // tiles opened before cash-out, as a bitmask like the layout
     pub payout: u64,
-    pub ed25519_instr_index: u8,
 }
 
 // flip (coinflip)
@@ -301,13 +940,13 @@ pub struct FlipLockArgs {
     pub side: u8,
     pub nonce: u64,
     pub expiry_unix: i64,
-    pub ed25519_instr_index: u8,
+    pub client_seed: [u8; 32],
+    pub expiry_slot: u64, // 0 = unix deadline only
 }
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct FlipResolveArgs {
-    pub winner_side: u8,
+    pub server_seed: [u8; 32], // opens pending.server_commit (zeroes for oracle-mode bets)
     pub payout: u64,
-    pub ed25519_instr_index: u8,
 }
 
 // crash
@@ -316,13 +955,14 @@ pub struct CrashLockArgs {
     pub bet_amount: u64,
     pub nonce: u64,
     pub expiry_unix: i64,
-    pub ed25519_instr_index: u8,
+    pub client_seed: [u8; 32],
+    pub expiry_slot: u64, // 0 = unix deadline only
 }
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct CrashResolveArgs {
-    pub multiplier_bps: u32,
+    pub server_seed: [u8; 32], // opens pending.server_commit (zeroes for oracle-mode bets)
+    pub multiplier_bps: u32,   // player's cash-out point
     pub payout: u64,
-    pub ed25519_instr_index: u8,
 }
 
 // plinko
@@ -331,13 +971,13 @@ pub struct PlinkoLockArgs {
     pub unit_amount: u64, pub balls: u16, pub rows: u8, pub difficulty: u8,
     pub nonce: u64,
     pub expiry_unix: i64,
-    pub ed25519_instr_index: u8,
+    pub client_seed: [u8; 32],
+    pub expiry_slot: u64, // 0 = unix deadline only
 }
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct PlinkoResolveArgs {
-    pub checksum: u8,
+    pub server_seed: [u8; 32], // opens pending.server_commit (zeroes for oracle-mode bets)
     pub total_payout: u64,
-    pub ed25519_instr_index: u8,
 }
 
 // slots
@@ -346,13 +986,13 @@ pub struct SlotsLockArgs {
     pub bet_amount: u64,
     pub nonce: u64,
     pub expiry_unix: i64,
-    pub ed25519_instr_index: u8,
+    pub client_seed: [u8; 32],
+    pub expiry_slot: u64, // 0 = unix deadline only
 }
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct SlotsResolveArgs {
-    pub checksum: u8,
+    pub server_seed: [u8; 32], // opens pending.server_commit (zeroes for oracle-mode bets)
     pub payout: u64,
-    pub ed25519_instr_index: u8,
 }
 
 // ---- program ----
@@ -360,21 +1000,50 @@ pub struct SlotsResolveArgs {
 pub mod casino {
     use super::*;
 
+    /// `admin_pubkey` becomes the resolver, which only posts seed commitments (resolves are
+    /// signed by the resolver set); the signer holds every other role until rotated.
     pub fn init_admin(ctx: Context<InitAdmin>, admin_pubkey: [u8; 32]) -> Result<()> {
-        ctx.accounts.admin_config.admin_pubkey = admin_pubkey;
+        require!(
+            deploy::may_initialize(ctx.program_id, &ctx.accounts.authority.key(), ctx.accounts.program_data.as_deref()),
+            CasinoErr::NotDeployer
+        );
+        let roles = Roles::new(Pubkey::new_from_array(admin_pubkey), ctx.accounts.authority.key());
+        ctx.accounts.admin_config.roles = roles;
+        for (role, key) in roles.keys.iter().enumerate() {
+            emit!(RoleChanged { role: role as u8, previous: Pubkey::default(), current: *key });
+        }
         Ok(())
     }
 
-    // NEW: update existing AdminConfig without re-init
-    pub fn update_admin(ctx: Context<UpdateAdmin>, new_admin_pubkey: [u8; 32]) -> Result<()> {
-        let hardcoded = Pubkey::new_from_array(ADMIN_PUBKEY_BYTES);
-        require!(ctx.accounts.authority.key() == hardcoded, CasinoErr::BadParams);
+    /// Super-admin queues `new_key` for `role` (see `casino_common::roles`); it can be
+    /// applied once `roles::ROLE_TIMELOCK_SECS` have passed.
+    pub fn propose_role(ctx: Context<ManageRole>, role: u8, new_key: Pubkey) -> Result<()> {
+        let roles = &mut ctx.accounts.admin_config.roles;
+        let eta = admin::propose_role(roles, &ctx.accounts.authority.key(), role, new_key)?;
+        emit!(RoleChangeProposed { role, current: roles.key(role), proposed: new_key, eta });
+        Ok(())
+    }
 
-        ctx.accounts.admin_config.admin_pubkey = new_admin_pubkey;
+    /// Super-admin drops a pending role change before it is applied.
+    pub fn cancel_role(ctx: Context<ManageRole>, role: u8) -> Result<()> {
+        let roles = &mut ctx.accounts.admin_config.roles;
+        let proposed = admin::cancel_role(roles, &ctx.accounts.authority.key(), role)?;
+        emit!(RoleChangeCancelled { role, proposed });
+        Ok(())
+    }
+
+    /// Permissionless: installs a proposed key once its timelock has elapsed.
+    pub fn apply_role(ctx: Context<ApplyRole>, role: u8) -> Result<()> {
+        let (previous, current) = admin::apply_role(&mut ctx.accounts.admin_config.roles, role)?;
+        emit!(RoleChanged { role, previous, current });
         Ok(())
     }
 
     pub fn init_house_vault(ctx: Context<InitHouseVault>) -> Result<()> {
+        require!(
+            deploy::may_initialize(ctx.program_id, &ctx.accounts.payer.key(), ctx.accounts.program_data.as_deref()),
+            CasinoErr::NotDeployer
+        );
         let rent = Rent::get()?.minimum_balance(0);
         let bump = ctx.bumps.house_vault;
         let ix = system_instruction::create_account(
@@ -443,20 +1112,431 @@ pub mod casino {
         safe_move_lamports(&from, &to, args.amount)
     }
 
-    // ✅ secure admin house vault withdraw (to ANY destination) using invoke_signed
-    pub fn house_withdraw(ctx: Context<HouseWithdraw>, args: HouseWithdrawArgs) -> Result<()> {
+    /// Withdraws everything above the user vault's rent-exempt minimum.
+    pub fn withdraw_all(ctx: Context<WithdrawFromVault>) -> Result<()> {
+        let from = ctx.accounts.user_vault.to_account_info();
+        let to   = ctx.accounts.player.to_account_info();
+        let amount = withdrawable(&from)?;
+        require!(amount > 0, CasinoErr::InsufficientVault);
+        safe_move_lamports(&from, &to, amount)
+    }
+
+    /// Read-only (simulate): the user vault's balance, its rent-exempt reserve and the part of
+    /// it that can be withdrawn or staked.
+    pub fn vault_balance(ctx: Context<QueryUserVault>) -> Result<VaultBalance> {
+        let ai = ctx.accounts.user_vault.to_account_info();
+        let withdrawable = withdrawable(&ai)?;
+        Ok(VaultBalance { balance: ai.lamports(), rent_reserve: ai.lamports() - withdrawable, withdrawable })
+    }
+
+    /// Creates the (unpaused) GameState PDA that every lock and resolve reads.
+    pub fn init_game_state(ctx: Context<InitGameState>) -> Result<()> {
+        admin::require_pauser(&ctx.accounts.admin_config.roles, &ctx.accounts.authority.key())?;
+        let gs = &mut ctx.accounts.game_state;
+        gs.house = House::new(DEFAULT_OUTFLOW_THRESHOLD, DEFAULT_OUTFLOW_WINDOW_HOURS);
+        gs.fee_reimburse = [DEFAULT_FEE_REIMBURSE_LAMPORTS; GAME_COUNT];
+        gs.bump = ctx.bumps.game_state;
+        Ok(())
+    }
+
+    /// Halts new bets for `games_mask` (`1 << GAME_*` bits, or `pause::GLOBAL` for all games),
+    /// and their resolves too when `halt_resolve`. `reason` is an incident code echoed in the
+    /// event. Withdrawals and expired-bet refunds stay open.
+    pub fn pause(ctx: Context<SetPause>, games_mask: u8, halt_resolve: bool, reason: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        require!(games_mask != 0 && games_mask & !(ALL_GAMES_MASK | pause::GLOBAL) == 0, CasinoErr::BadParams);
+        admin::pause(&ctx.accounts.admin_config.roles, &by, &mut ctx.accounts.game_state.house, games_mask, halt_resolve)?;
+        emit!(GamePaused { by, games_mask, halt_resolve, reason });
+        Ok(())
+    }
+
+    pub fn unpause(ctx: Context<SetPause>, games_mask: u8, reason: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        require!(games_mask != 0 && games_mask & !(ALL_GAMES_MASK | pause::GLOBAL) == 0, CasinoErr::BadParams);
+        admin::unpause(&ctx.accounts.admin_config.roles, &by, &mut ctx.accounts.game_state.house, games_mask)?;
+        emit!(GameUnpaused { by, games_mask, reason });
+        Ok(())
+    }
+
+    /// Config manager sets the share of the house vault (bps) one bet may stand to win; every
+    /// game's maximum bet follows from it.
+    pub fn set_max_payout_bps(ctx: Context<SetLimits>, max_payout_bps: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let house = &mut ctx.accounts.game_state.house;
+        let previous_bps = admin::set_max_payout_bps(&ctx.accounts.admin_config.roles, &by, house, max_payout_bps)?;
+        emit!(MaxPayoutChanged { by, previous_bps, current_bps: max_payout_bps });
+        Ok(())
+    }
+
+    /// Config manager sets the net outflow (payouts minus stakes, lamports; 0 disables) over the
+    /// last `window_hours` that trips the circuit breaker and pauses every game.
+    pub fn set_outflow_breaker(ctx: Context<SetLimits>, threshold: u64, window_hours: u8) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let house = &mut ctx.accounts.game_state.house;
+        admin::set_outflow_breaker(&ctx.accounts.admin_config.roles, &by, house, threshold, window_hours)?;
+        emit!(BreakerChanged { by, threshold, window_hours });
+        Ok(())
+    }
+
+    /// Config manager sets the fee reimbursement (lamports, at most MAX_FEE_REIMBURSE_LAMPORTS)
+    /// that a lock of each game in `games_mask` moves from the user vault to the fee payer.
+    pub fn set_fee_reimburse(ctx: Context<SetLimits>, games_mask: u8, lamports: u64) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        require!(games_mask != 0 && games_mask & !ALL_GAMES_MASK == 0, CasinoErr::BadParams);
+        require!(lamports <= MAX_FEE_REIMBURSE_LAMPORTS, CasinoErr::BadParams);
+        let gs = &mut ctx.accounts.game_state;
+        for (game, fee) in gs.fee_reimburse.iter_mut().enumerate() {
+            if games_mask & (1 << game) != 0 {
+                *fee = lamports;
+            }
+        }
+        emit!(FeeReimburseChanged { by, games_mask, lamports });
+        Ok(())
+    }
+
+    /// Config manager waives (VIP) or restores the lock fee reimbursement for one user vault.
+    pub fn set_fee_waiver(ctx: Context<SetFeeWaiver>, waived: bool) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        let uv = &mut ctx.accounts.user_vault;
+        uv.fee_waived = waived;
+        emit!(FeeWaiverChanged { by, owner: uv.owner, waived });
+        Ok(())
+    }
+
+    // ---- per-game config ----
+    /// Config manager creates `game`'s config: bet limits, house edge and whether it takes
+    /// bets. The game's locks fail until it exists.
+    pub fn init_game_config(ctx: Context<InitGameConfig>, game: u8, enabled: bool, limits: GameLimits) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        require!(limits_supported(game, &limits), CasinoErr::BadParams);
+        let cfg = &mut ctx.accounts.game_config;
+        cfg.game = game;
+        cfg.enabled = enabled;
+        cfg.version = 1;
+        cfg.limits = limits;
+        cfg.randomness = RandomnessConfig::default();
+        cfg.bump = ctx.bumps.game_config;
+        emit!(GameConfigChanged { by, game, version: cfg.version, enabled, limits });
+        Ok(())
+    }
+
+    /// Config manager replaces a game's limits and enabled flag and bumps its `version`. Open
+    /// bets keep the exposure (and, for crash, the house edge and cash-out range) recorded at lock.
+    pub fn update_game_config(ctx: Context<UpdateGameConfig>, enabled: bool, limits: GameLimits) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        let cfg = &mut ctx.accounts.game_config;
+        require!(limits_supported(cfg.game, &limits), CasinoErr::BadParams);
+        cfg.enabled = enabled;
+        cfg.limits = limits;
+        cfg.version = cfg.version.saturating_add(1);
+        emit!(GameConfigChanged { by, game: cfg.game, version: cfg.version, enabled, limits });
+        Ok(())
+    }
+
+    /// Super-admin picks a game's randomness source: backend commit/reveal, or an oracle program
+    /// on the build's allow-list (randomness::ORACLE_PROGRAM_IDS) whose requests locks create and
+    /// resolves read. Bets already locked keep the source they were locked with.
+    pub fn set_randomness(ctx: Context<UpdateGameConfig>, randomness: RandomnessConfig) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::SUPER_ADMIN, &by)?;
+        require!(randomness.is_valid(), CasinoErr::BadParams);
+        let cfg = &mut ctx.accounts.game_config;
+        cfg.randomness = randomness;
+        cfg.version = cfg.version.saturating_add(1);
+        emit!(RandomnessChanged { by, game: cfg.game, version: cfg.version, randomness });
+        Ok(())
+    }
+
+    // ---- resolver co-signing ----
+    /// Super-admin registers up to `MAX_COSIGNERS` resolver keys, one per backend signer
+    /// instance, each allowed to sign `epoch_budget` lamports of payouts per epoch (0 = no limit).
+    /// A payout above `threshold` lamports needs `quorum` of them to sign the resolve message.
+    pub fn init_resolver_set(
+        ctx: Context<InitResolverSet>,
+        keys: Vec<Pubkey>,
+        quorum: u8,
+        threshold: u64,
+        epoch_budget: u64,
+    ) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut ctx.accounts.resolver_set;
+        rs.cosigners = admin::new_resolver_set(&ctx.accounts.admin_config.roles, &by, &keys, quorum, threshold, epoch_budget)?;
+        rs.bump = ctx.bumps.resolver_set;
+        emit!(ResolverSetChanged { by, keys, quorum, threshold, epoch_budget });
+        Ok(())
+    }
+
+    /// Super-admin queues a replacement for the resolver keys, quorum, co-signing threshold and
+    /// budgets (every key enabled); `apply_resolver_set` installs it after ROLE_TIMELOCK_SECS.
+    pub fn propose_resolver_set(
+        ctx: Context<SetResolverSet>,
+        keys: Vec<Pubkey>,
+        quorum: u8,
+        threshold: u64,
+        epoch_budget: u64,
+    ) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let roles = &ctx.accounts.admin_config.roles;
+        let pending = &mut ctx.accounts.resolver_set.pending;
+        let eta = admin::propose_resolver_set(roles, &by, pending, &keys, quorum, threshold, epoch_budget)?;
+        emit!(ResolverSetProposed { by, keys, quorum, threshold, epoch_budget, eta });
+        Ok(())
+    }
+
+    /// Super-admin drops the pending resolver-set change (a proposed set or queued key changes).
+    pub fn cancel_resolver_set(ctx: Context<SetResolverSet>) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::cancel_resolver_set(&ctx.accounts.admin_config.roles, &by, &mut ctx.accounts.resolver_set.pending)?;
+        emit!(ResolverSetCancelled { by });
+        Ok(())
+    }
+
+    /// Permissionless: installs the pending resolver set once its timelock has elapsed.
+    pub fn apply_resolver_set(ctx: Context<ApplyResolverSet>) -> Result<()> {
+        let rs = &mut *ctx.accounts.resolver_set;
+        admin::apply_resolver_set(&mut rs.cosigners, &mut rs.pending)?;
+        let cosigners = &rs.cosigners;
+        let keys = cosigners.keys[..cosigners.count as usize].iter().map(|k| k.key).collect();
+        emit!(ResolverSetApplied { keys, quorum: cosigners.quorum, threshold: cosigners.threshold });
+        Ok(())
+    }
+
+    /// Super-admin sets one resolver key's enabled flag and per-epoch budget. Disabling it or
+    /// lowering the budget applies at once; enabling it or raising the budget is queued behind
+    /// the timelock for `apply_resolver_set`.
+    pub fn set_resolver_key(ctx: Context<SetResolverSet>, key: Pubkey, enabled: bool, epoch_budget: u64) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut *ctx.accounts.resolver_set;
+        let roles = &ctx.accounts.admin_config.roles;
+        match admin::set_resolver_key(roles, &by, &mut rs.cosigners, &mut rs.pending, &key, enabled, epoch_budget)? {
+            None => emit!(ResolverKeyChanged { by, key, enabled, epoch_budget }),
+            Some(eta) => emit!(ResolverKeyChangeProposed { by, key, enabled, epoch_budget, eta }),
+        }
+        Ok(())
+    }
+
+    /// Pauser or super-admin disables one resolver key (e.g. a compromised signer instance),
+    /// in the live set and in any pending one.
+    pub fn disable_resolver_key(ctx: Context<SetResolverSet>, key: Pubkey) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut *ctx.accounts.resolver_set;
+        let roles = &ctx.accounts.admin_config.roles;
+        let epoch_budget = admin::disable_resolver_key(roles, &by, &mut rs.cosigners, &mut rs.pending, &key)?;
+        emit!(ResolverKeyChanged { by, key, enabled: false, epoch_budget });
+        Ok(())
+    }
+
+    // ---- bankroll limit views (read-only; simulate to get the returned BetLimits) ----
+    pub fn dice_limits(ctx: Context<QueryLimits>, bet_type: u8, target: u8) -> Result<BetLimits> {
+        let cfg = view_limits(&ctx.accounts.game_config, GAME_DICE)?;
+        require!(bet_type <= 1 && cfg.param_ok(target as u32), CasinoErr::BadParams);
+        ctx.accounts.game_state.house.limits_for(&ctx.accounts.house_vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, cfg.max_bet, |s| exposure::dice(s, bet_type, target))
+    }
+
+    pub fn mines_limits(ctx: Context<QueryLimits>, rows: u8, cols: u8, mines: u8) -> Result<BetLimits> {
+        let cfg = view_limits(&ctx.accounts.game_config, GAME_MINES)?;
+        require!(cfg.param_ok(rows as u32) && cfg.param_ok(cols as u32), CasinoErr::BadParams);
+        require!(mines >= 1 && mines < rows * cols, CasinoErr::BadParams);
+        ctx.accounts.game_state.house.limits_for(&ctx.accounts.house_vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, cfg.max_bet, |s| exposure::mines(s, rows * cols, mines))
+    }
+
+    pub fn flip_limits(ctx: Context<QueryLimits>) -> Result<BetLimits> {
+        let cfg = view_limits(&ctx.accounts.game_config, GAME_FLIP)?;
+        ctx.accounts.game_state.house.limits_for(&ctx.accounts.house_vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, cfg.max_bet, exposure::flip)
+    }
+
+    pub fn crash_limits(ctx: Context<QueryLimits>) -> Result<BetLimits> {
+        let cfg = view_limits(&ctx.accounts.game_config, GAME_CRASH)?;
+        ctx.accounts.game_state.house.limits_for(&ctx.accounts.house_vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, cfg.max_bet, |s| exposure::multiple(s, cfg.max_param))
+    }
+
+    /// `max_bet` is the total stake across all balls.
+    pub fn plinko_limits(ctx: Context<QueryLimits>, rows: u8, difficulty: u8) -> Result<BetLimits> {
+        let cfg = view_limits(&ctx.accounts.game_config, GAME_PLINKO)?;
+        require!(cfg.param_ok(rows as u32) && difficulty <= 5, CasinoErr::BadParams);
+        ctx.accounts.game_state.house.limits_for(&ctx.accounts.house_vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, cfg.max_bet, |s| exposure::plinko(s, rows, difficulty))
+    }
+
+    pub fn slots_limits(ctx: Context<QueryLimits>) -> Result<BetLimits> {
+        let cfg = view_limits(&ctx.accounts.game_config, GAME_SLOTS)?;
+        ctx.accounts.game_state.house.limits_for(&ctx.accounts.house_vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, cfg.max_bet, |s| exposure::multiple(s, cfg.max_param))
+    }
+
+    /// Resolver posts `sha256(server_seed)` for the player's next bet; the next lock consumes it
+    /// and the matching resolve must reveal `server_seed`. Overwriting an unused commitment is allowed.
+    pub fn commit_seed(ctx: Context<CommitSeed>, commitment: [u8; 32]) -> Result<()> {
+        require!(ctx.accounts.resolver.key().to_bytes() == ctx.accounts.admin_config.roles.resolver_key(), CasinoErr::Unauthorized);
+        require!(commitment != fair::EMPTY_COMMITMENT, CasinoErr::BadParams);
+        let uv = &mut ctx.accounts.user_vault;
+        uv.seed_commit = commitment;
+        emit!(SeedCommitted { player: uv.owner, commitment });
+        Ok(())
+    }
+
+    // ---- session keys (owner-approved delegates with spending caps) ----
+    pub fn session_create(ctx: Context<SessionCreate>, args: SessionArgs) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(args.expires_at > now, CasinoErr::BadParams);
+        require!(args.max_bet > 0 && args.max_bet <= args.total_cap, CasinoErr::BadParams);
+        require!(args.games_mask != 0 && args.games_mask & !ALL_GAMES_MASK == 0, CasinoErr::BadParams);
+        require!(args.delegate != ctx.accounts.owner.key(), CasinoErr::BadParams);
+
+        let s = &mut ctx.accounts.session;
+        s.owner      = ctx.accounts.owner.key();
+        s.delegate   = args.delegate;
+        s.expires_at = args.expires_at;
+        s.max_bet    = args.max_bet;
+        s.remaining  = args.total_cap;
+        s.games_mask = args.games_mask;
+        s.bump       = ctx.bumps.session;
+
+        emit!(SessionCreated {
+            owner: s.owner,
+            delegate: s.delegate,
+            expires_at: s.expires_at,
+            max_bet: s.max_bet,
+            total_cap: args.total_cap,
+            games_mask: s.games_mask,
+        });
+        Ok(())
+    }
+
+    pub fn session_top_up(ctx: Context<SessionTopUp>, args: SessionTopUpArgs) -> Result<()> {
+        let s = &mut ctx.accounts.session;
+        if args.expires_at != 0 {
+            require!(args.expires_at > Clock::get()?.unix_timestamp, CasinoErr::BadParams);
+            s.expires_at = args.expires_at;
+        }
+        s.remaining = s.remaining.checked_add(args.amount).ok_or(CasinoErr::BadParams)?;
+
+        emit!(SessionToppedUp {
+            owner: s.owner,
+            delegate: s.delegate,
+            expires_at: s.expires_at,
+            remaining: s.remaining,
+        });
+        Ok(())
+    }
+
+    pub fn session_revoke(ctx: Context<SessionRevoke>) -> Result<()> {
+        let s = &ctx.accounts.session;
+        emit!(SessionRevoked { owner: s.owner, delegate: s.delegate, unspent: s.remaining });
+        Ok(())
+    }
+
+    // ---- house vault withdrawals (M-of-N multisig) ----
+    /// Treasurer fixes the owner set (up to `MAX_MULTISIG_OWNERS`, no duplicates) and threshold.
+    pub fn init_multisig(ctx: Context<InitMultisig>, owners: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        require!(
+            ctx.accounts.admin_config.roles.is(roles::TREASURER, &ctx.accounts.authority.key()),
+            CasinoErr::Unauthorized
+        );
+        require!(!owners.is_empty() && owners.len() <= MAX_MULTISIG_OWNERS, CasinoErr::BadParams);
+        require!(threshold >= 1 && threshold as usize <= owners.len(), CasinoErr::BadParams);
+        for (i, o) in owners.iter().enumerate() {
+            require!(*o != Pubkey::default() && !owners[..i].contains(o), CasinoErr::BadParams);
+        }
+
+        let ms = &mut ctx.accounts.multisig;
+        ms.owners[..owners.len()].copy_from_slice(&owners);
+        ms.owner_count = owners.len() as u8;
+        ms.threshold = threshold;
+        ms.next_proposal = 0;
+        ms.bump = ctx.bumps.multisig;
+
+        emit!(MultisigCreated { owners, threshold });
+        Ok(())
+    }
+
+    pub fn propose_house_withdraw(ctx: Context<ProposeHouseWithdraw>, args: HouseWithdrawArgs) -> Result<()> {
         require!(args.amount > 0, CasinoErr::BadParams);
+        require!(ctx.accounts.treasury.is_listed(&args.destination), CasinoErr::DestinationNotAllowed);
+        let now = Clock::get()?.unix_timestamp;
+        require!(args.expires_at > now && args.expires_at - now <= MAX_PROPOSAL_TTL_SECS, CasinoErr::BadParams);
+        let proposer = ctx.accounts.proposer.key();
+        let ms = &mut ctx.accounts.multisig;
+        let bit = ms.owner_bit(&proposer).ok_or(CasinoErr::Unauthorized)?;
+        let id = ms.next_proposal;
+        ms.next_proposal = id.checked_add(1).ok_or(CasinoErr::BadParams)?;
+
+        let p = &mut ctx.accounts.proposal;
+        p.id = id;
+        p.proposer = proposer;
+        p.destination = args.destination;
+        p.amount = args.amount;
+        p.expires_at = args.expires_at;
+        p.approvals = bit;
+        p.release_at = 0;
+        p.bump = ctx.bumps.proposal;
+
+        emit!(HouseWithdrawProposed {
+            id,
+            proposer,
+            destination: args.destination,
+            amount: args.amount,
+            expires_at: args.expires_at,
+        });
+        emit!(HouseWithdrawApproved { id, owner: proposer, approvals: 1 });
+        Ok(())
+    }
 
-        // Only allow our fixed admin key to withdraw from the house vault
-        let expected_admin = Pubkey::new_from_array(ADMIN_PUBKEY_BYTES);
-        require!(ctx.accounts.admin.key() == expected_admin, CasinoErr::BadParams);
+    pub fn approve_house_withdraw(ctx: Context<VoteHouseWithdraw>) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let bit = ctx.accounts.multisig.owner_bit(&owner).ok_or(CasinoErr::Unauthorized)?;
+        let p = &mut ctx.accounts.proposal;
+        require!(Clock::get()?.unix_timestamp < p.expires_at, CasinoErr::ProposalExpired);
+        require!(p.approvals & bit == 0, CasinoErr::BadParams);
+        p.approvals |= bit;
+        emit!(HouseWithdrawApproved { id: p.id, owner, approvals: p.approvals.count_ones() as u8 });
+        Ok(())
+    }
+
+    pub fn revoke_house_withdraw(ctx: Context<VoteHouseWithdraw>) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let bit = ctx.accounts.multisig.owner_bit(&owner).ok_or(CasinoErr::Unauthorized)?;
+        let p = &mut ctx.accounts.proposal;
+        require!(p.approvals & bit != 0, CasinoErr::BadParams);
+        p.approvals &= !bit;
+        emit!(HouseWithdrawRevoked { id: p.id, owner, approvals: p.approvals.count_ones() as u8 });
+        Ok(())
+    }
+
+    /// Executes a live proposal with at least `threshold` approvals and closes it. If it would
+    /// push the rolling 24h outflow over `daily_limit` it is queued for
+    /// `LARGE_WITHDRAW_DELAY_SECS` instead; call again after `release_at` (owners can still
+    /// revoke approvals in between to stop it).
+    pub fn house_withdraw(ctx: Context<HouseWithdraw>) -> Result<()> {
+        let ms = &ctx.accounts.multisig;
+        ms.owner_bit(&ctx.accounts.owner.key()).ok_or(CasinoErr::Unauthorized)?;
+        let now = Clock::get()?.unix_timestamp;
+        let p = &mut ctx.accounts.proposal;
+        require!(p.approvals.count_ones() >= ms.threshold as u32, CasinoErr::NotEnoughApprovals);
+        let treasury = &mut ctx.accounts.treasury;
+        require!(treasury.is_active(&p.destination, now), CasinoErr::DestinationNotAllowed);
+
+        if p.release_at == 0 {
+            require!(now < p.expires_at, CasinoErr::ProposalExpired);
+            if treasury.outflow.total(now).saturating_add(p.amount) > treasury.daily_limit {
+                p.release_at = now.saturating_add(LARGE_WITHDRAW_DELAY_SECS);
+                emit!(HouseWithdrawQueued { id: p.id, amount: p.amount, release_at: p.release_at });
+                return Ok(());
+            }
+        } else {
+            require!(now >= p.release_at, CasinoErr::WithdrawDelayed);
+        }
+        require_debit(&ctx.accounts.house_vault.to_account_info(), p.amount)?;
+        treasury.outflow.add(now, p.amount);
 
         // Use SystemProgram.transfer via CPI + PDA signature
         let bump_v = ctx.bumps.house_vault;
         let ix = system_instruction::transfer(
             &ctx.accounts.house_vault.key(),
             &ctx.accounts.destination.key(),
-            args.amount,
+            p.amount,
         );
 
         invoke_signed(
@@ -470,10 +1550,106 @@ pub mod casino {
         )?;
 
         emit!(HouseWithdrawn {
-            to: ctx.accounts.destination.key(),
-            amount: args.amount,
+            id: p.id,
+            to: p.destination,
+            amount: p.amount,
+            approvals: p.approvals,
         });
 
+        p.close(ctx.accounts.proposer.to_account_info())
+    }
+
+    /// Expired proposals that were never queued, and queued ones whose approvals were revoked
+    /// below the threshold, can be discarded.
+    pub fn discard_house_withdraw(ctx: Context<DiscardHouseWithdraw>) -> Result<()> {
+        let p = &ctx.accounts.proposal;
+        let discardable = if p.release_at == 0 {
+            Clock::get()?.unix_timestamp >= p.expires_at
+        } else {
+            p.approvals.count_ones() < ctx.accounts.multisig.threshold as u32
+        };
+        require!(discardable, CasinoErr::BadParams);
+        emit!(HouseWithdrawDiscarded { id: p.id });
+        Ok(())
+    }
+
+    // ---- treasury allow-list and rolling 24h limit ----
+    pub fn init_treasury(ctx: Context<InitTreasury>, daily_limit: u64) -> Result<()> {
+        require!(
+            ctx.accounts.admin_config.roles.is(roles::TREASURER, &ctx.accounts.authority.key()),
+            CasinoErr::Unauthorized
+        );
+        let t = &mut ctx.accounts.treasury;
+        t.daily_limit = daily_limit;
+        t.bump = ctx.bumps.treasury;
+        emit!(DailyLimitChanged { previous: 0, current: daily_limit });
+        Ok(())
+    }
+
+    /// New destinations become usable after `TREASURY_TIMELOCK_SECS`.
+    pub fn add_destination(ctx: Context<ManageTreasury>, key: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.admin_config.roles.is(roles::TREASURER, &ctx.accounts.authority.key()),
+            CasinoErr::Unauthorized
+        );
+        let t = &mut ctx.accounts.treasury;
+        require!(key != Pubkey::default() && !t.is_listed(&key), CasinoErr::BadParams);
+        let n = t.destination_count as usize;
+        require!(n < MAX_TREASURY_DESTINATIONS, CasinoErr::BadParams);
+        let active_at = Clock::get()?.unix_timestamp.saturating_add(TREASURY_TIMELOCK_SECS);
+        t.destinations[n] = Destination { key, active_at };
+        t.destination_count += 1;
+        emit!(DestinationAdded { key, active_at });
+        Ok(())
+    }
+
+    /// Removal is immediate (also blocks queued withdraws to that destination).
+    pub fn remove_destination(ctx: Context<ManageTreasury>, key: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.admin_config.roles.is(roles::TREASURER, &ctx.accounts.authority.key()),
+            CasinoErr::Unauthorized
+        );
+        let t = &mut ctx.accounts.treasury;
+        let n = t.destination_count as usize;
+        let i = t.destinations().iter().position(|d| d.key == key).ok_or(CasinoErr::BadParams)?;
+        t.destinations[i] = t.destinations[n - 1];
+        t.destinations[n - 1] = Destination::default();
+        t.destination_count -= 1;
+        emit!(DestinationRemoved { key });
+        Ok(())
+    }
+
+    /// Lowering the daily limit applies at once (and drops any pending increase); raising it
+    /// is queued behind `TREASURY_TIMELOCK_SECS`.
+    pub fn set_daily_limit(ctx: Context<ManageTreasury>, limit: u64) -> Result<()> {
+        require!(
+            ctx.accounts.admin_config.roles.is(roles::TREASURER, &ctx.accounts.authority.key()),
+            CasinoErr::Unauthorized
+        );
+        let t = &mut ctx.accounts.treasury;
+        if limit <= t.daily_limit {
+            let previous = t.daily_limit;
+            t.daily_limit = limit;
+            t.pending_limit = 0;
+            t.limit_eta = 0;
+            emit!(DailyLimitChanged { previous, current: limit });
+        } else {
+            let eta = Clock::get()?.unix_timestamp.saturating_add(TREASURY_TIMELOCK_SECS);
+            t.pending_limit = limit;
+            t.limit_eta = eta;
+            emit!(DailyLimitProposed { limit, eta });
+        }
+        Ok(())
+    }
+
+    pub fn apply_daily_limit(ctx: Context<ApplyDailyLimit>) -> Result<()> {
+        let t = &mut ctx.accounts.treasury;
+        require!(t.limit_eta != 0 && Clock::get()?.unix_timestamp >= t.limit_eta, CasinoErr::BadParams);
+        let previous = t.daily_limit;
+        t.daily_limit = t.pending_limit;
+        t.pending_limit = 0;
+        t.limit_eta = 0;
+        emit!(DailyLimitChanged { previous, current: t.daily_limit });
         Ok(())
     }
 
@@ -481,6 +1657,7 @@ pub mod casino {
     #[derive(Accounts)]
     #[instruction(args: DiceLockArgs)]
     pub struct DiceLock<'info> {
+        /// Must sign, unless a session key signs or a player-signed bet intent precedes this instruction
         pub player: SystemAccount<'info>,
         #[account(mut, signer)] pub fee_payer: SystemAccount<'info>,
         #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
@@ -493,45 +1670,95 @@ pub mod casino {
         /// CHECK
         #[account(address = SYSVAR_INSTRUCTIONS_ID)]
         pub sysvar_instructions: UncheckedAccount<'info>,
+        // optional: session key spending from user_vault (appended last)
+        #[account(mut)] pub session: Option<Account<'info, SessionKey>>,
+        pub session_signer: Option<Signer<'info>>,
+        /// CHECK: SlotHashes sysvar (entropy captured at lock)
+        #[account(address = slot_hashes::ID)]
+        pub slot_hashes: UncheckedAccount<'info>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        #[account(seeds=[b"game_config", GAME_DICE.to_le_bytes().as_ref()], bump=game_config.bump)]
+        pub game_config: Account<'info, GameConfig>,
+        /// CHECK: randomness oracle (oracle mode only); must be game_config.randomness.oracle_program
+        pub oracle_program: Option<UncheckedAccount<'info>>,
+        /// CHECK: oracle request PDA for this bet, created by the oracle CPI
+        #[account(mut)]
+        pub oracle_request: Option<UncheckedAccount<'info>>,
     }
     #[derive(Accounts)]
     pub struct DiceResolve<'info> {
         #[account(mut)] pub player: SystemAccount<'info>,
         #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
-        #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
         #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
         pub user_vault: Account<'info, UserVault>,
-        #[account(mut, close=user_vault, seeds=[b"bet", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+        #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"bet", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
         pub pending: Account<'info, PendingDice>,
         pub system_program: Program<'info, System>,
         /// CHECK
         #[account(address = SYSVAR_INSTRUCTIONS_ID)]
         pub sysvar_instructions: UncheckedAccount<'info>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        #[account(mut, seeds=[b"resolvers"], bump=resolver_set.bump)]
+        pub resolver_set: Account<'info, ResolverSet>,
+        /// Funded the pending account at lock; gets its rent back when it closes
+        #[account(mut)] pub rent_payer: SystemAccount<'info>,
+        /// CHECK: fulfilled oracle request (oracle-mode bets only); must match the pending record
+        pub oracle_request: Option<UncheckedAccount<'info>>,
     }
 
     pub fn dice_lock(ctx: Context<DiceLock>, args: DiceLockArgs) -> Result<()> {
-        require!(args.bet_amount >= MIN_BET_LAMPORTS && args.bet_amount <= MAX_BET_LAMPORTS, CasinoErr::BadParams);
-        require!(args.target >= 2 && args.target <= 98, CasinoErr::BadParams);
+        require!(!ctx.accounts.game_state.house.flags.lock_paused(GAME_DICE), CasinoErr::Paused);
+        let limits = lock_limits(&ctx.accounts.game_config)?;
+        require!(limits.bet_ok(args.bet_amount), CasinoErr::BadParams);
+        require!(limits.param_ok(args.target as u32), CasinoErr::BadParams);
         require!(args.bet_type <= 1, CasinoErr::BadParams);
-        require_ed25519_present(
+        require_lock_window(GAME_DICE, args.expiry_unix, args.expiry_slot)?;
+        require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
+        advance_nonce(&mut ctx.accounts.user_vault, args.nonce)?;
+        require_player_consent(
             &ctx.accounts.sysvar_instructions.to_account_info(),
-            args.ed25519_instr_index,
+            &ctx.accounts.player.to_account_info(),
+            ctx.accounts.session.as_mut(),
+            ctx.accounts.session_signer.as_ref(),
+            ctx.program_id,
+            GAME_DICE,
+            &dice_params(args.bet_amount, args.bet_type, args.target),
+            &args.client_seed,
+            args.bet_amount,
+            args.nonce,
+            args.expiry_unix,
         )?;
-        require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
 
-        let uv_bal = **ctx.accounts.user_vault.to_account_info().lamports.borrow();
-        let need = args.bet_amount.saturating_add(FEE_REIMBURSE_LAMPORTS);
-        require!(uv_bal >= need, CasinoErr::InsufficientVault);
+        let pending_key = ctx.accounts.pending.key();
+        let (server_commit, oracle_request) = randomness::lock(
+            &ctx.accounts.game_config.randomness,
+            ctx.accounts.oracle_program.as_deref(),
+            ctx.accounts.oracle_request.as_deref(),
+            Some(&mut ctx.accounts.user_vault.seed_commit),
+            &ctx.accounts.fee_payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &pending_key,
+        )?;
+        let slot_hash = fair::latest_slot_hash(&ctx.accounts.slot_hashes.to_account_info())
+            .ok_or(CasinoErr::BadParams)?;
+
+        let fee = lock_fee(&ctx.accounts.game_state, &ctx.accounts.user_vault, GAME_DICE);
+        let need = args.bet_amount.saturating_add(fee);
+        require_debit(&ctx.accounts.user_vault.to_account_info(), need)?;
 
         let uv_ai = ctx.accounts.user_vault.to_account_info();
         let hv_ai = ctx.accounts.house_vault.to_account_info();
         safe_move_lamports(&uv_ai, &hv_ai, args.bet_amount)?;
 
-        if FEE_REIMBURSE_LAMPORTS > 0 {
+        if fee > 0 {
             let fp_ai = ctx.accounts.fee_payer.to_account_info();
-            safe_move_lamports(&uv_ai, &fp_ai, FEE_REIMBURSE_LAMPORTS)?;
+            safe_move_lamports(&uv_ai, &fp_ai, fee)?;
         }
 
+        let reserved = ctx.accounts.game_state.house.reserve(&hv_ai, HOUSE_RESERVE_LAMPORTS, exposure::dice(args.bet_amount, args.bet_type, args.target))?;
+
         let p = &mut ctx.accounts.pending;
         p.player = ctx.accounts.player.key();
         p.amount = args.bet_amount;
@@ -539,6 +1766,14 @@ pub mod casino {
         p.target = args.target;
         p.nonce = args.nonce;
         p.expiry_unix = args.expiry_unix;
+        p.expiry_slot = args.expiry_slot;
+        p.server_commit = server_commit;
+        p.client_seed = args.client_seed;
+        p.slot_hash = slot_hash;
+        p.oracle_request = oracle_request;
+        p.exposure = reserved;
+        p.rent_payer = ctx.accounts.fee_payer.key();
+        p.house_edge_bps = limits.house_edge_bps;
         p.settled = false;
 
         emit!(DiceLocked {
@@ -547,33 +1782,59 @@ pub mod casino {
             bet_type: p.bet_type,
             target: p.target,
             nonce: p.nonce,
+            slot_hash: p.slot_hash,
         });
         Ok(())
     }
 
     pub fn dice_resolve(ctx: Context<DiceResolve>, args: DiceResolveArgs) -> Result<()> {
+        require!(!ctx.accounts.game_state.house.flags.resolve_paused(GAME_DICE), CasinoErr::Paused);
+        let pending_key = ctx.accounts.pending.key();
+        let house_key   = ctx.accounts.house_vault.key();
+        let player_key  = ctx.accounts.player.key();
+
         let p = &mut ctx.accounts.pending;
         require!(!p.settled, CasinoErr::BadPending);
 
         let clock = Clock::get()?;
-        require!(clock.unix_timestamp <= p.expiry_unix, CasinoErr::Expired);
-        require_ed25519_present(
+        require!(!expiry::is_expired(&clock, p.expiry_unix, p.expiry_slot), CasinoErr::Expired);
+
+        let seed = randomness::resolve_seed(
+            &p.oracle_request,
+            ctx.accounts.oracle_request.as_deref(),
+            &p.server_commit,
+            &args.server_seed,
+        )?;
+        let outcome = fair::outcome_hash(&seed, &p.client_seed, &p.slot_hash, p.nonce);
+        let roll = fair::dice_roll(&outcome);
+
+        let expected = message::resolve_message(
+            DICE_DOMAIN_TAG,
+            ctx.program_id,
+            &house_key,
+            &player_key,
+            &pending_key,
+            &[&dice_params(p.amount, p.bet_type, p.target), &[roll]],
+            args.payout,
+            p.nonce,
+            p.expiry_unix,
+        );
+        cosign::require_signers(
             &ctx.accounts.sysvar_instructions.to_account_info(),
-            args.ed25519_instr_index,
+            &mut ctx.accounts.resolver_set.cosigners,
+            &expected,
+            args.payout,
+            Clock::get()?.epoch,
         )?;
 
-        require!(args.roll >= 1 && args.roll <= 100, CasinoErr::BadParams);
         let win = match p.bet_type {
-            0 => args.roll < p.target,
-            _ => args.roll > p.target,
+            0 => roll < p.target,
+            _ => roll > p.target,
         };
-        if win {
-            require!(args.payout > 0 && args.payout <= MAX_PAYOUT_LAMPORTS, CasinoErr::BadPayout);
-        } else {
-            require!(args.payout == 0, CasinoErr::BadPayout);
-        }
+        let due = payout::dice(p.amount, p.bet_type, p.target, roll, p.house_edge_bps);
+        require!(args.payout == due && due <= p.exposure, CasinoErr::BadPayout);
 
-        if win && args.payout > 0 {
+        if args.payout > 0 {
             let bump_v = ctx.bumps.house_vault;
             let ix = system_instruction::transfer(
                 &ctx.accounts.house_vault.key(),
@@ -591,21 +1852,60 @@ pub mod casino {
             )?;
         }
 
+        record_outflow(&mut ctx.accounts.game_state, p.amount, args.payout)?;
+        ctx.accounts.game_state.house.release(p.exposure);
         p.settled = true;
         emit!(DiceResolved {
             player: p.player,
             win,
-            roll: args.roll,
+            roll,
             payout: args.payout,
             nonce: p.nonce,
         });
         Ok(())
     }
 
+    #[derive(Accounts)]
+    pub struct DiceRefund<'info> {
+        pub player: SystemAccount<'info>,
+        #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
+        #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
+        pub user_vault: Account<'info, UserVault>,
+        #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"bet", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+        pub pending: Account<'info, PendingDice>,
+        pub system_program: Program<'info, System>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        /// Funded the pending account at lock; gets its rent back when it closes
+        #[account(mut)] pub rent_payer: SystemAccount<'info>,
+    }
+
+    /// Permissionless: once past expiry + grace, return the stake to the player's user_vault.
+    pub fn dice_refund_expired(ctx: Context<DiceRefund>) -> Result<()> {
+        let p = &ctx.accounts.pending;
+        require_refundable(p.settled, p.expiry_unix, p.expiry_slot)?;
+        let amount = p.amount;
+
+        refund_principal(
+            &ctx.accounts.house_vault,
+            &ctx.accounts.user_vault,
+            &ctx.accounts.system_program,
+            ctx.bumps.house_vault,
+            amount,
+        )?;
+
+        let p = &mut ctx.accounts.pending;
+        ctx.accounts.game_state.house.release(p.exposure);
+        p.settled = true;
+        emit!(BetRefunded { player: p.player, game: GAME_DICE, amount, nonce: p.nonce });
+        Ok(())
+    }
+
     // ---- mines ----
     #[derive(Accounts)]
     #[instruction(args: MinesLockArgs)]
     pub struct MinesLock<'info> {
+        /// Must sign, unless a session key signs or a player-signed bet intent precedes this instruction
         pub player: SystemAccount<'info>,
         #[account(mut, signer)] pub fee_payer: SystemAccount<'info>,
         #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
@@ -618,48 +1918,97 @@ pub mod casino {
         /// CHECK
         #[account(address = SYSVAR_INSTRUCTIONS_ID)]
         pub sysvar_instructions: UncheckedAccount<'info>,
+        // optional: session key spending from user_vault (appended last)
+        #[account(mut)] pub session: Option<Account<'info, SessionKey>>,
+        pub session_signer: Option<Signer<'info>>,
+        /// CHECK: SlotHashes sysvar (entropy captured at lock)
+        #[account(address = slot_hashes::ID)]
+        pub slot_hashes: UncheckedAccount<'info>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        #[account(seeds=[b"game_config", GAME_MINES.to_le_bytes().as_ref()], bump=game_config.bump)]
+        pub game_config: Account<'info, GameConfig>,
+        /// CHECK: randomness oracle (oracle mode only); must be game_config.randomness.oracle_program
+        pub oracle_program: Option<UncheckedAccount<'info>>,
+        /// CHECK: oracle request PDA for this bet, created by the oracle CPI
+        #[account(mut)]
+        pub oracle_request: Option<UncheckedAccount<'info>>,
     }
     #[derive(Accounts)]
     pub struct MinesResolve<'info> {
         #[account(mut)] pub player: SystemAccount<'info>,
         #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
-        #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
         #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
         pub user_vault: Account<'info, UserVault>,
-        #[account(mut, close=user_vault, seeds=[b"round", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+        #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"round", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
         pub pending: Account<'info, PendingMines>,
         pub system_program: Program<'info, System>,
         /// CHECK
         #[account(address = SYSVAR_INSTRUCTIONS_ID)]
         pub sysvar_instructions: UncheckedAccount<'info>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        #[account(mut, seeds=[b"resolvers"], bump=resolver_set.bump)]
+        pub resolver_set: Account<'info, ResolverSet>,
+        /// Funded the pending account at lock; gets its rent back when it closes
+        #[account(mut)] pub rent_payer: SystemAccount<'info>,
+        /// CHECK: fulfilled oracle request (oracle-mode bets only); must match the pending record
+        pub oracle_request: Option<UncheckedAccount<'info>>,
     }
 
     pub fn mines_lock(ctx: Context<MinesLock>, args: MinesLockArgs) -> Result<()> {
-        require!(args.bet_amount >= MIN_BET_LAMPORTS && args.bet_amount <= MAX_BET_LAMPORTS, CasinoErr::BadParams);
-        require!(args.rows >= 2 && args.rows <= 8, CasinoErr::BadParams);
-        require!(args.cols >= 2 && args.cols <= 8, CasinoErr::BadParams);
+        require!(!ctx.accounts.game_state.house.flags.lock_paused(GAME_MINES), CasinoErr::Paused);
+        let limits = lock_limits(&ctx.accounts.game_config)?;
+        require!(limits.bet_ok(args.bet_amount), CasinoErr::BadParams);
+        require!(limits.param_ok(args.rows as u32) && limits.param_ok(args.cols as u32), CasinoErr::BadParams);
         let total = (args.rows as u16) * (args.cols as u16);
         require!(args.mines >= 1 && (args.mines as u16) < total, CasinoErr::BadParams);
 
-        require_ed25519_present(
+        require_lock_window(GAME_MINES, args.expiry_unix, args.expiry_slot)?;
+        require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
+        advance_nonce(&mut ctx.accounts.user_vault, args.nonce)?;
+        require_player_consent(
             &ctx.accounts.sysvar_instructions.to_account_info(),
-            args.ed25519_instr_index,
+            &ctx.accounts.player.to_account_info(),
+            ctx.accounts.session.as_mut(),
+            ctx.accounts.session_signer.as_ref(),
+            ctx.program_id,
+            GAME_MINES,
+            &mines_params(args.bet_amount, args.rows, args.cols, args.mines),
+            &args.client_seed,
+            args.bet_amount,
+            args.nonce,
+            args.expiry_unix,
         )?;
-        require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
 
-        let uv_bal = **ctx.accounts.user_vault.to_account_info().lamports.borrow();
-        let need = args.bet_amount.saturating_add(FEE_REIMBURSE_LAMPORTS);
-        require!(uv_bal >= need, CasinoErr::InsufficientVault);
+        let pending_key = ctx.accounts.pending.key();
+        let (server_commit, oracle_request) = randomness::lock(
+            &ctx.accounts.game_config.randomness,
+            ctx.accounts.oracle_program.as_deref(),
+            ctx.accounts.oracle_request.as_deref(),
+            Some(&mut ctx.accounts.user_vault.seed_commit),
+            &ctx.accounts.fee_payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &pending_key,
+        )?;
+        let slot_hash = fair::latest_slot_hash(&ctx.accounts.slot_hashes.to_account_info())
+            .ok_or(CasinoErr::BadParams)?;
+
+        let fee = lock_fee(&ctx.accounts.game_state, &ctx.accounts.user_vault, GAME_MINES);
+        let need = args.bet_amount.saturating_add(fee);
+        require_debit(&ctx.accounts.user_vault.to_account_info(), need)?;
 
         let uv_ai = ctx.accounts.user_vault.to_account_info();
         let hv_ai = ctx.accounts.house_vault.to_account_info();
         safe_move_lamports(&uv_ai, &hv_ai, args.bet_amount)?;
 
-        if FEE_REIMBURSE_LAMPORTS > 0 {
+        if fee > 0 {
             let fp_ai = ctx.accounts.fee_payer.to_account_info();
-            safe_move_lamports(&uv_ai, &fp_ai, FEE_REIMBURSE_LAMPORTS)?;
+            safe_move_lamports(&uv_ai, &fp_ai, fee)?;
         }
 
+        let reserved = ctx.accounts.game_state.house.reserve(&hv_ai, HOUSE_RESERVE_LAMPORTS, exposure::mines(args.bet_amount, args.rows * args.cols, args.mines))?;
+
         let p = &mut ctx.accounts.pending;
         p.player = ctx.accounts.player.key();
         p.amount = args.bet_amount;
@@ -668,6 +2017,14 @@ pub mod casino {
         p.mines = args.mines;
         p.nonce = args.nonce;
         p.expiry_unix = args.expiry_unix;
+        p.expiry_slot = args.expiry_slot;
+        p.server_commit = server_commit;
+        p.client_seed = args.client_seed;
+        p.slot_hash = slot_hash;
+        p.oracle_request = oracle_request;
+        p.exposure = reserved;
+        p.rent_payer = ctx.accounts.fee_payer.key();
+        p.house_edge_bps = limits.house_edge_bps;
         p.settled = false;
 
         emit!(MinesLocked {
@@ -677,24 +2034,54 @@ pub mod casino {
             cols: p.cols,
             mines: p.mines,
             nonce: p.nonce,
+            slot_hash: p.slot_hash,
         });
         Ok(())
     }
 
     pub fn mines_resolve(ctx: Context<MinesResolve>, args: MinesResolveArgs) -> Result<()> {
+        require!(!ctx.accounts.game_state.house.flags.resolve_paused(GAME_MINES), CasinoErr::Paused);
+        let pending_key = ctx.accounts.pending.key();
+        let house_key   = ctx.accounts.house_vault.key();
+        let player_key  = ctx.accounts.player.key();
+
         let p = &mut ctx.accounts.pending;
         require!(!p.settled, CasinoErr::BadPending);
 
         let clock = Clock::get()?;
-        require!(clock.unix_timestamp <= p.expiry_unix, CasinoErr::Expired);
-        require_ed25519_present(
+        require!(!expiry::is_expired(&clock, p.expiry_unix, p.expiry_slot), CasinoErr::Expired);
+
+        let seed = randomness::resolve_seed(
+            &p.oracle_request,
+            ctx.accounts.oracle_request.as_deref(),
+            &p.server_commit,
+            &args.server_seed,
+        )?;
+        let outcome = fair::outcome_hash(&seed, &p.client_seed, &p.slot_hash, p.nonce);
+        let layout = fair::mines_layout(&outcome, p.rows * p.cols, p.mines);
+
+        let expected = message::resolve_message(
+            MINES_DOMAIN_TAG,
+            ctx.program_id,
+            &house_key,
+            &player_key,
+            &pending_key,
+            &[&mines_params(p.amount, p.rows, p.cols, p.mines), &layout.to_le_bytes(), &args.picks.to_le_bytes()],
+            args.payout,
+            p.nonce,
+            p.expiry_unix,
+        );
+        cosign::require_signers(
             &ctx.accounts.sysvar_instructions.to_account_info(),
-            args.ed25519_instr_index,
+            &mut ctx.accounts.resolver_set.cosigners,
+            &expected,
+            args.payout,
+            Clock::get()?.epoch,
         )?;
 
-        let expected = ((p.nonce % 251) + 1) as u8;
-        require!(args.checksum == expected, CasinoErr::BadParams);
-        require!(args.payout <= MAX_PAYOUT_LAMPORTS, CasinoErr::BadPayout);
+        let due = payout::mines(p.amount, p.rows * p.cols, p.mines, layout, args.picks, p.house_edge_bps)
+            .ok_or(CasinoErr::BadParams)?;
+        require!(args.payout == due && due <= p.exposure, CasinoErr::BadPayout);
 
         if args.payout > 0 {
             let bump_v = ctx.bumps.house_vault;
@@ -714,20 +2101,59 @@ pub mod casino {
             )?;
         }
 
+        record_outflow(&mut ctx.accounts.game_state, p.amount, args.payout)?;
+        ctx.accounts.game_state.house.release(p.exposure);
         p.settled = true;
         emit!(MinesResolved {
             player: p.player,
             payout: args.payout,
-            checksum: args.checksum,
+            mines_layout: layout,
             nonce: p.nonce,
         });
         Ok(())
     }
 
+    #[derive(Accounts)]
+    pub struct MinesRefund<'info> {
+        pub player: SystemAccount<'info>,
+        #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
+        #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
+        pub user_vault: Account<'info, UserVault>,
+        #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"round", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+        pub pending: Account<'info, PendingMines>,
+        pub system_program: Program<'info, System>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        /// Funded the pending account at lock; gets its rent back when it closes
+        #[account(mut)] pub rent_payer: SystemAccount<'info>,
+    }
+
+    /// Permissionless: once past expiry + grace, return the stake to the player's user_vault.
+    pub fn mines_refund_expired(ctx: Context<MinesRefund>) -> Result<()> {
+        let p = &ctx.accounts.pending;
+        require_refundable(p.settled, p.expiry_unix, p.expiry_slot)?;
+        let amount = p.amount;
+
+        refund_principal(
+            &ctx.accounts.house_vault,
+            &ctx.accounts.user_vault,
+            &ctx.accounts.system_program,
+            ctx.bumps.house_vault,
+            amount,
+        )?;
+
+        let p = &mut ctx.accounts.pending;
+        ctx.accounts.game_state.house.release(p.exposure);
+        p.settled = true;
+        emit!(BetRefunded { player: p.player, game: GAME_MINES, amount, nonce: p.nonce });
+        Ok(())
+    }
+
     // ---- coinflip ----
     #[derive(Accounts)]
     #[instruction(args: FlipLockArgs)]
     pub struct FlipLock<'info> {
+        /// Must sign, unless a session key signs or a player-signed bet intent precedes this instruction
         pub player: SystemAccount<'info>,
         #[account(mut, signer)] pub fee_payer: SystemAccount<'info>,
         #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
@@ -740,80 +2166,163 @@ pub mod casino {
         /// CHECK
         #[account(address = SYSVAR_INSTRUCTIONS_ID)]
         pub sysvar_instructions: UncheckedAccount<'info>,
+        // optional: session key spending from user_vault (appended last)
+        #[account(mut)] pub session: Option<Account<'info, SessionKey>>,
+        pub session_signer: Option<Signer<'info>>,
+        /// CHECK: SlotHashes sysvar (entropy captured at lock)
+        #[account(address = slot_hashes::ID)]
+        pub slot_hashes: UncheckedAccount<'info>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        #[account(seeds=[b"game_config", GAME_FLIP.to_le_bytes().as_ref()], bump=game_config.bump)]
+        pub game_config: Account<'info, GameConfig>,
+        /// CHECK: randomness oracle (oracle mode only); must be game_config.randomness.oracle_program
+        pub oracle_program: Option<UncheckedAccount<'info>>,
+        /// CHECK: oracle request PDA for this bet, created by the oracle CPI
+        #[account(mut)]
+        pub oracle_request: Option<UncheckedAccount<'info>>,
     }
     #[derive(Accounts)]
     pub struct FlipResolve<'info> {
         #[account(mut)] pub player: SystemAccount<'info>,
         #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
-        #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
         #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
         pub user_vault: Account<'info, UserVault>,
-        #[account(mut, close=user_vault, seeds=[b"flip", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+        #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"flip", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
         pub pending: Account<'info, PendingFlip>,
         pub system_program: Program<'info, System>,
         /// CHECK
         #[account(address = SYSVAR_INSTRUCTIONS_ID)]
         pub sysvar_instructions: UncheckedAccount<'info>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        #[account(mut, seeds=[b"resolvers"], bump=resolver_set.bump)]
+        pub resolver_set: Account<'info, ResolverSet>,
+        /// Funded the pending account at lock; gets its rent back when it closes
+        #[account(mut)] pub rent_payer: SystemAccount<'info>,
+        /// CHECK: fulfilled oracle request (oracle-mode bets only); must match the pending record
+        pub oracle_request: Option<UncheckedAccount<'info>>,
     }
 
     pub fn flip_lock(ctx: Context<FlipLock>, args: FlipLockArgs) -> Result<()> {
-        require!(args.bet_amount >= MIN_BET_LAMPORTS && args.bet_amount <= MAX_BET_LAMPORTS, CasinoErr::BadParams);
+        require!(!ctx.accounts.game_state.house.flags.lock_paused(GAME_FLIP), CasinoErr::Paused);
+        let limits = lock_limits(&ctx.accounts.game_config)?;
+        require!(limits.bet_ok(args.bet_amount), CasinoErr::BadParams);
         require!(args.side <= 1, CasinoErr::BadParams);
-        require_ed25519_present(
+        require_lock_window(GAME_FLIP, args.expiry_unix, args.expiry_slot)?;
+        require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
+        advance_nonce(&mut ctx.accounts.user_vault, args.nonce)?;
+        require_player_consent(
             &ctx.accounts.sysvar_instructions.to_account_info(),
-            args.ed25519_instr_index,
+            &ctx.accounts.player.to_account_info(),
+            ctx.accounts.session.as_mut(),
+            ctx.accounts.session_signer.as_ref(),
+            ctx.program_id,
+            GAME_FLIP,
+            &flip_params(args.bet_amount, args.side),
+            &args.client_seed,
+            args.bet_amount,
+            args.nonce,
+            args.expiry_unix,
         )?;
-        require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
 
-        let uv_bal = **ctx.accounts.user_vault.to_account_info().lamports.borrow();
-        let need = args.bet_amount.saturating_add(FEE_REIMBURSE_LAMPORTS);
-        require!(uv_bal >= need, CasinoErr::InsufficientVault);
+        let pending_key = ctx.accounts.pending.key();
+        let (server_commit, oracle_request) = randomness::lock(
+            &ctx.accounts.game_config.randomness,
+            ctx.accounts.oracle_program.as_deref(),
+            ctx.accounts.oracle_request.as_deref(),
+            Some(&mut ctx.accounts.user_vault.seed_commit),
+            &ctx.accounts.fee_payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &pending_key,
+        )?;
+        let slot_hash = fair::latest_slot_hash(&ctx.accounts.slot_hashes.to_account_info())
+            .ok_or(CasinoErr::BadParams)?;
+
+        let fee = lock_fee(&ctx.accounts.game_state, &ctx.accounts.user_vault, GAME_FLIP);
+        let need = args.bet_amount.saturating_add(fee);
+        require_debit(&ctx.accounts.user_vault.to_account_info(), need)?;
 
         let uv_ai = ctx.accounts.user_vault.to_account_info();
         let hv_ai = ctx.accounts.house_vault.to_account_info();
         safe_move_lamports(&uv_ai, &hv_ai, args.bet_amount)?;
 
-        if FEE_REIMBURSE_LAMPORTS > 0 {
+        if fee > 0 {
             let fp_ai = ctx.accounts.fee_payer.to_account_info();
-            safe_move_lamports(&uv_ai, &fp_ai, FEE_REIMBURSE_LAMPORTS)?;
+            safe_move_lamports(&uv_ai, &fp_ai, fee)?;
         }
 
+        let reserved = ctx.accounts.game_state.house.reserve(&hv_ai, HOUSE_RESERVE_LAMPORTS, exposure::flip(args.bet_amount))?;
+
         let p = &mut ctx.accounts.pending;
         p.player = ctx.accounts.player.key();
         p.amount = args.bet_amount;
         p.side = args.side;
         p.nonce = args.nonce;
         p.expiry_unix = args.expiry_unix;
+        p.expiry_slot = args.expiry_slot;
+        p.server_commit = server_commit;
+        p.client_seed = args.client_seed;
+        p.slot_hash = slot_hash;
+        p.oracle_request = oracle_request;
+        p.exposure = reserved;
+        p.rent_payer = ctx.accounts.fee_payer.key();
+        p.house_edge_bps = limits.house_edge_bps;
         p.settled = false;
         emit!(FlipLocked {
             player: p.player,
             amount: p.amount,
             side: p.side,
             nonce: p.nonce,
+            slot_hash: p.slot_hash,
         });
         Ok(())
     }
 
     pub fn flip_resolve(ctx: Context<FlipResolve>, args: FlipResolveArgs) -> Result<()> {
+        require!(!ctx.accounts.game_state.house.flags.resolve_paused(GAME_FLIP), CasinoErr::Paused);
+        let pending_key = ctx.accounts.pending.key();
+        let house_key   = ctx.accounts.house_vault.key();
+        let player_key  = ctx.accounts.player.key();
+
         let p = &mut ctx.accounts.pending;
         require!(!p.settled, CasinoErr::BadPending);
 
         let clock = Clock::get()?;
-        require!(clock.unix_timestamp <= p.expiry_unix, CasinoErr::Expired);
-        require_ed25519_present(
+        require!(!expiry::is_expired(&clock, p.expiry_unix, p.expiry_slot), CasinoErr::Expired);
+
+        let seed = randomness::resolve_seed(
+            &p.oracle_request,
+            ctx.accounts.oracle_request.as_deref(),
+            &p.server_commit,
+            &args.server_seed,
+        )?;
+        let outcome = fair::outcome_hash(&seed, &p.client_seed, &p.slot_hash, p.nonce);
+        let winner_side = fair::coin_side(&outcome);
+
+        let expected = message::resolve_message(
+            FLIP_DOMAIN_TAG,
+            ctx.program_id,
+            &house_key,
+            &player_key,
+            &pending_key,
+            &[&flip_params(p.amount, p.side), &[winner_side]],
+            args.payout,
+            p.nonce,
+            p.expiry_unix,
+        );
+        cosign::require_signers(
             &ctx.accounts.sysvar_instructions.to_account_info(),
-            args.ed25519_instr_index,
+            &mut ctx.accounts.resolver_set.cosigners,
+            &expected,
+            args.payout,
+            Clock::get()?.epoch,
         )?;
 
-        require!(args.winner_side <= 1, CasinoErr::BadParams);
-        let win = args.winner_side == p.side;
-        if win {
-            require!(args.payout > 0 && args.payout <= MAX_PAYOUT_LAMPORTS, CasinoErr::BadPayout);
-        } else {
-            require!(args.payout == 0, CasinoErr::BadPayout);
-        }
+        let due = payout::flip(p.amount, p.side, winner_side, p.house_edge_bps);
+        require!(args.payout == due && due <= p.exposure, CasinoErr::BadPayout);
 
-        if win && args.payout > 0 {
+        if args.payout > 0 {
             let bump_v = ctx.bumps.house_vault;
             let ix = system_instruction::transfer(
                 &ctx.accounts.house_vault.key(),
@@ -830,20 +2339,59 @@ pub mod casino {
                 &[&[b"vault", &[bump_v]]],
             )?;
         }
+        record_outflow(&mut ctx.accounts.game_state, p.amount, args.payout)?;
+        ctx.accounts.game_state.house.release(p.exposure);
         p.settled = true;
         emit!(FlipResolved {
             player: p.player,
-            winner_side: args.winner_side,
+            winner_side,
             payout: args.payout,
             nonce: p.nonce,
         });
         Ok(())
     }
 
+    #[derive(Accounts)]
+    pub struct FlipRefund<'info> {
+        pub player: SystemAccount<'info>,
+        #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
+        #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
+        pub user_vault: Account<'info, UserVault>,
+        #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"flip", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+        pub pending: Account<'info, PendingFlip>,
+        pub system_program: Program<'info, System>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        /// Funded the pending account at lock; gets its rent back when it closes
+        #[account(mut)] pub rent_payer: SystemAccount<'info>,
+    }
+
+    /// Permissionless: once past expiry + grace, return the stake to the player's user_vault.
+    pub fn flip_refund_expired(ctx: Context<FlipRefund>) -> Result<()> {
+        let p = &ctx.accounts.pending;
+        require_refundable(p.settled, p.expiry_unix, p.expiry_slot)?;
+        let amount = p.amount;
+
+        refund_principal(
+            &ctx.accounts.house_vault,
+            &ctx.accounts.user_vault,
+            &ctx.accounts.system_program,
+            ctx.bumps.house_vault,
+            amount,
+        )?;
+
+        let p = &mut ctx.accounts.pending;
+        ctx.accounts.game_state.house.release(p.exposure);
+        p.settled = true;
+        emit!(BetRefunded { player: p.player, game: GAME_FLIP, amount, nonce: p.nonce });
+        Ok(())
+    }
+
     // ---- crash ----
     #[derive(Accounts)]
     #[instruction(args: CrashLockArgs)]
     pub struct CrashLock<'info> {
+        /// Must sign, unless a session key signs or a player-signed bet intent precedes this instruction
         pub player: SystemAccount<'info>,
         #[account(mut, signer)] pub fee_payer: SystemAccount<'info>,
         #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
@@ -856,70 +2404,161 @@ pub mod casino {
         /// CHECK
         #[account(address = SYSVAR_INSTRUCTIONS_ID)]
         pub sysvar_instructions: UncheckedAccount<'info>,
+        // optional: session key spending from user_vault (appended last)
+        #[account(mut)] pub session: Option<Account<'info, SessionKey>>,
+        pub session_signer: Option<Signer<'info>>,
+        /// CHECK: SlotHashes sysvar (entropy captured at lock)
+        #[account(address = slot_hashes::ID)]
+        pub slot_hashes: UncheckedAccount<'info>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        #[account(seeds=[b"game_config", GAME_CRASH.to_le_bytes().as_ref()], bump=game_config.bump)]
+        pub game_config: Account<'info, GameConfig>,
+        /// CHECK: randomness oracle (oracle mode only); must be game_config.randomness.oracle_program
+        pub oracle_program: Option<UncheckedAccount<'info>>,
+        /// CHECK: oracle request PDA for this bet, created by the oracle CPI
+        #[account(mut)]
+        pub oracle_request: Option<UncheckedAccount<'info>>,
     }
     #[derive(Accounts)]
     pub struct CrashResolve<'info> {
         #[account(mut)] pub player: SystemAccount<'info>,
         #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
-        #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
         #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
         pub user_vault: Account<'info, UserVault>,
-        #[account(mut, close=user_vault, seeds=[b"crash", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+        #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"crash", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
         pub pending: Account<'info, PendingCrash>,
         pub system_program: Program<'info, System>,
         /// CHECK
         #[account(address = SYSVAR_INSTRUCTIONS_ID)]
         pub sysvar_instructions: UncheckedAccount<'info>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        #[account(mut, seeds=[b"resolvers"], bump=resolver_set.bump)]
+        pub resolver_set: Account<'info, ResolverSet>,
+        /// Funded the pending account at lock; gets its rent back when it closes
+        #[account(mut)] pub rent_payer: SystemAccount<'info>,
+        /// CHECK: fulfilled oracle request (oracle-mode bets only); must match the pending record
+        pub oracle_request: Option<UncheckedAccount<'info>>,
     }
 
     pub fn crash_lock(ctx: Context<CrashLock>, args: CrashLockArgs) -> Result<()> {
-        require!(args.bet_amount >= MIN_BET_LAMPORTS && args.bet_amount <= MAX_BET_LAMPORTS, CasinoErr::BadParams);
-        require_ed25519_present(
+        require!(!ctx.accounts.game_state.house.flags.lock_paused(GAME_CRASH), CasinoErr::Paused);
+        let limits = lock_limits(&ctx.accounts.game_config)?;
+        require!(limits.bet_ok(args.bet_amount), CasinoErr::BadParams);
+        require_lock_window(GAME_CRASH, args.expiry_unix, args.expiry_slot)?;
+        require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
+        advance_nonce(&mut ctx.accounts.user_vault, args.nonce)?;
+        require_player_consent(
             &ctx.accounts.sysvar_instructions.to_account_info(),
-            args.ed25519_instr_index,
+            &ctx.accounts.player.to_account_info(),
+            ctx.accounts.session.as_mut(),
+            ctx.accounts.session_signer.as_ref(),
+            ctx.program_id,
+            GAME_CRASH,
+            &crash_params(args.bet_amount),
+            &args.client_seed,
+            args.bet_amount,
+            args.nonce,
+            args.expiry_unix,
         )?;
-        require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
 
-        let uv_bal = **ctx.accounts.user_vault.to_account_info().lamports.borrow();
-        let need = args.bet_amount.saturating_add(FEE_REIMBURSE_LAMPORTS);
-        require!(uv_bal >= need, CasinoErr::InsufficientVault);
+        let pending_key = ctx.accounts.pending.key();
+        let (server_commit, oracle_request) = randomness::lock(
+            &ctx.accounts.game_config.randomness,
+            ctx.accounts.oracle_program.as_deref(),
+            ctx.accounts.oracle_request.as_deref(),
+            Some(&mut ctx.accounts.user_vault.seed_commit),
+            &ctx.accounts.fee_payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &pending_key,
+        )?;
+        let slot_hash = fair::latest_slot_hash(&ctx.accounts.slot_hashes.to_account_info())
+            .ok_or(CasinoErr::BadParams)?;
+
+        let fee = lock_fee(&ctx.accounts.game_state, &ctx.accounts.user_vault, GAME_CRASH);
+        let need = args.bet_amount.saturating_add(fee);
+        require_debit(&ctx.accounts.user_vault.to_account_info(), need)?;
 
         let uv_ai = ctx.accounts.user_vault.to_account_info();
         let hv_ai = ctx.accounts.house_vault.to_account_info();
         safe_move_lamports(&uv_ai, &hv_ai, args.bet_amount)?;
 
-        if FEE_REIMBURSE_LAMPORTS > 0 {
+        if fee > 0 {
             let fp_ai = ctx.accounts.fee_payer.to_account_info();
-            safe_move_lamports(&uv_ai, &fp_ai, FEE_REIMBURSE_LAMPORTS)?;
+            safe_move_lamports(&uv_ai, &fp_ai, fee)?;
         }
 
+        let reserved = ctx.accounts.game_state.house.reserve(&hv_ai, HOUSE_RESERVE_LAMPORTS, exposure::multiple(args.bet_amount, limits.max_param))?;
+
         let p = &mut ctx.accounts.pending;
         p.player = ctx.accounts.player.key();
         p.amount = args.bet_amount;
         p.nonce = args.nonce;
         p.expiry_unix = args.expiry_unix;
+        p.expiry_slot = args.expiry_slot;
+        p.server_commit = server_commit;
+        p.client_seed = args.client_seed;
+        p.slot_hash = slot_hash;
+        p.oracle_request = oracle_request;
+        p.exposure = reserved;
+        p.house_edge_bps = limits.house_edge_bps;
+        p.min_multiplier_bps = limits.min_param;
+        p.max_multiplier_bps = limits.max_param;
+        p.rent_payer = ctx.accounts.fee_payer.key();
         p.settled = false;
         emit!(CrashLocked {
             player: p.player,
             amount: p.amount,
             nonce: p.nonce,
+            slot_hash: p.slot_hash,
         });
         Ok(())
     }
 
     pub fn crash_resolve(ctx: Context<CrashResolve>, args: CrashResolveArgs) -> Result<()> {
+        require!(!ctx.accounts.game_state.house.flags.resolve_paused(GAME_CRASH), CasinoErr::Paused);
+        let pending_key = ctx.accounts.pending.key();
+        let house_key   = ctx.accounts.house_vault.key();
+        let player_key  = ctx.accounts.player.key();
+
         let p = &mut ctx.accounts.pending;
         require!(!p.settled, CasinoErr::BadPending);
 
         let clock = Clock::get()?;
-        require!(clock.unix_timestamp <= p.expiry_unix, CasinoErr::Expired);
-        require_ed25519_present(
+        require!(!expiry::is_expired(&clock, p.expiry_unix, p.expiry_slot), CasinoErr::Expired);
+
+        let seed = randomness::resolve_seed(
+            &p.oracle_request,
+            ctx.accounts.oracle_request.as_deref(),
+            &p.server_commit,
+            &args.server_seed,
+        )?;
+        let outcome = fair::outcome_hash(&seed, &p.client_seed, &p.slot_hash, p.nonce);
+        let crash_bps = fair::crash_point_bps(&outcome, p.house_edge_bps);
+
+        let expected = message::resolve_message(
+            CRASH_DOMAIN_TAG,
+            ctx.program_id,
+            &house_key,
+            &player_key,
+            &pending_key,
+            &[&crash_params(p.amount), &args.multiplier_bps.to_le_bytes(), &crash_bps.to_le_bytes()],
+            args.payout,
+            p.nonce,
+            p.expiry_unix,
+        );
+        cosign::require_signers(
             &ctx.accounts.sysvar_instructions.to_account_info(),
-            args.ed25519_instr_index,
+            &mut ctx.accounts.resolver_set.cosigners,
+            &expected,
+            args.payout,
+            Clock::get()?.epoch,
         )?;
 
-        require!(args.multiplier_bps >= 10000, CasinoErr::BadParams); // >=1x
-        require!(args.payout <= MAX_PAYOUT_LAMPORTS, CasinoErr::BadPayout);
+        require!((p.min_multiplier_bps..=p.max_multiplier_bps).contains(&args.multiplier_bps), CasinoErr::BadParams);
+        let due = payout::crash(p.amount, args.multiplier_bps, crash_bps); // 0 if busted before cash-out
+        require!(args.payout == due && due <= p.exposure, CasinoErr::BadPayout);
         if args.payout > 0 {
             let bump_v = ctx.bumps.house_vault;
             let ix = system_instruction::transfer(
@@ -937,20 +2576,60 @@ pub mod casino {
                 &[&[b"vault", &[bump_v]]],
             )?;
         }
+        record_outflow(&mut ctx.accounts.game_state, p.amount, args.payout)?;
+        ctx.accounts.game_state.house.release(p.exposure);
         p.settled = true;
         emit!(CrashResolved {
             player: p.player,
             multiplier_bps: args.multiplier_bps,
+            crash_point_bps: crash_bps,
             payout: args.payout,
             nonce: p.nonce,
         });
         Ok(())
     }
 
+    #[derive(Accounts)]
+    pub struct CrashRefund<'info> {
+        pub player: SystemAccount<'info>,
+        #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
+        #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
+        pub user_vault: Account<'info, UserVault>,
+        #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"crash", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+        pub pending: Account<'info, PendingCrash>,
+        pub system_program: Program<'info, System>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        /// Funded the pending account at lock; gets its rent back when it closes
+        #[account(mut)] pub rent_payer: SystemAccount<'info>,
+    }
+
+    /// Permissionless: once past expiry + grace, return the stake to the player's user_vault.
+    pub fn crash_refund_expired(ctx: Context<CrashRefund>) -> Result<()> {
+        let p = &ctx.accounts.pending;
+        require_refundable(p.settled, p.expiry_unix, p.expiry_slot)?;
+        let amount = p.amount;
+
+        refund_principal(
+            &ctx.accounts.house_vault,
+            &ctx.accounts.user_vault,
+            &ctx.accounts.system_program,
+            ctx.bumps.house_vault,
+            amount,
+        )?;
+
+        let p = &mut ctx.accounts.pending;
+        ctx.accounts.game_state.house.release(p.exposure);
+        p.settled = true;
+        emit!(BetRefunded { player: p.player, game: GAME_CRASH, amount, nonce: p.nonce });
+        Ok(())
+    }
+
     // ---- plinko ----
     #[derive(Accounts)]
     #[instruction(args: PlinkoLockArgs)]
     pub struct PlinkoLock<'info> {
+        /// Must sign, unless a session key signs or a player-signed bet intent precedes this instruction
         pub player: SystemAccount<'info>,
         #[account(mut, signer)] pub fee_payer: SystemAccount<'info>,
         #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
@@ -962,50 +2641,100 @@ pub mod casino {
         /// CHECK
         #[account(address = SYSVAR_INSTRUCTIONS_ID)]
         pub sysvar_instructions: UncheckedAccount<'info>,
+        // optional: session key spending from user_vault (appended last)
+        #[account(mut)] pub session: Option<Account<'info, SessionKey>>,
+        pub session_signer: Option<Signer<'info>>,
+        /// CHECK: SlotHashes sysvar (entropy captured at lock)
+        #[account(address = slot_hashes::ID)]
+        pub slot_hashes: UncheckedAccount<'info>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        #[account(seeds=[b"game_config", GAME_PLINKO.to_le_bytes().as_ref()], bump=game_config.bump)]
+        pub game_config: Account<'info, GameConfig>,
+        /// CHECK: randomness oracle (oracle mode only); must be game_config.randomness.oracle_program
+        pub oracle_program: Option<UncheckedAccount<'info>>,
+        /// CHECK: oracle request PDA for this bet, created by the oracle CPI
+        #[account(mut)]
+        pub oracle_request: Option<UncheckedAccount<'info>>,
     }
     #[derive(Accounts)]
     pub struct PlinkoResolve<'info> {
         #[account(mut)] pub player: SystemAccount<'info>,
         #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
-        #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
         #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
         pub user_vault: Account<'info, UserVault>,
-        #[account(mut, close=user_vault, seeds=[b"plinkobet", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+        #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"plinkobet", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
         pub pending: Account<'info, PendingPlinko>,
         pub system_program: Program<'info, System>,
         /// CHECK
         #[account(address = SYSVAR_INSTRUCTIONS_ID)]
         pub sysvar_instructions: UncheckedAccount<'info>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        #[account(mut, seeds=[b"resolvers"], bump=resolver_set.bump)]
+        pub resolver_set: Account<'info, ResolverSet>,
+        /// Funded the pending account at lock; gets its rent back when it closes
+        #[account(mut)] pub rent_payer: SystemAccount<'info>,
+        /// CHECK: fulfilled oracle request (oracle-mode bets only); must match the pending record
+        pub oracle_request: Option<UncheckedAccount<'info>>,
     }
 
     pub fn plinko_lock(ctx: Context<PlinkoLock>, args: PlinkoLockArgs) -> Result<()> {
-        require!(args.unit_amount >= MIN_BET_LAMPORTS && args.unit_amount <= MAX_BET_LAMPORTS, CasinoErr::BadParams);
-        require!(args.balls >= 1, CasinoErr::BadParams);
-        require!(args.rows >= 8 && args.rows <= 16, CasinoErr::BadParams);
+        require!(!ctx.accounts.game_state.house.flags.lock_paused(GAME_PLINKO), CasinoErr::Paused);
+        let limits = lock_limits(&ctx.accounts.game_config)?;
+        require!(limits.bet_ok(args.unit_amount), CasinoErr::BadParams);
+        require!(args.balls >= 1 && args.balls <= limits.max_count, CasinoErr::BadParams);
+        require!(limits.param_ok(args.rows as u32), CasinoErr::BadParams);
         require!(args.difficulty <= 5, CasinoErr::BadParams); // 0..5 (easy→extreme)
 
-        require_ed25519_present(
+        let total = (args.unit_amount as u128) * (args.balls as u128);
+        require!(total <= (limits.max_bet as u128), CasinoErr::BadParams);
+
+        require_lock_window(GAME_PLINKO, args.expiry_unix, args.expiry_slot)?;
+        require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
+        advance_nonce(&mut ctx.accounts.user_vault, args.nonce)?;
+        require_player_consent(
             &ctx.accounts.sysvar_instructions.to_account_info(),
-            args.ed25519_instr_index,
+            &ctx.accounts.player.to_account_info(),
+            ctx.accounts.session.as_mut(),
+            ctx.accounts.session_signer.as_ref(),
+            ctx.program_id,
+            GAME_PLINKO,
+            &plinko_params(args.unit_amount, args.balls, args.rows, args.difficulty),
+            &args.client_seed,
+            total as u64,
+            args.nonce,
+            args.expiry_unix,
         )?;
-        require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
 
-        let total = (args.unit_amount as u128) * (args.balls as u128);
-        require!(total <= (MAX_BET_LAMPORTS as u128), CasinoErr::BadParams);
+        let pending_key = ctx.accounts.pending.key();
+        let (server_commit, oracle_request) = randomness::lock(
+            &ctx.accounts.game_config.randomness,
+            ctx.accounts.oracle_program.as_deref(),
+            ctx.accounts.oracle_request.as_deref(),
+            Some(&mut ctx.accounts.user_vault.seed_commit),
+            &ctx.accounts.fee_payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &pending_key,
+        )?;
+        let slot_hash = fair::latest_slot_hash(&ctx.accounts.slot_hashes.to_account_info())
+            .ok_or(CasinoErr::BadParams)?;
 
-        let uv_bal = **ctx.accounts.user_vault.to_account_info().lamports.borrow();
-        let need = (total as u64).saturating_add(FEE_REIMBURSE_LAMPORTS);
-        require!(uv_bal >= need, CasinoErr::InsufficientVault);
+        let fee = lock_fee(&ctx.accounts.game_state, &ctx.accounts.user_vault, GAME_PLINKO);
+        let need = (total as u64).saturating_add(fee);
+        require_debit(&ctx.accounts.user_vault.to_account_info(), need)?;
 
         let uv_ai = ctx.accounts.user_vault.to_account_info();
         let hv_ai = ctx.accounts.house_vault.to_account_info();
         safe_move_lamports(&uv_ai, &hv_ai, total as u64)?;
 
-        if FEE_REIMBURSE_LAMPORTS > 0 {
+        if fee > 0 {
             let fp_ai = ctx.accounts.fee_payer.to_account_info();
-            safe_move_lamports(&uv_ai, &fp_ai, FEE_REIMBURSE_LAMPORTS)?;
+            safe_move_lamports(&uv_ai, &fp_ai, fee)?;
         }
 
+        let reserved = ctx.accounts.game_state.house.reserve(&hv_ai, HOUSE_RESERVE_LAMPORTS, exposure::plinko(total as u64, args.rows, args.difficulty))?;
+
         let p = &mut ctx.accounts.pending;
         p.player = ctx.accounts.player.key();
         p.unit_amount = args.unit_amount;
@@ -1014,6 +2743,13 @@ pub mod casino {
         p.difficulty = args.difficulty;
         p.nonce = args.nonce;
         p.expiry_unix = args.expiry_unix;
+        p.expiry_slot = args.expiry_slot;
+        p.server_commit = server_commit;
+        p.client_seed = args.client_seed;
+        p.slot_hash = slot_hash;
+        p.oracle_request = oracle_request;
+        p.exposure = reserved;
+        p.rent_payer = ctx.accounts.fee_payer.key();
         p.settled = false;
         emit!(PlinkoLocked {
             player: p.player,
@@ -1022,22 +2758,52 @@ pub mod casino {
             rows: p.rows,
             difficulty: p.difficulty,
             nonce: p.nonce,
+            slot_hash: p.slot_hash,
         });
         Ok(())
     }
 
     pub fn plinko_resolve(ctx: Context<PlinkoResolve>, args: PlinkoResolveArgs) -> Result<()> {
+        require!(!ctx.accounts.game_state.house.flags.resolve_paused(GAME_PLINKO), CasinoErr::Paused);
+        let pending_key = ctx.accounts.pending.key();
+        let house_key   = ctx.accounts.house_vault.key();
+        let player_key  = ctx.accounts.player.key();
+
         let p = &mut ctx.accounts.pending;
         require!(!p.settled, CasinoErr::BadPending);
 
         let clock = Clock::get()?;
-        require!(clock.unix_timestamp <= p.expiry_unix, CasinoErr::Expired);
-        require_ed25519_present(
+        require!(!expiry::is_expired(&clock, p.expiry_unix, p.expiry_slot), CasinoErr::Expired);
+
+        let seed = randomness::resolve_seed(
+            &p.oracle_request,
+            ctx.accounts.oracle_request.as_deref(),
+            &p.server_commit,
+            &args.server_seed,
+        )?;
+        let outcome = fair::outcome_hash(&seed, &p.client_seed, &p.slot_hash, p.nonce);
+
+        let expected = message::resolve_message(
+            PLINKO_DOMAIN_TAG,
+            ctx.program_id,
+            &house_key,
+            &player_key,
+            &pending_key,
+            &[&plinko_params(p.unit_amount, p.balls, p.rows, p.difficulty), &outcome],
+            args.total_payout,
+            p.nonce,
+            p.expiry_unix,
+        );
+        cosign::require_signers(
             &ctx.accounts.sysvar_instructions.to_account_info(),
-            args.ed25519_instr_index,
+            &mut ctx.accounts.resolver_set.cosigners,
+            &expected,
+            args.total_payout,
+            Clock::get()?.epoch,
         )?;
 
-        require!(args.total_payout <= MAX_PAYOUT_LAMPORTS, CasinoErr::BadPayout);
+        let due = payout::plinko(p.unit_amount, p.balls, p.rows, p.difficulty, &outcome).ok_or(CasinoErr::BadParams)?;
+        require!(args.total_payout == due && due <= p.exposure, CasinoErr::BadPayout);
         if args.total_payout > 0 {
             let bump_v = ctx.bumps.house_vault;
             let ix = system_instruction::transfer(
@@ -1055,20 +2821,59 @@ pub mod casino {
                 &[&[b"vault", &[bump_v]]],
             )?;
         }
+        record_outflow(&mut ctx.accounts.game_state, p.unit_amount.saturating_mul(p.balls as u64), args.total_payout)?;
+        ctx.accounts.game_state.house.release(p.exposure);
         p.settled = true;
         emit!(PlinkoResolved {
             player: p.player,
             total_payout: args.total_payout,
-            checksum: args.checksum,
+            outcome,
             nonce: p.nonce,
         });
         Ok(())
     }
 
+    #[derive(Accounts)]
+    pub struct PlinkoRefund<'info> {
+        pub player: SystemAccount<'info>,
+        #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
+        #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
+        pub user_vault: Account<'info, UserVault>,
+        #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"plinkobet", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+        pub pending: Account<'info, PendingPlinko>,
+        pub system_program: Program<'info, System>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        /// Funded the pending account at lock; gets its rent back when it closes
+        #[account(mut)] pub rent_payer: SystemAccount<'info>,
+    }
+
+    /// Permissionless: once past expiry + grace, return the stake to the player's user_vault.
+    pub fn plinko_refund_expired(ctx: Context<PlinkoRefund>) -> Result<()> {
+        let p = &ctx.accounts.pending;
+        require_refundable(p.settled, p.expiry_unix, p.expiry_slot)?;
+        let amount = (p.unit_amount as u128 * p.balls as u128) as u64; // bounded by the config's max_bet at lock
+
+        refund_principal(
+            &ctx.accounts.house_vault,
+            &ctx.accounts.user_vault,
+            &ctx.accounts.system_program,
+            ctx.bumps.house_vault,
+            amount,
+        )?;
+
+        let p = &mut ctx.accounts.pending;
+        ctx.accounts.game_state.house.release(p.exposure);
+        p.settled = true;
+        emit!(BetRefunded { player: p.player, game: GAME_PLINKO, amount, nonce: p.nonce });
+        Ok(())
+    }
+
     // ---- slots ----
     #[derive(Accounts)]
     #[instruction(args: SlotsLockArgs)]
     pub struct SlotsLock<'info> {
+        /// Must sign, unless a session key signs or a player-signed bet intent precedes this instruction
         pub player: SystemAccount<'info>,
         #[account(mut, signer)] pub fee_payer: SystemAccount<'info>,
         #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
@@ -1081,69 +2886,157 @@ pub mod casino {
         /// CHECK
         #[account(address = SYSVAR_INSTRUCTIONS_ID)]
         pub sysvar_instructions: UncheckedAccount<'info>,
+        // optional: session key spending from user_vault (appended last)
+        #[account(mut)] pub session: Option<Account<'info, SessionKey>>,
+        pub session_signer: Option<Signer<'info>>,
+        /// CHECK: SlotHashes sysvar (entropy captured at lock)
+        #[account(address = slot_hashes::ID)]
+        pub slot_hashes: UncheckedAccount<'info>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        #[account(seeds=[b"game_config", GAME_SLOTS.to_le_bytes().as_ref()], bump=game_config.bump)]
+        pub game_config: Account<'info, GameConfig>,
+        /// CHECK: randomness oracle (oracle mode only); must be game_config.randomness.oracle_program
+        pub oracle_program: Option<UncheckedAccount<'info>>,
+        /// CHECK: oracle request PDA for this bet, created by the oracle CPI
+        #[account(mut)]
+        pub oracle_request: Option<UncheckedAccount<'info>>,
     }
     #[derive(Accounts)]
     pub struct SlotsResolve<'info> {
         #[account(mut)] pub player: SystemAccount<'info>,
         #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
-        #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
         #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
         pub user_vault: Account<'info, UserVault>,
-        #[account(mut, close=user_vault, seeds=[b"spin", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+        #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"spin", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
         pub pending: Account<'info, PendingSlots>,
         pub system_program: Program<'info, System>,
         /// CHECK
         #[account(address = SYSVAR_INSTRUCTIONS_ID)]
         pub sysvar_instructions: UncheckedAccount<'info>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        #[account(mut, seeds=[b"resolvers"], bump=resolver_set.bump)]
+        pub resolver_set: Account<'info, ResolverSet>,
+        /// Funded the pending account at lock; gets its rent back when it closes
+        #[account(mut)] pub rent_payer: SystemAccount<'info>,
+        /// CHECK: fulfilled oracle request (oracle-mode bets only); must match the pending record
+        pub oracle_request: Option<UncheckedAccount<'info>>,
     }
 
     pub fn slots_lock(ctx: Context<SlotsLock>, args: SlotsLockArgs) -> Result<()> {
-        require!(args.bet_amount >= MIN_BET_LAMPORTS && args.bet_amount <= MAX_BET_LAMPORTS, CasinoErr::BadParams);
-        require_ed25519_present(
+        require!(!ctx.accounts.game_state.house.flags.lock_paused(GAME_SLOTS), CasinoErr::Paused);
+        let limits = lock_limits(&ctx.accounts.game_config)?;
+        require!(limits.bet_ok(args.bet_amount), CasinoErr::BadParams);
+        require_lock_window(GAME_SLOTS, args.expiry_unix, args.expiry_slot)?;
+        require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
+        advance_nonce(&mut ctx.accounts.user_vault, args.nonce)?;
+        require_player_consent(
             &ctx.accounts.sysvar_instructions.to_account_info(),
-            args.ed25519_instr_index,
+            &ctx.accounts.player.to_account_info(),
+            ctx.accounts.session.as_mut(),
+            ctx.accounts.session_signer.as_ref(),
+            ctx.program_id,
+            GAME_SLOTS,
+            &slots_params(args.bet_amount),
+            &args.client_seed,
+            args.bet_amount,
+            args.nonce,
+            args.expiry_unix,
         )?;
-        require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
 
-        let uv_bal = **ctx.accounts.user_vault.to_account_info().lamports.borrow();
-        let need = args.bet_amount.saturating_add(FEE_REIMBURSE_LAMPORTS);
-        require!(uv_bal >= need, CasinoErr::InsufficientVault);
+        let pending_key = ctx.accounts.pending.key();
+        let (server_commit, oracle_request) = randomness::lock(
+            &ctx.accounts.game_config.randomness,
+            ctx.accounts.oracle_program.as_deref(),
+            ctx.accounts.oracle_request.as_deref(),
+            Some(&mut ctx.accounts.user_vault.seed_commit),
+            &ctx.accounts.fee_payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &pending_key,
+        )?;
+        let slot_hash = fair::latest_slot_hash(&ctx.accounts.slot_hashes.to_account_info())
+            .ok_or(CasinoErr::BadParams)?;
+
+        let fee = lock_fee(&ctx.accounts.game_state, &ctx.accounts.user_vault, GAME_SLOTS);
+        let need = args.bet_amount.saturating_add(fee);
+        require_debit(&ctx.accounts.user_vault.to_account_info(), need)?;
 
         let uv_ai = ctx.accounts.user_vault.to_account_info();
         let hv_ai = ctx.accounts.house_vault.to_account_info();
         safe_move_lamports(&uv_ai, &hv_ai, args.bet_amount)?;
 
-        if FEE_REIMBURSE_LAMPORTS > 0 {
+        if fee > 0 {
             let fp_ai = ctx.accounts.fee_payer.to_account_info();
-            safe_move_lamports(&uv_ai, &fp_ai, FEE_REIMBURSE_LAMPORTS)?;
+            safe_move_lamports(&uv_ai, &fp_ai, fee)?;
         }
 
+        let reserved = ctx.accounts.game_state.house.reserve(&hv_ai, HOUSE_RESERVE_LAMPORTS, exposure::multiple(args.bet_amount, limits.max_param))?;
+
         let p = &mut ctx.accounts.pending;
         p.player = ctx.accounts.player.key();
         p.amount = args.bet_amount;
         p.nonce = args.nonce;
         p.expiry_unix = args.expiry_unix;
+        p.expiry_slot = args.expiry_slot;
+        p.server_commit = server_commit;
+        p.client_seed = args.client_seed;
+        p.slot_hash = slot_hash;
+        p.oracle_request = oracle_request;
+        p.exposure = reserved;
+        p.rent_payer = ctx.accounts.fee_payer.key();
+        p.house_edge_bps = limits.house_edge_bps;
         p.settled = false;
         emit!(SlotsLocked {
             player: p.player,
             amount: p.amount,
             nonce: p.nonce,
+            slot_hash: p.slot_hash,
         });
         Ok(())
     }
 
     pub fn slots_resolve(ctx: Context<SlotsResolve>, args: SlotsResolveArgs) -> Result<()> {
+        require!(!ctx.accounts.game_state.house.flags.resolve_paused(GAME_SLOTS), CasinoErr::Paused);
+        let pending_key = ctx.accounts.pending.key();
+        let house_key   = ctx.accounts.house_vault.key();
+        let player_key  = ctx.accounts.player.key();
+
         let p = &mut ctx.accounts.pending;
         require!(!p.settled, CasinoErr::BadPending);
 
         let clock = Clock::get()?;
-        require!(clock.unix_timestamp <= p.expiry_unix, CasinoErr::Expired);
-        require_ed25519_present(
+        require!(!expiry::is_expired(&clock, p.expiry_unix, p.expiry_slot), CasinoErr::Expired);
+
+        let seed = randomness::resolve_seed(
+            &p.oracle_request,
+            ctx.accounts.oracle_request.as_deref(),
+            &p.server_commit,
+            &args.server_seed,
+        )?;
+        let outcome = fair::outcome_hash(&seed, &p.client_seed, &p.slot_hash, p.nonce);
+
+        let expected = message::resolve_message(
+            SLOTS_DOMAIN_TAG,
+            ctx.program_id,
+            &house_key,
+            &player_key,
+            &pending_key,
+            &[&slots_params(p.amount), &outcome],
+            args.payout,
+            p.nonce,
+            p.expiry_unix,
+        );
+        cosign::require_signers(
             &ctx.accounts.sysvar_instructions.to_account_info(),
-            args.ed25519_instr_index,
+            &mut ctx.accounts.resolver_set.cosigners,
+            &expected,
+            args.payout,
+            Clock::get()?.epoch,
         )?;
 
-        require!(args.payout <= MAX_PAYOUT_LAMPORTS, CasinoErr::BadPayout);
+        let due = payout::slots(p.amount, fair::slots_reels(&outcome), p.house_edge_bps);
+        require!(args.payout == due && due <= p.exposure, CasinoErr::BadPayout);
         if args.payout > 0 {
             let bump_v = ctx.bumps.house_vault;
             let ix = system_instruction::transfer(
@@ -1161,13 +3054,51 @@ pub mod casino {
                 &[&[b"vault", &[bump_v]]],
             )?;
         }
+        record_outflow(&mut ctx.accounts.game_state, p.amount, args.payout)?;
+        ctx.accounts.game_state.house.release(p.exposure);
         p.settled = true;
         emit!(SlotsResolved {
             player: p.player,
             payout: args.payout,
-            checksum: args.checksum,
+            outcome,
             nonce: p.nonce,
         });
         Ok(())
     }
+
+    #[derive(Accounts)]
+    pub struct SlotsRefund<'info> {
+        pub player: SystemAccount<'info>,
+        #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
+        #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
+        pub user_vault: Account<'info, UserVault>,
+        #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"spin", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+        pub pending: Account<'info, PendingSlots>,
+        pub system_program: Program<'info, System>,
+        #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+        pub game_state: Account<'info, GameState>,
+        /// Funded the pending account at lock; gets its rent back when it closes
+        #[account(mut)] pub rent_payer: SystemAccount<'info>,
+    }
+
+    /// Permissionless: once past expiry + grace, return the stake to the player's user_vault.
+    pub fn slots_refund_expired(ctx: Context<SlotsRefund>) -> Result<()> {
+        let p = &ctx.accounts.pending;
+        require_refundable(p.settled, p.expiry_unix, p.expiry_slot)?;
+        let amount = p.amount;
+
+        refund_principal(
+            &ctx.accounts.house_vault,
+            &ctx.accounts.user_vault,
+            &ctx.accounts.system_program,
+            ctx.bumps.house_vault,
+            amount,
+        )?;
+
+        let p = &mut ctx.accounts.pending;
+        ctx.accounts.game_state.house.release(p.exposure);
+        p.settled = true;
+        emit!(BetRefunded { player: p.player, game: GAME_SLOTS, amount, nonce: p.nonce });
+        Ok(())
+    }
 }
@@ -6,15 +6,40 @@ use anchor_lang::solana_program::{
     system_instruction,
     sysvar,
 };
+use casino_common::config::GameLimits;
+use casino_common::cosign::{self, Cosigners, PendingCosigners};
+use casino_common::deploy;
+use casino_common::house::{BetLimits, House};
+use casino_common::roles::{self, Roles};
+use casino_common::{admin, expiry, exposure, fair, message, pause, payout};
 
 declare_id!("5NnSFQrH7BF14ocwmUMTbMktBjo7E2496pvi2w9Up4KA");
 
+// Canonical domain tag for off-chain signing
+const DOMAIN_TAG: &[u8] = b"COINFLIP_V1";
+// After `expired_at` / `expiry_slot`, before anyone may refund an unresolved entry
+const REFUND_GRACE_SECS: i64 = 600;
+const REFUND_GRACE_SLOTS: u64 = 1_500;
+// Longest lock → expiry window (covers waiting for the opponent)
+const MAX_TTL_SECS: i64 = 600;
+const MAX_TTL_SLOTS: u64 = 1_500;
+// Vault balance never promised to open entries; default and highest share of the vault (bps)
+// one entry may win
+const HOUSE_RESERVE_LAMPORTS: u64 = 100_000_000;
+// Net vault outflow (50 SOL) over the last hour that trips the circuit breaker
+const DEFAULT_OUTFLOW_THRESHOLD: u64 = 50_000_000_000;
+const DEFAULT_OUTFLOW_WINDOW_HOURS: u8 = 1;
+
 #[program]
 pub mod coinflip {
     use super::*;
 
     /// Create the vault PDA as a plain System account (space = 0).
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        require!(
+            deploy::may_initialize(ctx.program_id, &ctx.accounts.authority.key(), ctx.accounts.program_data.as_deref()),
+            CfError::NotDeployer
+        );
         // If it's already funded/created, do nothing
         if ctx.accounts.vault.lamports() > 0 {
             return Ok(());
@@ -45,17 +70,254 @@ pub mod coinflip {
         Ok(())
     }
 
-    /// Player deposits entry into vault and opens their pending round.
+    /// One-time: create the AdminConfig PDA. `admin_pubkey` becomes the resolver, which only
+    /// posts seed commitments (resolves are signed by the resolver set), and the
+    /// signer holds every other role until rotated.
+    pub fn init_admin(ctx: Context<InitAdmin>, admin_pubkey: [u8; 32]) -> Result<()> {
+        require!(
+            deploy::may_initialize(ctx.program_id, &ctx.accounts.authority.key(), ctx.accounts.program_data.as_deref()),
+            CfError::NotDeployer
+        );
+        let roles = Roles::new(Pubkey::new_from_array(admin_pubkey), ctx.accounts.authority.key());
+        ctx.accounts.admin_config.roles = roles;
+        for (role, key) in roles.keys.iter().enumerate() {
+            emit!(RoleChanged { role: role as u8, previous: Pubkey::default(), current: *key });
+        }
+        Ok(())
+    }
+
+    /// Super-admin queues `new_key` for `role` (see `casino_common::roles`); it can be
+    /// applied once `roles::ROLE_TIMELOCK_SECS` have passed.
+    pub fn propose_role(ctx: Context<ManageRole>, role: u8, new_key: Pubkey) -> Result<()> {
+        let roles = &mut ctx.accounts.admin_config.roles;
+        let eta = admin::propose_role(roles, &ctx.accounts.authority.key(), role, new_key)?;
+        emit!(RoleChangeProposed { role, current: roles.key(role), proposed: new_key, eta });
+        Ok(())
+    }
+
+    /// Super-admin drops a pending role change before it is applied.
+    pub fn cancel_role(ctx: Context<ManageRole>, role: u8) -> Result<()> {
+        let roles = &mut ctx.accounts.admin_config.roles;
+        let proposed = admin::cancel_role(roles, &ctx.accounts.authority.key(), role)?;
+        emit!(RoleChangeCancelled { role, proposed });
+        Ok(())
+    }
+
+    /// Permissionless: installs a proposed key once its timelock has elapsed.
+    pub fn apply_role(ctx: Context<ApplyRole>, role: u8) -> Result<()> {
+        let (previous, current) = admin::apply_role(&mut ctx.accounts.admin_config.roles, role)?;
+        emit!(RoleChanged { role, previous, current });
+        Ok(())
+    }
+
+    /// Creates the (unpaused) GameState PDA that locks and resolves read.
+    pub fn init_game_state(ctx: Context<InitGameState>) -> Result<()> {
+        admin::require_pauser(&ctx.accounts.admin_config.roles, &ctx.accounts.authority.key())?;
+        let gs = &mut ctx.accounts.game_state;
+        gs.house = House::new(DEFAULT_OUTFLOW_THRESHOLD, DEFAULT_OUTFLOW_WINDOW_HOURS);
+        gs.bump = ctx.bumps.game_state;
+        Ok(())
+    }
+
+    /// Halts new bets, and resolves too when `halt_resolve`; a resolve halt already in place
+    /// stays until `unpause`. `reason` is an incident code echoed in the event. Expired-bet
+    /// refunds stay open.
+    pub fn pause(ctx: Context<SetPause>, halt_resolve: bool, reason: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let house = &mut ctx.accounts.game_state.house;
+        admin::pause(&ctx.accounts.admin_config.roles, &by, house, pause::GLOBAL, halt_resolve)?;
+        emit!(GamePaused { by, halt_resolve, reason });
+        Ok(())
+    }
+
+    pub fn unpause(ctx: Context<SetPause>, reason: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::unpause(&ctx.accounts.admin_config.roles, &by, &mut ctx.accounts.game_state.house, pause::GLOBAL)?;
+        emit!(GameUnpaused { by, reason });
+        Ok(())
+    }
+
+    /// Config manager sets the share of the vault (bps) one round may stand to win; the maximum
+    /// bet for every setting follows from it.
+    pub fn set_max_payout_bps(ctx: Context<SetLimits>, max_payout_bps: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let house = &mut ctx.accounts.game_state.house;
+        let previous_bps = admin::set_max_payout_bps(&ctx.accounts.admin_config.roles, &by, house, max_payout_bps)?;
+        emit!(MaxPayoutChanged { by, previous_bps, current_bps: max_payout_bps });
+        Ok(())
+    }
+
+    /// Config manager sets the net outflow (payouts minus stakes, lamports; 0 disables) over the
+    /// last `window_hours` that trips the circuit breaker and pauses the game.
+    pub fn set_outflow_breaker(ctx: Context<SetLimits>, threshold: u64, window_hours: u8) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let house = &mut ctx.accounts.game_state.house;
+        admin::set_outflow_breaker(&ctx.accounts.admin_config.roles, &by, house, threshold, window_hours)?;
+        emit!(BreakerChanged { by, threshold, window_hours });
+        Ok(())
+    }
+
+    /// Super-admin registers up to `MAX_COSIGNERS` resolver keys, one per backend signer
+    /// instance, each allowed to sign `epoch_budget` lamports of payouts per epoch (0 = no limit).
+    /// A payout above `threshold` lamports needs `quorum` of them to sign the resolve message.
+    pub fn init_resolver_set(
+        ctx: Context<InitResolverSet>,
+        keys: Vec<Pubkey>,
+        quorum: u8,
+        threshold: u64,
+        epoch_budget: u64,
+    ) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut ctx.accounts.resolver_set;
+        rs.cosigners = admin::new_resolver_set(&ctx.accounts.admin_config.roles, &by, &keys, quorum, threshold, epoch_budget)?;
+        rs.bump = ctx.bumps.resolver_set;
+        emit!(ResolverSetChanged { by, keys, quorum, threshold, epoch_budget });
+        Ok(())
+    }
+
+    /// Super-admin queues a replacement for the resolver keys, quorum, co-signing threshold and
+    /// budgets (every key enabled); `apply_resolver_set` installs it after ROLE_TIMELOCK_SECS.
+    pub fn propose_resolver_set(
+        ctx: Context<SetResolverSet>,
+        keys: Vec<Pubkey>,
+        quorum: u8,
+        threshold: u64,
+        epoch_budget: u64,
+    ) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let roles = &ctx.accounts.admin_config.roles;
+        let pending = &mut ctx.accounts.resolver_set.pending;
+        let eta = admin::propose_resolver_set(roles, &by, pending, &keys, quorum, threshold, epoch_budget)?;
+        emit!(ResolverSetProposed { by, keys, quorum, threshold, epoch_budget, eta });
+        Ok(())
+    }
+
+    /// Super-admin drops the pending resolver-set change (a proposed set or queued key changes).
+    pub fn cancel_resolver_set(ctx: Context<SetResolverSet>) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::cancel_resolver_set(&ctx.accounts.admin_config.roles, &by, &mut ctx.accounts.resolver_set.pending)?;
+        emit!(ResolverSetCancelled { by });
+        Ok(())
+    }
+
+    /// Permissionless: installs the pending resolver set once its timelock has elapsed.
+    pub fn apply_resolver_set(ctx: Context<ApplyResolverSet>) -> Result<()> {
+        let rs = &mut *ctx.accounts.resolver_set;
+        admin::apply_resolver_set(&mut rs.cosigners, &mut rs.pending)?;
+        let cosigners = &rs.cosigners;
+        let keys = cosigners.keys[..cosigners.count as usize].iter().map(|k| k.key).collect();
+        emit!(ResolverSetApplied { keys, quorum: cosigners.quorum, threshold: cosigners.threshold });
+        Ok(())
+    }
+
+    /// Super-admin sets one resolver key's enabled flag and per-epoch budget. Disabling it or
+    /// lowering the budget applies at once; enabling it or raising the budget is queued behind
+    /// the timelock for `apply_resolver_set`.
+    pub fn set_resolver_key(ctx: Context<SetResolverSet>, key: Pubkey, enabled: bool, epoch_budget: u64) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut *ctx.accounts.resolver_set;
+        let roles = &ctx.accounts.admin_config.roles;
+        match admin::set_resolver_key(roles, &by, &mut rs.cosigners, &mut rs.pending, &key, enabled, epoch_budget)? {
+            None => emit!(ResolverKeyChanged { by, key, enabled, epoch_budget }),
+            Some(eta) => emit!(ResolverKeyChangeProposed { by, key, enabled, epoch_budget, eta }),
+        }
+        Ok(())
+    }
+
+    /// Pauser or super-admin disables one resolver key (e.g. a compromised signer instance),
+    /// in the live set and in any pending one.
+    pub fn disable_resolver_key(ctx: Context<SetResolverSet>, key: Pubkey) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut *ctx.accounts.resolver_set;
+        let roles = &ctx.accounts.admin_config.roles;
+        let epoch_budget = admin::disable_resolver_key(roles, &by, &mut rs.cosigners, &mut rs.pending, &key)?;
+        emit!(ResolverKeyChanged { by, key, enabled: false, epoch_budget });
+        Ok(())
+    }
+
+    /// Config manager creates the game config (bet limits, house edge, enabled flag); locks
+    /// fail until it exists.
+    pub fn init_game_config(ctx: Context<InitGameConfig>, enabled: bool, limits: GameLimits) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        require!(limits_supported(&limits), CfError::BadConfig);
+        let cfg = &mut ctx.accounts.game_config;
+        cfg.enabled = enabled;
+        cfg.version = 1;
+        cfg.limits = limits;
+        cfg.bump = ctx.bumps.game_config;
+        emit!(GameConfigChanged { by, version: cfg.version, enabled, limits });
+        Ok(())
+    }
+
+    /// Config manager replaces the limits and enabled flag and bumps `version`. Open rounds
+    /// keep the exposure recorded at lock.
+    pub fn update_game_config(ctx: Context<UpdateGameConfig>, enabled: bool, limits: GameLimits) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        require!(limits_supported(&limits), CfError::BadConfig);
+        let cfg = &mut ctx.accounts.game_config;
+        cfg.enabled = enabled;
+        cfg.limits = limits;
+        cfg.version = cfg.version.saturating_add(1);
+        emit!(GameConfigChanged { by, version: cfg.version, enabled, limits });
+        Ok(())
+    }
+
+    /// Read-only (simulate): bankroll limits and the largest entry.
+    pub fn bet_limits(ctx: Context<QueryLimits>) -> Result<BetLimits> {
+        let max_bet = ctx.accounts.game_config.limits.max_bet;
+        ctx.accounts.game_state.house.limits_for(&ctx.accounts.vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, max_bet, exposure::flip)
+    }
+
+    /// Resolver posts `sha256(server_seed)` for the player's next match (overwrites an unused one).
+    /// Both players of a match get the same commitment, so the revealed coin is shared.
+    pub fn commit_seed(ctx: Context<CommitSeed>, commitment: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.resolver.key().to_bytes() == ctx.accounts.admin_config.roles.resolver_key(),
+            CfError::Unauthorized
+        );
+        require!(commitment != fair::EMPTY_COMMITMENT, CfError::NoSeedCommit);
+
+        let sc = &mut ctx.accounts.seed_commit;
+        sc.player = ctx.accounts.player.key();
+        sc.commitment = commitment;
+        sc.bump = ctx.bumps.seed_commit;
+        Ok(())
+    }
+
+    /// Player deposits entry into vault and opens their pending round (consumes the seed commitment).
     pub fn lock(
         ctx: Context<Lock>,
         entry_lamports: u64,
         side: u8,         // 0=heads, 1=tails
         nonce: u64,       // shared match nonce
-        expiry_unix: i64, // not enforced here
+        expiry_unix: i64,
+        expiry_slot: u64, // 0 = unix deadline only
     ) -> Result<()> {
-        require!(entry_lamports > 0, CfError::BadBet);
+        require!(!ctx.accounts.game_state.house.flags.lock_paused(pause::SOLE_GAME), CfError::Paused);
+        let limits = lock_limits(&ctx.accounts.game_config)?;
+        require!(limits.bet_ok(entry_lamports), CfError::BadBet);
         require!(side <= 1, CfError::BadSide);
 
+        // Deadline must lie in the future and within the max TTL
+        let clock = Clock::get()?;
+        require!(
+            expiry::lock_window_ok(&clock, expiry_unix, MAX_TTL_SECS, expiry_slot, MAX_TTL_SLOTS),
+            CfError::BadExpiry
+        );
+
+        // Nonces only move forward (a closed pending PDA must never be re-opened)
+        let pn = &mut ctx.accounts.player_nonce;
+        require!(nonce > pn.last_nonce, CfError::StaleNonce);
+        pn.player = ctx.accounts.player.key();
+        pn.last_nonce = nonce;
+        pn.bump = ctx.bumps.player_nonce;
+
+        let server_commit = ctx.accounts.seed_commit.commitment;
+        require!(server_commit != fair::EMPTY_COMMITMENT, CfError::NoSeedCommit);
+        ctx.accounts.seed_commit.commitment = fair::EMPTY_COMMITMENT;
+
         // Transfer player → vault
         let ix = system_instruction::transfer(
             &ctx.accounts.player.key(),
@@ -71,6 +333,10 @@ pub mod coinflip {
             ],
         )?;
 
+        // Reserve the winning payout against the vault
+        let reserved = exposure::flip(entry_lamports);
+        ctx.accounts.game_state.house.reserve(&ctx.accounts.vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, reserved)?;
+
         // Initialize pending
         let p = &mut ctx.accounts.pending;
         p.player = ctx.accounts.player.key();
@@ -78,31 +344,75 @@ pub mod coinflip {
         p.side = side;
         p.nonce = nonce;
         p.expired_at = expiry_unix;
+        p.expiry_slot = expiry_slot;
+        p.server_commit = server_commit;
+        p.exposure = reserved;
+        p.house_edge_bps = limits.house_edge_bps;
         p.settled = false;
 
         Ok(())
     }
 
     /// Backend resolves: pays winner from vault; loser payout=0. Closes pending to player.
+    /// A registered resolver key must either sign this instruction as `admin` or attest the
+    /// canonical message through an ed25519 pre-instruction; a payout above the registry's
+    /// threshold needs `quorum` attestations.
+    /// The coin is `coin_side(sha256(server_seed | nonce))`, identical for both players of a match.
     pub fn resolve(
         ctx: Context<Resolve>,
-        checksum: u8,         // must equal (nonce % 251) + 1
+        server_seed: [u8; 32], // opens pending.server_commit
         payout: u64,          // lamports paid to this player
-        _ed25519_ix_index: u8,
-        winner_side: u8,      // 0=heads,1=tails (from backend RNG)
     ) -> Result<()> {
+        require!(!ctx.accounts.game_state.house.flags.resolve_paused(pause::SOLE_GAME), CfError::Paused);
+        let pending_key = ctx.accounts.pending.key();
+        let vault_key = ctx.accounts.vault.key();
+        let player_key = ctx.accounts.player.key();
+
+        let pending = &ctx.accounts.pending;
+        require!(fair::is_reveal_of(&pending.server_commit, &server_seed), CfError::BadReveal);
+        let outcome = fair::outcome_hash(&server_seed, &[], &[], pending.nonce);
+        let winner_side = fair::coin_side(&outcome);
+
+        // a registered resolver key signs directly, or the ed25519 run before this instruction
+        // holds registered signature(s) over the canonical message (see casino_common::cosign)
+        let admin_key = ctx.accounts.admin.key();
+        let epoch = Clock::get()?.epoch;
+        let resolvers = &mut ctx.accounts.resolver_set.cosigners;
+        if ctx.accounts.admin.is_signer && resolvers.contains(&admin_key) && !resolvers.requires_quorum(payout) {
+            resolvers.authorize_signer(&admin_key, payout, epoch)?;
+        } else {
+            // game body: entry_lamports | side | winner_side (see casino_common::message)
+            let expected = message::resolve_message(
+                DOMAIN_TAG,
+                ctx.program_id,
+                &vault_key,
+                &player_key,
+                &pending_key,
+                &[&pending.entry_lamports.to_le_bytes(), &[pending.side, winner_side]],
+                payout,
+                pending.nonce,
+                pending.expired_at,
+            );
+            cosign::require_signers(
+                &ctx.accounts.instructions.to_account_info(),
+                resolvers,
+                &expected,
+                payout,
+                epoch,
+            )?;
+        }
+
         let pending = &mut ctx.accounts.pending;
 
         // Rails
-        let expected = ((pending.nonce % 251) + 1) as u8;
-        require!(checksum == expected, CfError::BadChecksum);
         require!(!pending.settled, CfError::AlreadySettled);
         require!(pending.player == ctx.accounts.player.key(), CfError::PlayerMismatch);
-        require!(winner_side <= 1, CfError::BadSide);
 
         if payout > 0 {
             require!(winner_side == pending.side, CfError::WrongWinnerSide);
         }
+        let due = payout::flip(pending.entry_lamports, pending.side, winner_side, pending.house_edge_bps);
+        require!(payout == due && due <= pending.exposure, CfError::BadPayout);
 
         // Confirm vault PDA
         let (vault_pda, vault_bump) = Pubkey::find_program_address(&[b"vault"], ctx.program_id);
@@ -127,9 +437,47 @@ pub mod coinflip {
         }
 
         // mark settled; account closes to player via `close = player`
+        record_outflow(&mut ctx.accounts.game_state, pending.entry_lamports, payout)?;
+        ctx.accounts.game_state.house.release(pending.exposure);
         pending.settled = true;
         Ok(())
     }
+
+    /// Permissionless: once a deadline + grace has passed, return an unresolved entry
+    /// to the player and close the pending round.
+    pub fn refund_expired(ctx: Context<RefundExpired>) -> Result<()> {
+        let pending = &ctx.accounts.pending;
+        require!(!pending.settled, CfError::AlreadySettled);
+        let clock = Clock::get()?;
+        require!(
+            expiry::is_refundable(&clock, pending.expired_at, pending.expiry_slot, REFUND_GRACE_SECS, REFUND_GRACE_SLOTS),
+            CfError::NotRefundable
+        );
+        let amount = pending.entry_lamports;
+
+        // Return entry vault → player
+        let bump = ctx.bumps.vault;
+        let ix = system_instruction::transfer(
+            &ctx.accounts.vault.key(),
+            &ctx.accounts.player.key(),
+            amount,
+        );
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.player.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[&[b"vault", &[bump]]],
+        )?;
+
+        let pending = &mut ctx.accounts.pending;
+        ctx.accounts.game_state.house.release(pending.exposure);
+        pending.settled = true;
+        emit!(EntryRefunded { player: pending.player, amount, nonce: pending.nonce });
+        Ok(())
+    }
 }
 
 /* ---------------- Accounts ---------------- */
@@ -148,10 +496,186 @@ pub struct Initialize<'info> {
     pub vault: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
+
+    /// CHECK: this program's ProgramData (upgrade-authority check); optional when the deployer key signs
+    pub program_data: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
-#[instruction(entry_lamports: u64, side: u8, nonce: u64, expiry_unix: i64)]
+pub struct InitAdmin<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AdminConfig::SIZE,
+        seeds = [b"admin"],
+        bump
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: this program's ProgramData (upgrade-authority check); optional when the deployer key signs
+    pub program_data: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRole<'info> {
+    /// Current super-admin
+    pub authority: Signer<'info>,
+
+    #[account(mut, seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyRole<'info> {
+    /// Anyone may apply a proposed change once its timelock has elapsed
+    #[account(mut, seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitGameState<'info> {
+    /// Pauser or super-admin; pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(init, payer = authority, space = 8 + GameState::SIZE, seeds = [b"game_state"], bump)]
+    pub game_state: Account<'info, GameState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    /// Pauser or super-admin
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+}
+
+#[derive(Accounts)]
+pub struct SetLimits<'info> {
+    /// Config manager
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+}
+
+/// Read-only: simulate `bet_limits` to read the returned BetLimits
+#[derive(Accounts)]
+pub struct QueryLimits<'info> {
+    #[account(seeds = [b"vault"], bump)]
+    /// CHECK: PDA validated by seeds; only its balance is read.
+    pub vault: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+
+    #[account(seeds = [b"game_config"], bump = game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitGameConfig<'info> {
+    /// Config manager; pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(init, payer = authority, space = 8 + GameConfig::SIZE, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGameConfig<'info> {
+    /// Config manager
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut, seeds = [b"game_config"], bump = game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitResolverSet<'info> {
+    /// Super-admin; pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(init, payer = authority, space = 8 + ResolverSet::SIZE, seeds = [b"resolvers"], bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetResolverSet<'info> {
+    /// Super-admin (pauser may disable a key)
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut, seeds = [b"resolvers"], bump = resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyResolverSet<'info> {
+    /// Anyone may apply a pending resolver-set change once its timelock has elapsed
+    #[account(mut, seeds = [b"resolvers"], bump = resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+}
+
+#[derive(Accounts)]
+pub struct CommitSeed<'info> {
+    #[account(mut)]
+    pub resolver: Signer<'info>,
+
+    /// CHECK: only used as a PDA seed
+    pub player: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        space = 8 + SeedCommit::SIZE,
+        seeds = [b"seed", player.key().as_ref()],
+        bump
+    )]
+    pub seed_commit: Account<'info, SeedCommit>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_lamports: u64, side: u8, nonce: u64, expiry_unix: i64, expiry_slot: u64)]
 pub struct Lock<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
@@ -174,6 +698,27 @@ pub struct Lock<'info> {
     pub pending: Account<'info, Pending>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(mut, seeds = [b"seed", player.key().as_ref()], bump = seed_commit.bump)]
+    pub seed_commit: Account<'info, SeedCommit>,
+
+    /// Per-player nonce high-water mark (created on the player's first entry)
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerNonce::SIZE,
+        seeds = [b"nonce", player.key().as_ref()],
+        bump
+    )]
+    pub player_nonce: Account<'info, PlayerNonce>,
+
+    /// Pause switches
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+
+    /// Bet limits
+    #[account(seeds = [b"game_config"], bump = game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
 }
 
 #[derive(Accounts)]
@@ -186,7 +731,7 @@ pub struct Resolve<'info> {
     /// CHECK: PDA validated by seeds; used only for native SOL transfers.
     pub vault: UncheckedAccount<'info>,
 
-    /// CHECK: Kept for backend layout compatibility; not used by the program.
+    /// CHECK: Resolver wallet; accepted as authorization only if it signs and is registered in `resolver_set`.
     pub admin: UncheckedAccount<'info>,
 
     #[account(
@@ -201,12 +746,112 @@ pub struct Resolve<'info> {
     pub system_program: Program<'info, System>,
 
     #[account(address = sysvar::instructions::ID)]
-    /// CHECK: Sysvar Instructions account (optional ed25519 preverify by backend).
+    /// CHECK: Sysvar Instructions account (ed25519 attestation when `admin` does not sign).
     pub instructions: UncheckedAccount<'info>,
+
+    /// Pause switches
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+
+    /// Resolver key registry (charged with this payout)
+    #[account(mut, seeds = [b"resolvers"], bump = resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+}
+
+#[derive(Accounts)]
+pub struct RefundExpired<'info> {
+    #[account(mut)]
+    /// CHECK: Receiver of the refund; key is compared with `pending.player`.
+    pub player: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"vault"], bump)]
+    /// CHECK: PDA validated by seeds; used only for native SOL transfers.
+    pub vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = player,
+        close = player,
+        seeds = [b"match", player.key().as_ref(), &pending.nonce.to_le_bytes()],
+        bump
+    )]
+    pub pending: Account<'info, Pending>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Open exposure is released here too
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
 }
 
 /* ---------------- Data ---------------- */
 
+#[account]
+pub struct AdminConfig {
+    pub roles: Roles, // resolver, super-admin, treasurer, pauser, config-manager (+ pending changes)
+}
+impl AdminConfig {
+    pub const SIZE: usize = Roles::SIZE;
+}
+
+#[account]
+pub struct SeedCommit {
+    pub player: Pubkey,       // 32
+    pub commitment: [u8; 32], // 32 (sha256(server_seed); zero once consumed)
+    pub bump: u8,             // 1
+}
+impl SeedCommit {
+    pub const SIZE: usize = 32 + 32 + 1; // 65
+}
+
+/// Highest match nonce the player has locked; new entries must use a strictly larger one
+/// so a closed pending PDA can never be re-opened under an old nonce.
+#[account]
+pub struct PlayerNonce {
+    pub player: Pubkey,  // 32
+    pub last_nonce: u64, // 8
+    pub bump: u8,        // 1
+}
+impl PlayerNonce {
+    pub const SIZE: usize = 32 + 8 + 1; // 41
+}
+
+/// Incident switch read by every lock (and resolve). Expired refunds never check it.
+/// The outflow breaker throws both switches when it trips.
+#[account]
+pub struct GameState {
+    pub house: House,         // House::SIZE (pause bits, open exposure, payout share, outflow breaker)
+    pub bump: u8,             // 1
+}
+impl GameState {
+    pub const SIZE: usize = House::SIZE + 1;
+}
+
+/// Bet limits, house edge and on/off switch read by every lock (see casino_common::config).
+#[account]
+pub struct GameConfig {
+    pub enabled: bool,       // 1 (rejects new locks when false)
+    pub version: u32,        // 4 (counts updates)
+    pub limits: GameLimits,  // GameLimits::SIZE
+    pub bump: u8,            // 1
+}
+impl GameConfig {
+    pub const SIZE: usize = 1 + 4 + GameLimits::SIZE + 1;
+}
+
+/// Resolver key registry, one key per backend signer instance, each with an enabled flag and a
+/// per-epoch payout budget. A payout above the threshold needs K-of-N co-signatures, smaller
+/// payouts any one usable key (see casino_common::cosign).
+#[account]
+pub struct ResolverSet {
+    pub cosigners: Cosigners,       // Cosigners::SIZE
+    pub bump: u8,                   // 1
+    pub pending: PendingCosigners,  // PendingCosigners::SIZE
+}
+impl ResolverSet {
+    pub const SIZE: usize = Cosigners::SIZE + 1 + PendingCosigners::SIZE;
+}
+
 #[account]
 pub struct Pending {
     pub player: Pubkey,      // 32
@@ -214,10 +859,14 @@ pub struct Pending {
     pub side: u8,            // 1  (0=heads,1=tails)
     pub nonce: u64,          // 8
     pub expired_at: i64,     // 8
+    pub server_commit: [u8; 32], // 32
+    pub expiry_slot: u64,    // 8  (0 = no slot deadline)
+    pub exposure: u64,       // 8  (winning payout reserved in GameState at lock)
+    pub house_edge_bps: u16, // 2  (config edge at lock; prices the winning payout)
     pub settled: bool,       // 1
 }
 impl Pending {
-    pub const SIZE: usize = 32 + 8 + 1 + 8 + 8 + 1; // 58
+    pub const SIZE: usize = 32 + 8 + 1 + 8 + 8 + 32 + 8 + 8 + 2 + 1; // 108
 }
 
 #[error_code]
@@ -229,4 +878,162 @@ pub enum CfError {
     #[msg("Pending player mismatch")] PlayerMismatch,
     #[msg("Vault PDA mismatch")] VaultMismatch,
     #[msg("Winner side does not match player's chosen side")] WrongWinnerSide,
+    #[msg("Resolver not authorized")] Unauthorized,
+    #[msg("No server seed commitment posted")] NoSeedCommit,
+    #[msg("Server seed does not open the commitment")] BadReveal,
+    #[msg("Entry not yet refundable")] NotRefundable,
+    #[msg("Expiry outside the allowed window")] BadExpiry,
+    #[msg("Nonce must exceed the last one used")] StaleNonce,
+    #[msg("Only the upgrade authority or deployer may initialize")] NotDeployer,
+    #[msg("Game is paused")] Paused,
+    #[msg("Payout does not match the round's outcome")] BadPayout,
+    #[msg("Invalid config value")] BadConfig,
+    #[msg("Game is disabled")] GameDisabled,
 }
+
+/* ---------------- Events ---------------- */
+
+#[event]
+pub struct EntryRefunded {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub nonce: u64,
+}
+
+#[event]
+pub struct RoleChangeProposed {
+    pub role: u8,
+    pub current: Pubkey,
+    pub proposed: Pubkey,
+    pub eta: i64,
+}
+
+#[event]
+pub struct RoleChangeCancelled {
+    pub role: u8,
+    pub proposed: Pubkey,
+}
+
+#[event]
+pub struct RoleChanged {
+    pub role: u8,
+    pub previous: Pubkey,
+    pub current: Pubkey,
+}
+
+#[event]
+pub struct GamePaused {
+    pub by: Pubkey,
+    pub halt_resolve: bool,
+    pub reason: u16,
+}
+
+#[event]
+pub struct GameUnpaused {
+    pub by: Pubkey,
+    pub reason: u16,
+}
+
+#[event]
+pub struct MaxPayoutChanged {
+    pub by: Pubkey,
+    pub previous_bps: u16,
+    pub current_bps: u16,
+}
+
+#[event]
+pub struct BreakerChanged {
+    pub by: Pubkey,
+    pub threshold: u64,
+    pub window_hours: u8,
+}
+
+#[event]
+pub struct BreakerTripped {
+    pub net_outflow: u64,
+    pub threshold: u64,
+    pub window_hours: u8,
+}
+
+#[event]
+pub struct ResolverSetChanged {
+    pub by: Pubkey,
+    pub keys: Vec<Pubkey>,
+    pub quorum: u8,
+    pub threshold: u64,
+    pub epoch_budget: u64,
+}
+
+#[event]
+pub struct ResolverKeyChanged {
+    pub by: Pubkey,
+    pub key: Pubkey,
+    pub enabled: bool,
+    pub epoch_budget: u64,
+}
+
+#[event]
+pub struct ResolverSetProposed {
+    pub by: Pubkey,
+    pub keys: Vec<Pubkey>,
+    pub quorum: u8,
+    pub threshold: u64,
+    pub epoch_budget: u64,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ResolverSetCancelled {
+    pub by: Pubkey,
+}
+
+#[event]
+pub struct ResolverSetApplied {
+    pub keys: Vec<Pubkey>,
+    pub quorum: u8,
+    pub threshold: u64,
+}
+
+#[event]
+pub struct ResolverKeyChangeProposed {
+    pub by: Pubkey,
+    pub key: Pubkey,
+    pub enabled: bool,
+    pub epoch_budget: u64,
+    pub eta: i64,
+}
+
+#[event]
+pub struct GameConfigChanged {
+    pub by: Pubkey,
+    pub version: u32,
+    pub enabled: bool,
+    pub limits: GameLimits,
+}
+
+/* ---------------- Helpers ---------------- */
+
+/// Limits a new round must meet; fails while the game is disabled.
+fn lock_limits(cfg: &GameConfig) -> Result<GameLimits> {
+    require!(cfg.enabled, CfError::GameDisabled);
+    Ok(cfg.limits)
+}
+
+/// Coin flip has no game parameter; only the stake range and edge are checked.
+fn limits_supported(limits: &GameLimits) -> bool {
+    limits.is_valid()
+}
+
+/// Feed a resolved round's stake and payout into the outflow breaker. If it trips, locks and
+/// resolves are paused (this resolve still settles) until a pauser unpauses.
+fn record_outflow(game_state: &mut GameState, staked: u64, paid: u64) -> Result<()> {
+    if let Some(net_outflow) = game_state.house.record_outflow(staked, paid)? {
+        emit!(BreakerTripped {
+            net_outflow,
+            threshold: game_state.house.breaker.threshold,
+            window_hours: game_state.house.breaker.window_hours,
+        });
+    }
+    Ok(())
+}
+
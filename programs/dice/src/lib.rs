@@ -1,41 +1,56 @@
 use anchor_lang::system_program;
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{
-    ed25519_program,
-    instruction::Instruction,
     program::{invoke, invoke_signed},
     system_instruction,
-    sysvar::instructions::{
-        load_current_index_checked,
-        load_instruction_at_checked,
-        ID as SYSVAR_INSTRUCTIONS_ID,
-    },
+    sysvar::{instructions::ID as SYSVAR_INSTRUCTIONS_ID, slot_hashes},
 };
+use casino_common::config::GameLimits;
+use casino_common::cosign::{self, Cosigners, PendingCosigners};
+use casino_common::deploy;
+use casino_common::house::{BetLimits, House};
+use casino_common::randomness::RandomnessConfig;
+use casino_common::roles::{self, Roles};
+use casino_common::{admin, expiry, exposure, fair, message, pause, payout, randomness};
 
 /// 👇 set this to your CURRENT deployed program id
 declare_id!("2m2qnCreEkuSf1CCZmWvjyBgAWkDYF13quCvsyEkDzGT");
 
 // App rails
 const DOMAIN_TAG: &[u8] = b"DICE_V1";
-const MAX_PAYOUT_LAMPORTS: u64 = 50_000_000_000; // 0.05 SOL
-const MIN_BET_LAMPORTS: u64 = 50_000;            // 0.00005 SOL
-const MAX_BET_LAMPORTS: u64 = 5_000_000_000;     // 5 SOL
+const REFUND_GRACE_SECS: i64 = 600;              // after expiry_unix, before anyone may refund
+const REFUND_GRACE_SLOTS: u64 = 1_500;           // same grace past an optional expiry_slot (~10 min)
+const MAX_TTL_SECS: i64 = 300;                   // longest lock → expiry_unix window
+const MAX_TTL_SLOTS: u64 = 750;                  // longest lock → expiry_slot window
+const HOUSE_RESERVE_LAMPORTS: u64 = 100_000_000; // vault balance never promised to open bets (0.1 SOL)
+const DEFAULT_OUTFLOW_THRESHOLD: u64 = 50_000_000_000; // net vault outflow that trips the breaker (50 SOL)
+const DEFAULT_OUTFLOW_WINDOW_HOURS: u8 = 1;       // hours of outflow the breaker sums
 
 #[error_code]
 pub enum DiceError {
     #[msg("Invalid instruction data")] InvalidIx,
-    #[msg("Invalid ed25519 pre-instruction")] InvalidEd25519,
     #[msg("Expired signature")] Expired,
     #[msg("Bet params invalid")] BadParams,
     #[msg("Payout sanity check failed")] BadPayout,
     #[msg("Vault mismatch")] VaultMismatch,
     #[msg("Bet not found or already settled")] BadBet,
+    #[msg("Resolver not authorized")] Unauthorized,
+    #[msg("No server seed commitment posted")] NoSeedCommit,
+    #[msg("Bet not yet refundable")] NotRefundable,
+    #[msg("Expiry outside the allowed window")] BadExpiry,
+    #[msg("Nonce must exceed the last one used")] StaleNonce,
+    #[msg("Only the upgrade authority or deployer may initialize")] NotDeployer,
+    #[msg("Game is paused")] Paused,
+    #[msg("Game is disabled")] GameDisabled,
 }
 
 #[account]
 pub struct AdminConfig {
-    /// Trusted ed25519 public key (32 bytes) of your backend signer
-    pub admin_pubkey: [u8; 32],
+    /// Resolver (your backend signer), super-admin, treasurer, pauser and config-manager keys
+    pub roles: Roles,
+}
+impl AdminConfig {
+    pub const LEN: usize = 8 + Roles::SIZE;
 }
 
 #[account]
@@ -46,11 +61,79 @@ pub struct PendingBet {
     pub target: u8,   // 2..98
     pub nonce: u64,
     pub expiry_unix: i64,
+    pub server_commit: [u8; 32], // seed commitment consumed at lock, opened at resolve
+    pub client_seed: [u8; 32],   // player-chosen entropy
+    pub slot_hash: [u8; 32],     // latest SlotHashes entry at lock time
+    pub oracle_request: Pubkey,  // oracle-mode request account; randomness::NO_ORACLE otherwise
+    pub expiry_slot: u64,        // optional slot deadline; expiry::NO_SLOT_DEADLINE if unused
+    pub exposure: u64,           // worst-case payout reserved in GameState at lock
+    pub house_edge_bps: u16,     // dice config's edge at lock; prices the resolve payout
     pub settled: bool,
 }
 impl PendingBet {
-    // 8 (disc) + 32 + 8 + 1 + 1 + 8 + 8 + 1 = 67
-    pub const LEN: usize = 8 + 32 + 8 + 1 + 1 + 8 + 8 + 1;
+    // 8 (disc) + 32 + 8 + 1 + 1 + 8 + 8 + 32 * 4 + 8 + 8 + 2 + 1 = 213
+    pub const LEN: usize = 8 + 32 + 8 + 1 + 1 + 8 + 8 + 32 * 4 + 8 + 8 + 2 + 1;
+}
+
+/// Resolver's `sha256(server_seed)` for the player's next bet; zeroed once a lock consumes it.
+#[account]
+pub struct SeedCommit {
+    pub player: Pubkey,
+    pub commitment: [u8; 32],
+    pub bump: u8,
+}
+impl SeedCommit {
+    pub const LEN: usize = 8 + 32 + 32 + 1;
+}
+
+/// Highest bet nonce the player has locked. Pending PDAs are closed on resolve, so new locks
+/// must use a strictly larger nonce or an old `(player, nonce)` could be re-opened.
+#[account]
+pub struct PlayerNonce {
+    pub player: Pubkey,
+    pub last_nonce: u64,
+    pub bump: u8,
+}
+impl PlayerNonce {
+    pub const LEN: usize = 8 + 32 + 8 + 1;
+}
+
+/// Incident switch read by every lock (and resolve). Expired-bet refunds never check it.
+/// The outflow breaker throws both switches when it trips.
+#[account]
+pub struct GameState {
+    pub house: House, // pause bits, open exposure, payout share, outflow breaker (see casino_common::house)
+    pub bump: u8,
+}
+impl GameState {
+    pub const LEN: usize = 8 + House::SIZE + 1;
+}
+
+/// Bet limits, house edge, on/off switch (see casino_common::config) and randomness source
+/// (see casino_common::randomness) read by every lock; `version` counts updates.
+#[account]
+pub struct GameConfig {
+    pub enabled: bool,
+    pub version: u32,
+    pub limits: GameLimits,
+    pub randomness: RandomnessConfig,
+    pub bump: u8,
+}
+impl GameConfig {
+    pub const LEN: usize = 8 + 1 + 4 + GameLimits::SIZE + RandomnessConfig::SIZE + 1;
+}
+
+/// Resolver key registry, one key per backend signer instance, each with an enabled flag and a
+/// per-epoch payout budget. A payout above the threshold needs K-of-N co-signatures, smaller
+/// payouts any one usable key (see casino_common::cosign).
+#[account]
+pub struct ResolverSet {
+    pub cosigners: Cosigners,
+    pub bump: u8,
+    pub pending: PendingCosigners,
+}
+impl ResolverSet {
+    pub const LEN: usize = 8 + Cosigners::SIZE + 1 + PendingCosigners::SIZE;
 }
 
 #[derive(Accounts)]
@@ -61,13 +144,32 @@ pub struct InitAdmin<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + 32,
+        space = AdminConfig::LEN,
         seeds = [b"admin"],
         bump
     )]
     pub admin_config: Account<'info, AdminConfig>,
 
     pub system_program: Program<'info, System>,
+
+    /// CHECK: this program's ProgramData (upgrade-authority check); optional when the deployer key signs
+    pub program_data: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRole<'info> {
+    /// Current super-admin
+    pub authority: Signer<'info>,
+
+    #[account(mut, seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyRole<'info> {
+    /// Anyone may apply a proposed change once its timelock has elapsed
+    #[account(mut, seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
 }
 
 #[derive(Accounts)]
@@ -81,6 +183,146 @@ pub struct InitVault<'info> {
     pub vault: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
+
+    /// CHECK: this program's ProgramData (upgrade-authority check); optional when the deployer key signs
+    pub program_data: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct CommitSeed<'info> {
+    /// Resolver wallet (must hold admin_config's resolver role); pays rent on first commit
+    #[account(mut, signer)]
+    pub resolver: SystemAccount<'info>,
+
+    /// CHECK: only used as a PDA seed
+    pub player: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        space = SeedCommit::LEN,
+        seeds = [b"seed", player.key().as_ref()],
+        bump
+    )]
+    pub seed_commit: Account<'info, SeedCommit>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitGameState<'info> {
+    /// Pauser or super-admin; pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(init, payer = authority, space = GameState::LEN, seeds = [b"game_state"], bump)]
+    pub game_state: Account<'info, GameState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    /// Pauser or super-admin
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+}
+
+#[derive(Accounts)]
+pub struct SetLimits<'info> {
+    /// Config manager
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+}
+
+/// Read-only: simulate `bet_limits` to read the returned BetLimits
+#[derive(Accounts)]
+pub struct QueryLimits<'info> {
+    #[account(seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+
+    #[account(seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+
+    #[account(seeds = [b"game_config"], bump = game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitGameConfig<'info> {
+    /// Config manager; pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(init, payer = authority, space = GameConfig::LEN, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGameConfig<'info> {
+    /// Config manager (super admin for set_randomness)
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut, seeds = [b"game_config"], bump = game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitResolverSet<'info> {
+    /// Super-admin; pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(init, payer = authority, space = ResolverSet::LEN, seeds = [b"resolvers"], bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetResolverSet<'info> {
+    /// Super-admin (pauser may disable a key)
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut, seeds = [b"resolvers"], bump = resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyResolverSet<'info> {
+    /// Anyone may apply a pending resolver-set change once its timelock has elapsed
+    #[account(mut, seeds = [b"resolvers"], bump = resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
 }
 
 #[derive(Accounts)]
@@ -105,6 +347,39 @@ pub struct PlaceBetLock<'info> {
     pub pending_bet: Account<'info, PendingBet>,
 
     pub system_program: Program<'info, System>,
+
+    /// Resolver's seed commitment for this bet (consumed; backend mode only)
+    #[account(mut, seeds = [b"seed", player.key().as_ref()], bump = seed_commit.bump)]
+    pub seed_commit: Option<Account<'info, SeedCommit>>,
+
+    /// CHECK: SlotHashes sysvar (entropy captured at lock)
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+
+    /// Per-player nonce high-water mark (created on the player's first bet)
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = PlayerNonce::LEN,
+        seeds = [b"nonce", player.key().as_ref()],
+        bump
+    )]
+    pub player_nonce: Account<'info, PlayerNonce>,
+
+    /// Pause switches
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+
+    /// Bet limits
+    #[account(seeds = [b"game_config"], bump = game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
+
+    /// CHECK: randomness oracle (oracle mode only); must be game_config.randomness.oracle_program
+    pub oracle_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: oracle request PDA for this bet, created by the oracle CPI
+    #[account(mut)]
+    pub oracle_request: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
@@ -117,10 +392,6 @@ pub struct ResolveBet<'info> {
     #[account(mut, seeds = [b"vault"], bump)]
     pub vault: SystemAccount<'info>,
 
-    /// Admin config PDA containing backend ed25519 pubkey
-    #[account(seeds = [b"admin"], bump)]
-    pub admin_config: Account<'info, AdminConfig>,
-
     /// Pending bet PDA to resolve (refund rent to player on success)
     #[account(
         mut,
@@ -135,6 +406,41 @@ pub struct ResolveBet<'info> {
     /// CHECK: Sysvar Instructions account used to read the ed25519 verify pre-instruction
     #[account(address = SYSVAR_INSTRUCTIONS_ID)]
     pub sysvar_instructions: UncheckedAccount<'info>,
+
+    /// Pause switches
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+
+    /// Resolver key registry (charged with this payout)
+    #[account(mut, seeds = [b"resolvers"], bump = resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+
+    /// CHECK: fulfilled oracle request (oracle-mode bets only); must match the pending record
+    pub oracle_request: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RefundExpired<'info> {
+    /// Player receiving the refund (anyone may submit once the bet is refundable)
+    #[account(mut)]
+    pub player: SystemAccount<'info>,
+
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"bet", player.key().as_ref(), &pending_bet.nonce.to_le_bytes()],
+        bump,
+        close = player
+    )]
+    pub pending_bet: Account<'info, PendingBet>,
+
+    /// Open exposure is released here too
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
@@ -144,13 +450,14 @@ pub struct PlaceBetLockArgs {
     pub target: u8,       // 2..98
     pub nonce: u64,
     pub expiry_unix: i64, // unix seconds
+    pub client_seed: [u8; 32], // player-chosen entropy
+    pub expiry_slot: u64,      // 0 = unix deadline only
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct ResolveBetArgs {
-    pub roll: u8,                 // 1..100  (backend RNG)
+    pub server_seed: [u8; 32],    // opens pending_bet.server_commit (zeroes for oracle-mode bets)
     pub payout: u64,              // 0 if loss; >0 if win (net)
-    pub ed25519_instr_index: u8,  // index hint of ed25519 verify ix
 }
 
 #[event]
@@ -171,16 +478,370 @@ pub struct BetResolved {
     pub nonce: u64,
 }
 
+#[event]
+pub struct BetRefunded {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub nonce: u64,
+}
+
+#[event]
+pub struct RoleChangeProposed {
+    pub role: u8,
+    pub current: Pubkey,
+    pub proposed: Pubkey,
+    pub eta: i64,
+}
+
+#[event]
+pub struct RoleChangeCancelled {
+    pub role: u8,
+    pub proposed: Pubkey,
+}
+
+#[event]
+pub struct RoleChanged {
+    pub role: u8,
+    pub previous: Pubkey,
+    pub current: Pubkey,
+}
+
+#[event]
+pub struct GamePaused {
+    pub by: Pubkey,
+    pub halt_resolve: bool,
+    pub reason: u16,
+}
+
+#[event]
+pub struct GameUnpaused {
+    pub by: Pubkey,
+    pub reason: u16,
+}
+
+#[event]
+pub struct MaxPayoutChanged {
+    pub by: Pubkey,
+    pub previous_bps: u16,
+    pub current_bps: u16,
+}
+
+#[event]
+pub struct BreakerChanged {
+    pub by: Pubkey,
+    pub threshold: u64,
+    pub window_hours: u8,
+}
+
+#[event]
+pub struct BreakerTripped {
+    pub net_outflow: u64,
+    pub threshold: u64,
+    pub window_hours: u8,
+}
+
+#[event]
+pub struct ResolverSetChanged {
+    pub by: Pubkey,
+    pub keys: Vec<Pubkey>,
+    pub quorum: u8,
+    pub threshold: u64,
+    pub epoch_budget: u64,
+}
+
+#[event]
+pub struct ResolverKeyChanged {
+    pub by: Pubkey,
+    pub key: Pubkey,
+    pub enabled: bool,
+    pub epoch_budget: u64,
+}
+
+#[event]
+pub struct ResolverSetProposed {
+    pub by: Pubkey,
+    pub keys: Vec<Pubkey>,
+    pub quorum: u8,
+    pub threshold: u64,
+    pub epoch_budget: u64,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ResolverSetCancelled {
+    pub by: Pubkey,
+}
+
+#[event]
+pub struct ResolverSetApplied {
+    pub keys: Vec<Pubkey>,
+    pub quorum: u8,
+    pub threshold: u64,
+}
+
+#[event]
+pub struct ResolverKeyChangeProposed {
+    pub by: Pubkey,
+    pub key: Pubkey,
+    pub enabled: bool,
+    pub epoch_budget: u64,
+    pub eta: i64,
+}
+
+#[event]
+pub struct GameConfigChanged {
+    pub by: Pubkey,
+    pub version: u32,
+    pub enabled: bool,
+    pub limits: GameLimits,
+}
+
+#[event]
+pub struct RandomnessChanged {
+    pub by: Pubkey,
+    pub version: u32,
+    pub randomness: RandomnessConfig,
+}
+
 #[program]
 pub mod anchor_dice {
     use super::*;
 
+    /// `admin_pubkey` becomes the resolver, which only posts seed commitments (resolves are
+    /// signed by the resolver set); the signer holds every other role until rotated.
     pub fn init_admin(ctx: Context<InitAdmin>, admin_pubkey: [u8; 32]) -> Result<()> {
-        ctx.accounts.admin_config.admin_pubkey = admin_pubkey;
+        require!(
+            deploy::may_initialize(ctx.program_id, &ctx.accounts.authority.key(), ctx.accounts.program_data.as_deref()),
+            DiceError::NotDeployer
+        );
+        let roles = Roles::new(Pubkey::new_from_array(admin_pubkey), ctx.accounts.authority.key());
+        ctx.accounts.admin_config.roles = roles;
+        for (role, key) in roles.keys.iter().enumerate() {
+            emit!(RoleChanged { role: role as u8, previous: Pubkey::default(), current: *key });
+        }
+        Ok(())
+    }
+
+    /// Super-admin queues `new_key` for `role` (see `casino_common::roles`); it can be
+    /// applied once `roles::ROLE_TIMELOCK_SECS` have passed.
+    pub fn propose_role(ctx: Context<ManageRole>, role: u8, new_key: Pubkey) -> Result<()> {
+        let roles = &mut ctx.accounts.admin_config.roles;
+        let eta = admin::propose_role(roles, &ctx.accounts.authority.key(), role, new_key)?;
+        emit!(RoleChangeProposed { role, current: roles.key(role), proposed: new_key, eta });
+        Ok(())
+    }
+
+    /// Super-admin drops a pending role change before it is applied.
+    pub fn cancel_role(ctx: Context<ManageRole>, role: u8) -> Result<()> {
+        let roles = &mut ctx.accounts.admin_config.roles;
+        let proposed = admin::cancel_role(roles, &ctx.accounts.authority.key(), role)?;
+        emit!(RoleChangeCancelled { role, proposed });
+        Ok(())
+    }
+
+    /// Permissionless: installs a proposed key once its timelock has elapsed.
+    pub fn apply_role(ctx: Context<ApplyRole>, role: u8) -> Result<()> {
+        let (previous, current) = admin::apply_role(&mut ctx.accounts.admin_config.roles, role)?;
+        emit!(RoleChanged { role, previous, current });
+        Ok(())
+    }
+
+    /// Creates the (unpaused) GameState PDA that locks and resolves read.
+    pub fn init_game_state(ctx: Context<InitGameState>) -> Result<()> {
+        admin::require_pauser(&ctx.accounts.admin_config.roles, &ctx.accounts.authority.key())?;
+        let gs = &mut ctx.accounts.game_state;
+        gs.house = House::new(DEFAULT_OUTFLOW_THRESHOLD, DEFAULT_OUTFLOW_WINDOW_HOURS);
+        gs.bump = ctx.bumps.game_state;
+        Ok(())
+    }
+
+    /// Halts new bets, and resolves too when `halt_resolve`; a resolve halt already in place
+    /// stays until `unpause`. `reason` is an incident code echoed in the event. Expired-bet
+    /// refunds stay open.
+    pub fn pause(ctx: Context<SetPause>, halt_resolve: bool, reason: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let house = &mut ctx.accounts.game_state.house;
+        admin::pause(&ctx.accounts.admin_config.roles, &by, house, pause::GLOBAL, halt_resolve)?;
+        emit!(GamePaused { by, halt_resolve, reason });
+        Ok(())
+    }
+
+    pub fn unpause(ctx: Context<SetPause>, reason: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::unpause(&ctx.accounts.admin_config.roles, &by, &mut ctx.accounts.game_state.house, pause::GLOBAL)?;
+        emit!(GameUnpaused { by, reason });
+        Ok(())
+    }
+
+    /// Config manager sets the share of the vault (bps) one bet may stand to win; the maximum
+    /// bet for every setting follows from it.
+    pub fn set_max_payout_bps(ctx: Context<SetLimits>, max_payout_bps: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let house = &mut ctx.accounts.game_state.house;
+        let previous_bps = admin::set_max_payout_bps(&ctx.accounts.admin_config.roles, &by, house, max_payout_bps)?;
+        emit!(MaxPayoutChanged { by, previous_bps, current_bps: max_payout_bps });
+        Ok(())
+    }
+
+    /// Config manager sets the net outflow (payouts minus stakes, lamports; 0 disables) over the
+    /// last `window_hours` that trips the circuit breaker and pauses the game.
+    pub fn set_outflow_breaker(ctx: Context<SetLimits>, threshold: u64, window_hours: u8) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let house = &mut ctx.accounts.game_state.house;
+        admin::set_outflow_breaker(&ctx.accounts.admin_config.roles, &by, house, threshold, window_hours)?;
+        emit!(BreakerChanged { by, threshold, window_hours });
+        Ok(())
+    }
+
+    /// Super-admin registers up to `MAX_COSIGNERS` resolver keys, one per backend signer
+    /// instance, each allowed to sign `epoch_budget` lamports of payouts per epoch (0 = no limit).
+    /// A payout above `threshold` lamports needs `quorum` of them to sign the resolve message.
+    pub fn init_resolver_set(
+        ctx: Context<InitResolverSet>,
+        keys: Vec<Pubkey>,
+        quorum: u8,
+        threshold: u64,
+        epoch_budget: u64,
+    ) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut ctx.accounts.resolver_set;
+        rs.cosigners = admin::new_resolver_set(&ctx.accounts.admin_config.roles, &by, &keys, quorum, threshold, epoch_budget)?;
+        rs.bump = ctx.bumps.resolver_set;
+        emit!(ResolverSetChanged { by, keys, quorum, threshold, epoch_budget });
+        Ok(())
+    }
+
+    /// Super-admin queues a replacement for the resolver keys, quorum, co-signing threshold and
+    /// budgets (every key enabled); `apply_resolver_set` installs it after ROLE_TIMELOCK_SECS.
+    pub fn propose_resolver_set(
+        ctx: Context<SetResolverSet>,
+        keys: Vec<Pubkey>,
+        quorum: u8,
+        threshold: u64,
+        epoch_budget: u64,
+    ) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let roles = &ctx.accounts.admin_config.roles;
+        let pending = &mut ctx.accounts.resolver_set.pending;
+        let eta = admin::propose_resolver_set(roles, &by, pending, &keys, quorum, threshold, epoch_budget)?;
+        emit!(ResolverSetProposed { by, keys, quorum, threshold, epoch_budget, eta });
+        Ok(())
+    }
+
+    /// Super-admin drops the pending resolver-set change (a proposed set or queued key changes).
+    pub fn cancel_resolver_set(ctx: Context<SetResolverSet>) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::cancel_resolver_set(&ctx.accounts.admin_config.roles, &by, &mut ctx.accounts.resolver_set.pending)?;
+        emit!(ResolverSetCancelled { by });
+        Ok(())
+    }
+
+    /// Permissionless: installs the pending resolver set once its timelock has elapsed.
+    pub fn apply_resolver_set(ctx: Context<ApplyResolverSet>) -> Result<()> {
+        let rs = &mut *ctx.accounts.resolver_set;
+        admin::apply_resolver_set(&mut rs.cosigners, &mut rs.pending)?;
+        let cosigners = &rs.cosigners;
+        let keys = cosigners.keys[..cosigners.count as usize].iter().map(|k| k.key).collect();
+        emit!(ResolverSetApplied { keys, quorum: cosigners.quorum, threshold: cosigners.threshold });
+        Ok(())
+    }
+
+    /// Super-admin sets one resolver key's enabled flag and per-epoch budget. Disabling it or
+    /// lowering the budget applies at once; enabling it or raising the budget is queued behind
+    /// the timelock for `apply_resolver_set`.
+    pub fn set_resolver_key(ctx: Context<SetResolverSet>, key: Pubkey, enabled: bool, epoch_budget: u64) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut *ctx.accounts.resolver_set;
+        let roles = &ctx.accounts.admin_config.roles;
+        match admin::set_resolver_key(roles, &by, &mut rs.cosigners, &mut rs.pending, &key, enabled, epoch_budget)? {
+            None => emit!(ResolverKeyChanged { by, key, enabled, epoch_budget }),
+            Some(eta) => emit!(ResolverKeyChangeProposed { by, key, enabled, epoch_budget, eta }),
+        }
+        Ok(())
+    }
+
+    /// Pauser or super-admin disables one resolver key (e.g. a compromised signer instance),
+    /// in the live set and in any pending one.
+    pub fn disable_resolver_key(ctx: Context<SetResolverSet>, key: Pubkey) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut *ctx.accounts.resolver_set;
+        let roles = &ctx.accounts.admin_config.roles;
+        let epoch_budget = admin::disable_resolver_key(roles, &by, &mut rs.cosigners, &mut rs.pending, &key)?;
+        emit!(ResolverKeyChanged { by, key, enabled: false, epoch_budget });
+        Ok(())
+    }
+
+    /// Config manager creates the game config (bet limits, house edge, enabled flag); locks
+    /// fail until it exists.
+    pub fn init_game_config(ctx: Context<InitGameConfig>, enabled: bool, limits: GameLimits) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        require!(limits_supported(&limits), DiceError::BadParams);
+        let cfg = &mut ctx.accounts.game_config;
+        cfg.enabled = enabled;
+        cfg.version = 1;
+        cfg.limits = limits;
+        cfg.randomness = RandomnessConfig::default();
+        cfg.bump = ctx.bumps.game_config;
+        emit!(GameConfigChanged { by, version: cfg.version, enabled, limits });
+        Ok(())
+    }
+
+    /// Config manager replaces the limits and enabled flag and bumps `version`. Open bets keep
+    /// the exposure recorded at lock.
+    pub fn update_game_config(ctx: Context<UpdateGameConfig>, enabled: bool, limits: GameLimits) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        require!(limits_supported(&limits), DiceError::BadParams);
+        let cfg = &mut ctx.accounts.game_config;
+        cfg.enabled = enabled;
+        cfg.limits = limits;
+        cfg.version = cfg.version.saturating_add(1);
+        emit!(GameConfigChanged { by, version: cfg.version, enabled, limits });
+        Ok(())
+    }
+
+    /// Super-admin picks the randomness source: backend commit/reveal, or an oracle program
+    /// on the build's allow-list (randomness::ORACLE_PROGRAM_IDS) whose requests locks create and
+    /// resolves read. Bets already locked keep the source they were locked with.
+    pub fn set_randomness(ctx: Context<UpdateGameConfig>, randomness: RandomnessConfig) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::SUPER_ADMIN, &by)?;
+        require!(randomness.is_valid(), DiceError::BadParams);
+        let cfg = &mut ctx.accounts.game_config;
+        cfg.randomness = randomness;
+        cfg.version = cfg.version.saturating_add(1);
+        emit!(RandomnessChanged { by, version: cfg.version, randomness });
+        Ok(())
+    }
+
+    /// Read-only (simulate): bankroll limits and the largest stake for the given setting.
+    pub fn bet_limits(ctx: Context<QueryLimits>, bet_type: u8, target: u8) -> Result<BetLimits> {
+        let cfg = &ctx.accounts.game_config.limits;
+        require!(bet_type <= 1 && cfg.param_ok(target as u32), DiceError::BadParams);
+        ctx.accounts.game_state.house.limits_for(&ctx.accounts.vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, cfg.max_bet, |s| exposure::dice(s, bet_type, target))
+    }
+
+    /// Resolver posts `sha256(server_seed)` for the player's next bet (overwrites an unused one).
+    pub fn commit_seed(ctx: Context<CommitSeed>, commitment: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.resolver.key().to_bytes() == ctx.accounts.admin_config.roles.resolver_key(),
+            DiceError::Unauthorized
+        );
+        require!(commitment != fair::EMPTY_COMMITMENT, DiceError::NoSeedCommit);
+
+        let sc = &mut ctx.accounts.seed_commit;
+        sc.player = ctx.accounts.player.key();
+        sc.commitment = commitment;
+        sc.bump = ctx.bumps.seed_commit;
         Ok(())
     }
 
     pub fn init_vault(ctx: Context<InitVault>) -> Result<()> {
+        require!(
+            deploy::may_initialize(ctx.program_id, &ctx.accounts.payer.key(), ctx.accounts.program_data.as_deref()),
+            DiceError::NotDeployer
+        );
         // Create the vault PDA as a system account with minimal lamports
         let rent = Rent::get()?.minimum_balance(0);
         let lamports = rent.max(1); // at least 1 lamport
@@ -209,12 +870,40 @@ pub mod anchor_dice {
 
     /// Step 1: Player deposits bet into vault and opens a PendingBet
     pub fn place_bet_lock(ctx: Context<PlaceBetLock>, args: PlaceBetLockArgs) -> Result<()> {
+        require!(!ctx.accounts.game_state.house.flags.lock_paused(pause::SOLE_GAME), DiceError::Paused);
+        let limits = lock_limits(&ctx.accounts.game_config)?;
+        require!(limits.bet_ok(args.bet_amount), DiceError::BadParams);
+        require!(limits.param_ok(args.target as u32), DiceError::BadParams);
+        require!(args.bet_type <= 1, DiceError::BadParams);
+
+        // Deadline must lie in the future and within the max TTL
+        let clock = Clock::get()?;
         require!(
-            args.bet_amount >= MIN_BET_LAMPORTS && args.bet_amount <= MAX_BET_LAMPORTS,
-            DiceError::BadParams
+            expiry::lock_window_ok(&clock, args.expiry_unix, MAX_TTL_SECS, args.expiry_slot, MAX_TTL_SLOTS),
+            DiceError::BadExpiry
         );
-        require!(args.target >= 2 && args.target <= 98, DiceError::BadParams);
-        require!(args.bet_type <= 1, DiceError::BadParams);
+
+        // Nonces only move forward (a closed pending PDA must never be re-opened)
+        let pn = &mut ctx.accounts.player_nonce;
+        require!(args.nonce > pn.last_nonce, DiceError::StaleNonce);
+        pn.player = ctx.accounts.player.key();
+        pn.last_nonce = args.nonce;
+        pn.bump = ctx.bumps.player_nonce;
+
+        // Pick the randomness source (oracle request or resolver's seed commitment)
+        // and capture lock-time entropy
+        let pending_key = ctx.accounts.pending_bet.key();
+        let (server_commit, oracle_request) = randomness::lock(
+            &ctx.accounts.game_config.randomness,
+            ctx.accounts.oracle_program.as_deref(),
+            ctx.accounts.oracle_request.as_deref(),
+            ctx.accounts.seed_commit.as_mut().map(|sc| &mut sc.commitment),
+            &ctx.accounts.player.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &pending_key,
+        )?;
+        let slot_hash = fair::latest_slot_hash(&ctx.accounts.slot_hashes.to_account_info())
+            .ok_or(DiceError::BadParams)?;
 
         // Transfer player → vault
         let collect_ix = system_instruction::transfer(
@@ -231,6 +920,10 @@ pub mod anchor_dice {
             ],
         )?;
 
+        // Reserve the worst-case payout against the vault
+        let reserved = exposure::dice(args.bet_amount, args.bet_type, args.target);
+        ctx.accounts.game_state.house.reserve(&ctx.accounts.vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, reserved)?;
+
         // Record pending bet
         let pb = &mut ctx.accounts.pending_bet;
         pb.player = ctx.accounts.player.key();
@@ -239,6 +932,13 @@ pub mod anchor_dice {
         pb.target = args.target;
         pb.nonce = args.nonce;
         pb.expiry_unix = args.expiry_unix;
+        pb.expiry_slot = args.expiry_slot;
+        pb.server_commit = server_commit;
+        pb.client_seed = args.client_seed;
+        pb.slot_hash = slot_hash;
+        pb.oracle_request = oracle_request;
+        pb.exposure = reserved;
+        pb.house_edge_bps = limits.house_edge_bps;
         pb.settled = false;
 
         emit!(BetLocked {
@@ -254,48 +954,60 @@ pub mod anchor_dice {
 
     /// Step 2: Backend signs result. Program verifies pre-instruction + rails, then pays if win
     pub fn resolve_bet(ctx: Context<ResolveBet>, args: ResolveBetArgs) -> Result<()> {
+        require!(!ctx.accounts.game_state.house.flags.resolve_paused(pause::SOLE_GAME), DiceError::Paused);
+        // take immutable data needed for the canonical message before the mutable borrow
+        let pending_key = ctx.accounts.pending_bet.key();
+        let vault_key = ctx.accounts.vault.key();
+        let player_key = ctx.accounts.player.key();
+
         let pb = &mut ctx.accounts.pending_bet;
         require!(!pb.settled, DiceError::BadBet);
 
         // Expiry
         let clock = Clock::get()?;
-        require!(clock.unix_timestamp <= pb.expiry_unix, DiceError::Expired);
-
-        // --- Robust ed25519 presence check (index hint + fallback scan) ---
-        let sys_ix_ai = &ctx.accounts.sysvar_instructions.to_account_info();
-
-        let hinted_ok = load_instruction_at_checked(args.ed25519_instr_index as usize, sys_ix_ai)
-            .map(|ix| ix.program_id == ed25519_program::id())
-            .unwrap_or(false);
-
-        let mut found = hinted_ok;
-        if !found {
-            let cur_idx = load_current_index_checked(sys_ix_ai)?; // index of THIS instruction
-            for i in 0..cur_idx {
-                if let Ok(ix) = load_instruction_at_checked(i as usize, sys_ix_ai) {
-                    if ix.program_id == ed25519_program::id() {
-                        found = true;
-                        break;
-                    }
-                }
-            }
-        }
-        require!(found, DiceError::InvalidEd25519);
+        require!(!expiry::is_expired(&clock, pb.expiry_unix, pb.expiry_slot), DiceError::Expired);
+
+        // Provably fair roll from the revealed server seed, client seed and lock-time slot hash
+        let seed = randomness::resolve_seed(
+            &pb.oracle_request,
+            ctx.accounts.oracle_request.as_deref(),
+            &pb.server_commit,
+            &args.server_seed,
+        )?;
+        let outcome = fair::outcome_hash(&seed, &pb.client_seed, &pb.slot_hash, pb.nonce);
+        let roll = fair::dice_roll(&outcome);
+
+        // --- ed25519 pre-instruction must hold registered resolver signature(s) over the canonical message ---
+        // game body: amount | bet_type | target | roll (see casino_common::message)
+        let expected = message::resolve_message(
+            DOMAIN_TAG,
+            ctx.program_id,
+            &vault_key,
+            &player_key,
+            &pending_key,
+            &[&pb.amount.to_le_bytes(), &[pb.bet_type, pb.target, roll]],
+            args.payout,
+            pb.nonce,
+            pb.expiry_unix,
+        );
+        cosign::require_signers(
+            &ctx.accounts.sysvar_instructions.to_account_info(),
+            &mut ctx.accounts.resolver_set.cosigners,
+            &expected,
+            args.payout,
+            Clock::get()?.epoch,
+        )?;
 
-        // Outcome + payout rails
-        require!(args.roll >= 1 && args.roll <= 100, DiceError::BadParams);
+        // Outcome + payout rails: the signed payout must be the one the roll and edge give
         let win = match pb.bet_type {
-            0 => args.roll < pb.target, // under
-            _ => args.roll > pb.target, // over
+            0 => roll < pb.target, // under
+            _ => roll > pb.target, // over
         };
-        if win {
-            require!(args.payout > 0 && args.payout <= MAX_PAYOUT_LAMPORTS, DiceError::BadPayout);
-        } else {
-            require!(args.payout == 0, DiceError::BadPayout);
-        }
+        let due = payout::dice(pb.amount, pb.bet_type, pb.target, roll, pb.house_edge_bps);
+        require!(args.payout == due && due <= pb.exposure, DiceError::BadPayout);
 
         // Pay winnings from vault → player
-        if win && args.payout > 0 {
+        if args.payout > 0 {
             let payout_ix = system_instruction::transfer(
                 &ctx.accounts.vault.key(),
                 &ctx.accounts.player.key(),
@@ -315,18 +1027,80 @@ pub mod anchor_dice {
         }
 
         // Mark settled (account closes to player at end of ix due to `close = player`)
+        record_outflow(&mut ctx.accounts.game_state, pb.amount, args.payout)?;
+        ctx.accounts.game_state.house.release(pb.exposure);
         pb.settled = true;
 
         emit!(BetResolved {
             player: pb.player,
             win,
-            roll: args.roll,
+            roll,
             payout: args.payout,
             nonce: pb.nonce,
         });
 
         Ok(())
     }
+
+    /// Permissionless: once past expiry + grace, return the stake to the player and close the bet
+    pub fn refund_expired(ctx: Context<RefundExpired>) -> Result<()> {
+        let pb = &ctx.accounts.pending_bet;
+        require!(!pb.settled, DiceError::BadBet);
+        let clock = Clock::get()?;
+        require!(
+            expiry::is_refundable(&clock, pb.expiry_unix, pb.expiry_slot, REFUND_GRACE_SECS, REFUND_GRACE_SLOTS),
+            DiceError::NotRefundable
+        );
+        let amount = pb.amount;
+
+        // Return principal vault → player
+        let refund_ix = system_instruction::transfer(
+            &ctx.accounts.vault.key(),
+            &ctx.accounts.player.key(),
+            amount,
+        );
+        let bump = ctx.bumps.vault;
+        invoke_signed(
+            &refund_ix,
+            &[
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.player.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[&[b"vault", &[bump]]],
+        )?;
+
+        // Mark settled (account closes to player at end of ix due to `close = player`)
+        let pb = &mut ctx.accounts.pending_bet;
+        ctx.accounts.game_state.house.release(pb.exposure);
+        pb.settled = true;
+        emit!(BetRefunded { player: pb.player, amount, nonce: pb.nonce });
+        Ok(())
+    }
+}
+
+/// Limits a new bet must meet; fails while the game is disabled.
+fn lock_limits(cfg: &GameConfig) -> Result<GameLimits> {
+    require!(cfg.enabled, DiceError::GameDisabled);
+    Ok(cfg.limits)
+}
+
+/// The config's target range must stay within 2..=98, which the payout maths supports.
+fn limits_supported(limits: &GameLimits) -> bool {
+    limits.is_valid() && limits.params_within(2, 98)
+}
+
+/// Feed a resolved bet's stake and payout into the outflow breaker. If it trips, locks and
+/// resolves are paused (this resolve still settles) until a pauser unpauses.
+fn record_outflow(game_state: &mut GameState, staked: u64, paid: u64) -> Result<()> {
+    if let Some(net_outflow) = game_state.house.record_outflow(staked, paid)? {
+        emit!(BreakerTripped {
+            net_outflow,
+            threshold: game_state.house.breaker.threshold,
+            window_hours: game_state.house.breaker.window_hours,
+        });
+    }
+    Ok(())
 }
 
 // user vault //
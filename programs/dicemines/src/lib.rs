@@ -2,50 +2,108 @@ use anchor_lang::system_program::ID as OtherID;
 use anchor_lang::prelude::*;
 use anchor_lang::system_program::System;
 use anchor_lang::solana_program::{
-    ed25519_program,
     program::{invoke, invoke_signed},
     system_instruction,
-    sysvar::instructions::{
-        load_current_index_checked,
-        load_instruction_at_checked,
-        ID as SYSVAR_INSTRUCTIONS_ID,
-    },
+    sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID,
+    sysvar::slot_hashes,
 };
+use casino_common::config::GameLimits;
+use casino_common::cosign::{self, Cosigners, PendingCosigners};
+use casino_common::deploy;
+use casino_common::house::{BetLimits, House};
+use casino_common::randomness::RandomnessConfig;
+use casino_common::roles::{self, Roles};
+use casino_common::{admin, ed25519, expiry, exposure, fair, message, pause, payout, randomness};
 
 declare_id!("2XSiZfPQDAHv6XWTiDxunPnDaqoMDNAxn3FmY4dYQAeT"); // ← REPLACE after deploy
 
 // ---- constants ----
-const MAX_PAYOUT_LAMPORTS: u64 = 50_000_000_000; // 0.05 SOL (tune)
-const MIN_BET_LAMPORTS: u64  = 50_000;           // 0.00005 SOL
-const MAX_BET_LAMPORTS: u64  = 5_000_000_000;    // 5 SOL
-const FEE_REIMBURSE_LAMPORTS: u64 = 1_400_000;   // user_vault → server fee payer (set 0 to disable)
+const DEFAULT_FEE_REIMBURSE_LAMPORTS: u64 = 1_400_000; // user_vault → server fee payer per lock, until reconfigured
+const MAX_FEE_REIMBURSE_LAMPORTS: u64 = 5_000_000;  // most a lock may charge for fees (0.005 SOL)
+const HOUSE_RESERVE_LAMPORTS: u64 = 100_000_000; // bankroll never promised to open bets (0.1 SOL)
+const DEFAULT_OUTFLOW_THRESHOLD: u64 = 50_000_000_000; // net house outflow that trips the breaker (50 SOL)
+const DEFAULT_OUTFLOW_WINDOW_HOURS: u8 = 1;      // hours of outflow the breaker sums
+const REFUND_GRACE_SECS: i64 = 600;              // after expiry_unix, before anyone may refund a pending bet
+const REFUND_GRACE_SLOTS: u64 = 1_500;           // same grace past an optional expiry_slot (~10 min)
+// Longest lock → expiry window per game
+const DICE_MAX_TTL_SECS: i64   = 300;
+const DICE_MAX_TTL_SLOTS: u64  = 750;
+const MINES_MAX_TTL_SECS: i64  = 3_600;
+const MINES_MAX_TTL_SLOTS: u64 = 9_000;
+
+// Game ids (pause switches)
+const GAME_DICE: u8  = 0;
+const GAME_MINES: u8 = 1;
+const ALL_GAMES_MASK: u8 = (1 << GAME_DICE) | (1 << GAME_MINES);
+const GAME_COUNT: usize = GAME_MINES as usize + 1;
+
+// Canonical domain tags for off-chain signing (one per game)
+const DICE_DOMAIN_TAG: &[u8]  = b"CASINO_DICE_V1";
+const MINES_DOMAIN_TAG: &[u8] = b"CASINO_MINES_V1";
+const INTENT_DOMAIN_TAG: &[u8] = b"CASINO_INTENT_V1"; // player-signed lock intent
 
 #[error_code]
 pub enum CasinoErr {
-    #[msg("Invalid ed25519 pre-instruction")] InvalidEd25519,
     #[msg("Expired signature/nonce")]        Expired,
     #[msg("Bad params")]                     BadParams,
     #[msg("Vault mismatch")]                 VaultMismatch,
     #[msg("Insufficient vault balance")]     InsufficientVault,
+    #[msg("Debit would leave the vault below its rent-exempt minimum")] RentReserve,
     #[msg("Payout sanity check failed")]     BadPayout,
     #[msg("Already settled or not found")]   BadPending,
+    #[msg("Signer not authorized")]          Unauthorized,
+    #[msg("Bet not yet refundable")]         NotRefundable,
+    #[msg("Expiry outside the allowed window")] BadExpiry,
+    #[msg("Nonce must exceed the last one used")] StaleNonce,
+    #[msg("Only the upgrade authority or deployer may initialize")] NotDeployer,
+    #[msg("Game is paused")]                 Paused,
+    #[msg("Game is disabled")]               GameDisabled,
+    #[msg("Player did not authorize this bet")] NoConsent,
 }
 
 // ---- accounts ----
+/// Role keys (resolver, super-admin, treasurer, pauser, config-manager) with timelocked rotation.
+#[account]
+pub struct AdminConfig { pub roles: Roles }
+impl AdminConfig { pub const LEN: usize = 8 + Roles::SIZE; }
+
+/// Incident pause switches, the worst-case payout still owed on open bets, the bankroll share
+/// one bet may win and the net-outflow breaker that pauses every game (see casino_common::house),
+/// plus each game's lock fee reimbursement; read by every lock and resolve.
+#[account]
+pub struct GameState {
+    pub house: House,
+    pub bump: u8,
+    pub fee_reimburse: [u64; GAME_COUNT], // lamports a lock moves from the user vault to the fee payer, by game id
+}
+impl GameState { pub const LEN: usize = 8 + House::SIZE + 1 + 8 * GAME_COUNT; }
+
+/// One game's bet limits, house edge, on/off switch (see casino_common::config) and randomness
+/// source (see casino_common::randomness) at `[b"game_config", &[game]]`; `version` counts
+/// updates. Read by that game's locks.
+#[account]
+pub struct GameConfig { pub game: u8, pub enabled: bool, pub version: u32, pub limits: GameLimits, pub randomness: RandomnessConfig, pub bump: u8 }
+impl GameConfig { pub const LEN: usize = 8 + 1 + 1 + 4 + GameLimits::SIZE + RandomnessConfig::SIZE + 1; }
+
+/// Resolver key registry, one key per backend signer instance, each with an enabled flag and a
+/// per-epoch payout budget; a payout above the threshold needs K-of-N co-signatures, smaller
+/// payouts any one usable key (see casino_common::cosign).
 #[account]
-pub struct AdminConfig { pub admin_pubkey: [u8; 32] }
+pub struct ResolverSet { pub cosigners: Cosigners, pub bump: u8, pub pending: PendingCosigners }
+impl ResolverSet { pub const LEN: usize = 8 + Cosigners::SIZE + 1 + PendingCosigners::SIZE; }
 
 #[account]
 pub struct UserVault {
     pub owner: Pubkey,
     pub bump:  u8,
-    pub _r1:   [u8; 7],
-    pub _r2:   [u8; 32],
+    pub fee_waived: bool,      // VIP: locks skip the fee reimbursement
+    pub _r1:   [u8; 6],
+    pub seed_commit: [u8; 32], // sha256(server_seed) for the next bet; zeroed when a lock consumes it
     pub _r3:   i64,
-    pub _r4:   u64,
+    pub last_nonce: u64,       // highest bet nonce locked from this vault (any game)
     pub _r5:   u64,
 }
-impl UserVault { pub const LEN: usize = 8 + 32 + 1 + 7 + 32 + 8 + 8 + 8; }
+impl UserVault { pub const LEN: usize = 8 + 32 + 1 + 1 + 6 + 32 + 8 + 8 + 8; }
 
 // dice pending
 #[account]
@@ -56,9 +114,17 @@ pub struct PendingBet {
     pub target:  u8,  // 2..98
     pub nonce:   u64,
     pub expiry_unix: i64,
+    pub server_commit: [u8; 32], // seed commitment consumed at lock, opened at resolve
+    pub client_seed: [u8; 32],   // player-chosen entropy
+    pub slot_hash: [u8; 32],     // latest SlotHashes entry at lock time
+    pub oracle_request: Pubkey,  // oracle-mode request account; randomness::NO_ORACLE otherwise
+    pub expiry_slot: u64,        // optional slot deadline; expiry::NO_SLOT_DEADLINE if unused
+    pub exposure: u64,           // worst-case payout reserved at lock; released on resolve/refund
+    pub rent_payer: Pubkey,      // fee_payer that funded this account at lock; its rent goes back there
+    pub house_edge_bps: u16,     // game config's edge at lock; prices the resolve payout
     pub settled: bool,
 }
-impl PendingBet { pub const LEN: usize = 8 + 32 + 8 + 1 + 1 + 8 + 8 + 1; }
+impl PendingBet { pub const LEN: usize = 8 + 32 + 8 + 1 + 1 + 8 + 8 + 32 + 32 + 32 + 32 + 8 + 8 + 32 + 2 + 1; }
 
 // mines pending
 #[account]
@@ -70,49 +136,213 @@ pub struct PendingRound {
     pub mines: u8,
     pub nonce: u64,
     pub expiry_unix: i64,
+    pub server_commit: [u8; 32], // seed commitment consumed at lock, opened at resolve
+    pub client_seed: [u8; 32],   // player-chosen entropy
+    pub slot_hash: [u8; 32],     // latest SlotHashes entry at lock time
+    pub oracle_request: Pubkey,  // oracle-mode request account; randomness::NO_ORACLE otherwise
+    pub expiry_slot: u64,        // optional slot deadline; expiry::NO_SLOT_DEADLINE if unused
+    pub exposure: u64,           // worst-case payout reserved at lock; released on resolve/refund
+    pub rent_payer: Pubkey,      // fee_payer that funded this account at lock; its rent goes back there
+    pub house_edge_bps: u16,     // game config's edge at lock; prices the resolve payout
     pub settled: bool,
 }
-impl PendingRound { pub const LEN: usize = 8 + 32 + 8 + 1 + 1 + 1 + 8 + 8 + 1; }
+impl PendingRound { pub const LEN: usize = 8 + 32 + 8 + 1 + 1 + 1 + 8 + 8 + 32 + 32 + 32 + 32 + 8 + 8 + 32 + 2 + 1; }
 
 // ---- events ----
-#[event] pub struct DiceLocked   { pub player: Pubkey, pub amount: u64, pub bet_type: u8, pub target: u8, pub nonce: u64 }
+#[event] pub struct DiceLocked   { pub player: Pubkey, pub amount: u64, pub bet_type: u8, pub target: u8, pub nonce: u64, pub slot_hash: [u8; 32] }
 #[event] pub struct DiceResolved { pub player: Pubkey, pub win: bool,   pub roll: u8,    pub payout: u64, pub nonce: u64 }
+#[event] pub struct DiceRefunded { pub player: Pubkey, pub amount: u64, pub nonce: u64 }
+
+#[event] pub struct MinesLocked   { pub player: Pubkey, pub amount: u64, pub rows: u8, pub cols: u8, pub mines: u8, pub nonce: u64, pub slot_hash: [u8; 32] }
+#[event] pub struct MinesResolved { pub player: Pubkey, pub payout: u64, pub mines_layout: u64, pub nonce: u64 }
+#[event] pub struct MinesRefunded { pub player: Pubkey, pub amount: u64, pub nonce: u64 }
 
-#[event] pub struct MinesLocked   { pub player: Pubkey, pub amount: u64, pub rows: u8, pub cols: u8, pub mines: u8, pub nonce: u64 }
-#[event] pub struct MinesResolved { pub player: Pubkey, pub payout: u64, pub checksum: u8, pub nonce: u64 }
+#[event] pub struct SeedCommitted { pub player: Pubkey, pub commitment: [u8; 32] }
+
+#[event] pub struct RoleChangeProposed  { pub role: u8, pub current: Pubkey, pub proposed: Pubkey, pub eta: i64 }
+#[event] pub struct RoleChangeCancelled { pub role: u8, pub proposed: Pubkey }
+#[event] pub struct RoleChanged         { pub role: u8, pub previous: Pubkey, pub current: Pubkey }
+#[event] pub struct GamePaused          { pub by: Pubkey, pub games_mask: u8, pub halt_resolve: bool, pub reason: u16 }
+#[event] pub struct GameUnpaused        { pub by: Pubkey, pub games_mask: u8, pub reason: u16 }
+#[event] pub struct MaxPayoutChanged    { pub by: Pubkey, pub previous_bps: u16, pub current_bps: u16 }
+#[event] pub struct BreakerChanged      { pub by: Pubkey, pub threshold: u64, pub window_hours: u8 }
+#[event] pub struct BreakerTripped      { pub net_outflow: u64, pub threshold: u64, pub window_hours: u8 }
+#[event] pub struct FeeReimburseChanged { pub by: Pubkey, pub games_mask: u8, pub lamports: u64 }
+#[event] pub struct FeeWaiverChanged    { pub by: Pubkey, pub owner: Pubkey, pub waived: bool }
+#[event] pub struct GameConfigChanged   { pub by: Pubkey, pub game: u8, pub version: u32, pub enabled: bool, pub limits: GameLimits }
+#[event] pub struct RandomnessChanged   { pub by: Pubkey, pub game: u8, pub version: u32, pub randomness: RandomnessConfig }
+#[event] pub struct ResolverSetChanged  { pub by: Pubkey, pub keys: Vec<Pubkey>, pub quorum: u8, pub threshold: u64, pub epoch_budget: u64 }
+#[event] pub struct ResolverKeyChanged  { pub by: Pubkey, pub key: Pubkey, pub enabled: bool, pub epoch_budget: u64 }
+#[event] pub struct ResolverSetProposed { pub by: Pubkey, pub keys: Vec<Pubkey>, pub quorum: u8, pub threshold: u64, pub epoch_budget: u64, pub eta: i64 }
+#[event] pub struct ResolverSetCancelled { pub by: Pubkey }
+#[event] pub struct ResolverSetApplied  { pub keys: Vec<Pubkey>, pub quorum: u8, pub threshold: u64 }
+#[event] pub struct ResolverKeyChangeProposed { pub by: Pubkey, pub key: Pubkey, pub enabled: bool, pub epoch_budget: u64, pub eta: i64 }
 
 // ---- utils ----
+/// Lamports `ai` can give up while staying rent-exempt for its current data size.
+fn withdrawable(ai: &AccountInfo<'_>) -> Result<u64> {
+    let reserve = Rent::get()?.minimum_balance(ai.data_len());
+    Ok(ai.lamports().saturating_sub(reserve))
+}
+
+/// Every vault debit must leave the account at or above its rent-exempt minimum.
+fn require_debit(from: &AccountInfo<'_>, amount: u64) -> Result<()> {
+    require!(from.lamports() >= amount, CasinoErr::InsufficientVault);
+    require!(amount <= withdrawable(from)?, CasinoErr::RentReserve);
+    Ok(())
+}
+
 fn safe_move_lamports(from: &AccountInfo<'_>, to: &AccountInfo<'_>, amount: u64) -> Result<()> {
     require!(amount > 0, CasinoErr::BadParams);
+    require_debit(from, amount)?;
     let mut from_lamports = from.try_borrow_mut_lamports()?;
     let mut to_lamports   = to.try_borrow_mut_lamports()?;
-    require!(**from_lamports >= amount, CasinoErr::InsufficientVault);
     **from_lamports -= amount;
     **to_lamports   += amount;
     Ok(())
 }
 
-fn require_ed25519_present(sys_ix_ai: &AccountInfo<'_>, hinted_idx: u8) -> Result<()> {
-    let hinted_ok = load_instruction_at_checked(hinted_idx as usize, sys_ix_ai)
-        .map(|ix| ix.program_id == ed25519_program::id())
-        .unwrap_or(false);
-    if hinted_ok { return Ok(()); }
-    let cur_idx = load_current_index_checked(sys_ix_ai)?;
-    for i in 0..cur_idx {
-        if let Ok(ix) = load_instruction_at_checked(i as usize, sys_ix_ai) {
-            if ix.program_id == ed25519_program::id() { return Ok(()); }
+/// Pending PDAs are closed on resolve, so a vault's bet nonces must strictly increase
+/// or an old `(player, nonce)` (and any resolver signature over it) could be re-opened.
+fn advance_nonce(user_vault: &mut UserVault, nonce: u64) -> Result<()> {
+    require!(nonce > user_vault.last_nonce, CasinoErr::StaleNonce);
+    user_vault.last_nonce = nonce;
+    Ok(())
+}
+
+/// Fee reimbursement a lock of `game` moves from the user vault to the fee payer (none for
+/// fee-waived vaults).
+fn lock_fee(game_state: &GameState, user_vault: &UserVault, game: u8) -> u64 {
+    if user_vault.fee_waived { 0 } else { game_state.fee_reimburse[game as usize] }
+}
+
+/// Lock-time deadline check against the game's maximum TTL.
+fn require_lock_window(expiry_unix: i64, max_ttl_secs: i64, expiry_slot: u64, max_ttl_slots: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    require!(expiry::lock_window_ok(&clock, expiry_unix, max_ttl_secs, expiry_slot, max_ttl_slots), CasinoErr::BadExpiry);
+    Ok(())
+}
+
+/// Pending bets become refundable once a deadline is past by its grace period.
+fn require_refundable(settled: bool, expiry_unix: i64, expiry_slot: u64) -> Result<()> {
+    require!(!settled, CasinoErr::BadPending);
+    let clock = Clock::get()?;
+    require!(
+        expiry::is_refundable(&clock, expiry_unix, expiry_slot, REFUND_GRACE_SECS, REFUND_GRACE_SLOTS),
+        CasinoErr::NotRefundable
+    );
+    Ok(())
+}
+
+/// Limits a new bet must meet; fails while the game is disabled.
+fn lock_limits(cfg: &GameConfig) -> Result<GameLimits> {
+    require!(cfg.enabled, CasinoErr::GameDisabled);
+    Ok(cfg.limits)
+}
+
+/// Limits of a config passed to a view, checked to be `game`'s.
+fn view_limits(cfg: &GameConfig, game: u8) -> Result<GameLimits> {
+    require!(cfg.game == game, CasinoErr::BadParams);
+    Ok(cfg.limits)
+}
+
+/// A game's config must keep its parameter range within what the game's fairness and
+/// exposure maths support.
+fn limits_supported(game: u8, limits: &GameLimits) -> bool {
+    limits.is_valid()
+        && match game {
+            GAME_DICE => limits.params_within(2, 98),
+            GAME_MINES => limits.params_within(2, 8),
+            _ => false,
         }
+}
+
+/// Feed a resolved bet's stake and payout into the outflow breaker. If it trips, every game's
+/// locks and resolves are paused (this resolve still settles) until a pauser unpauses.
+fn record_outflow(game_state: &mut GameState, staked: u64, paid: u64) -> Result<()> {
+    if let Some(net_outflow) = game_state.house.record_outflow(staked, paid)? {
+        emit!(BreakerTripped {
+            net_outflow,
+            threshold: game_state.house.breaker.threshold,
+            window_hours: game_state.house.breaker.window_hours,
+        });
     }
-    err!(CasinoErr::InvalidEd25519)
+    Ok(())
+}
+
+/// Return an expired bet's principal from the house vault to the player's user_vault.
+fn refund_principal<'info>(house_vault: &SystemAccount<'info>, user_vault: &Account<'info, UserVault>, system_program: &Program<'info, System>, house_bump: u8, amount: u64) -> Result<()> {
+    let ix = system_instruction::transfer(&house_vault.key(), &user_vault.key(), amount);
+    invoke_signed(&ix, &[
+        house_vault.to_account_info(),
+        user_vault.to_account_info(),
+        system_program.to_account_info(),
+    ], &[&[b"vault", &[house_bump]]])?;
+    Ok(())
+}
+
+/// A bet drawn from a UserVault needs the player's consent: either the player signs the lock
+/// itself, or the ed25519 run directly before it holds the player's signature over the intent
+/// `CASINO_INTENT_V1 | program_id | player | game | params | client_seed | nonce | expiry`.
+#[allow(clippy::too_many_arguments)]
+fn require_player_consent(
+    sys_ix_ai: &AccountInfo<'_>,
+    player: &AccountInfo<'_>,
+    program_id: &Pubkey,
+    game: u8,
+    params: &[u8],
+    client_seed: &[u8; 32],
+    nonce: u64,
+    expiry_unix: i64,
+) -> Result<()> {
+    if player.is_signer { return Ok(()); }
+
+    let mut intent = Vec::with_capacity(INTENT_DOMAIN_TAG.len() + 32 * 3 + 1 + params.len() + 8 + 8);
+    intent.extend_from_slice(INTENT_DOMAIN_TAG);
+    intent.extend_from_slice(program_id.as_ref());
+    intent.extend_from_slice(player.key.as_ref());
+    intent.push(game);
+    intent.extend_from_slice(params);
+    intent.extend_from_slice(client_seed);
+    intent.extend_from_slice(&nonce.to_le_bytes());
+    intent.extend_from_slice(&expiry_unix.to_le_bytes());
+
+    ed25519::require_signed_by(sys_ix_ai, &player.key.to_bytes(), &intent)
+        .map_err(|_| error!(CasinoErr::NoConsent))
+}
+
+// ---- canonical bet params per game (shared by bet intents and resolve messages) ----
+fn dice_params(amount: u64, bet_type: u8, target: u8) -> Vec<u8> {
+    [&amount.to_le_bytes()[..], &[bet_type, target]].concat()
+}
+fn mines_params(amount: u64, rows: u8, cols: u8, mines: u8) -> Vec<u8> {
+    [&amount.to_le_bytes()[..], &[rows, cols, mines]].concat()
 }
 
 // ---- contexts ----
 #[derive(Accounts)]
 pub struct InitAdmin<'info> {
     #[account(mut, signer)] pub authority: SystemAccount<'info>,
-    #[account(init, payer=authority, space=8+32, seeds=[b"admin"], bump)]
+    #[account(init, payer=authority, space=AdminConfig::LEN, seeds=[b"admin"], bump)]
     pub admin_config: Account<'info, AdminConfig>,
     pub system_program: Program<'info, System>,
+    /// CHECK: this program's ProgramData (upgrade-authority check); optional when the deployer key signs
+    pub program_data: Option<UncheckedAccount<'info>>,
+}
+
+/// Super-admin proposes or cancels a role change
+#[derive(Accounts)]
+pub struct ManageRole<'info> {
+    #[account(signer)] pub authority: SystemAccount<'info>,
+    #[account(mut, seeds=[b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+}
+
+/// Anyone may apply a proposed role change once its timelock has elapsed
+#[derive(Accounts)]
+pub struct ApplyRole<'info> {
+    #[account(mut, seeds=[b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
 }
 
 #[derive(Accounts)]
@@ -122,6 +352,8 @@ pub struct InitHouseVault<'info> {
     /// CHECK: system-owned lamports PDA
     pub house_vault: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
+    /// CHECK: this program's ProgramData (upgrade-authority check); optional when the deployer key signs
+    pub program_data: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
@@ -148,10 +380,120 @@ pub struct WithdrawFromVault<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// Read-only: a user vault's withdrawable balance (simulate `vault_balance` to read it)
+#[derive(Accounts)]
+pub struct QueryUserVault<'info> {
+    #[account(seeds=[b"user_vault", user_vault.owner.as_ref()], bump=user_vault.bump)]
+    pub user_vault: Account<'info, UserVault>,
+}
+
+/// Pauser or super-admin creates the (unpaused) GameState
+#[derive(Accounts)]
+pub struct InitGameState<'info> {
+    #[account(mut, signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(init, payer=authority, space=GameState::LEN, seeds=[b"game_state"], bump)]
+    pub game_state: Account<'info, GameState>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Pauser or super-admin flips pause switches
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    #[account(signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+}
+
+/// Config manager sets bankroll limits
+#[derive(Accounts)]
+pub struct SetLimits<'info> {
+    #[account(signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+}
+
+/// Read-only: bankroll limits for a game setting (simulate the instruction to read them)
+#[derive(Accounts)]
+pub struct QueryLimits<'info> {
+    #[account(seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
+    #[account(seeds=[b"game_state"], bump=game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+    #[account(seeds=[b"game_config", game_config.game.to_le_bytes().as_ref()], bump=game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
+}
+
+/// Config manager creates one game's config (once per game)
+#[derive(Accounts)]
+#[instruction(game: u8)]
+pub struct InitGameConfig<'info> {
+    #[account(mut, signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(init, payer=authority, space=GameConfig::LEN, seeds=[b"game_config", game.to_le_bytes().as_ref()], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Config manager updates one game's config (super admin: its randomness source)
+#[derive(Accounts)]
+pub struct UpdateGameConfig<'info> {
+    #[account(signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(mut, seeds=[b"game_config", game_config.game.to_le_bytes().as_ref()], bump=game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
+}
+
+/// Config manager waives or restores a player's lock fee reimbursement
+#[derive(Accounts)]
+pub struct SetFeeWaiver<'info> {
+    #[account(signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(mut, seeds=[b"user_vault", user_vault.owner.as_ref()], bump=user_vault.bump)]
+    pub user_vault: Account<'info, UserVault>,
+}
+
+/// Super-admin creates the resolver key registry
+#[derive(Accounts)]
+pub struct InitResolverSet<'info> {
+    #[account(mut, signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(init, payer=authority, space=ResolverSet::LEN, seeds=[b"resolvers"], bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Super-admin replaces the resolver registry or changes one key; pauser may disable a key
+#[derive(Accounts)]
+pub struct SetResolverSet<'info> {
+    #[account(signer)] pub authority: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(mut, seeds=[b"resolvers"], bump=resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+}
+
+/// Anyone may apply a pending resolver-set change once its timelock has elapsed
+#[derive(Accounts)]
+pub struct ApplyResolverSet<'info> {
+    #[account(mut, seeds=[b"resolvers"], bump=resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+}
+
+#[derive(Accounts)]
+pub struct CommitSeed<'info> {
+    /// Resolver wallet (must hold admin_config's resolver role)
+    #[account(signer)] pub resolver: SystemAccount<'info>,
+    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
+    #[account(mut, seeds=[b"user_vault", user_vault.owner.as_ref()], bump=user_vault.bump)]
+    pub user_vault: Account<'info, UserVault>,
+}
+
 // dice
 #[derive(Accounts)]
 #[instruction(args: DiceLockArgs)]
 pub struct DiceLock<'info> {
+    /// Must sign, unless a player-signed bet intent precedes this instruction
     pub player: SystemAccount<'info>,
     #[account(mut, signer)] pub fee_payer: SystemAccount<'info>,
     #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
@@ -164,27 +506,63 @@ pub struct DiceLock<'info> {
     /// CHECK
     #[account(address = SYSVAR_INSTRUCTIONS_ID)]
     pub sysvar_instructions: UncheckedAccount<'info>,
+    /// CHECK: SlotHashes sysvar (entropy captured at lock)
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+    #[account(seeds=[b"game_config", GAME_DICE.to_le_bytes().as_ref()], bump=game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
+    /// CHECK: randomness oracle (oracle mode only); must be game_config.randomness.oracle_program
+    pub oracle_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: oracle request PDA for this bet, created by the oracle CPI
+    #[account(mut)]
+    pub oracle_request: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
 pub struct DiceResolve<'info> {
     #[account(mut)] pub player: SystemAccount<'info>,
     #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
-    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
     #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
     pub user_vault: Account<'info, UserVault>,
-    #[account(mut, close=user_vault, seeds=[b"bet", player.key().as_ref(), &pending_bet.nonce.to_le_bytes()], bump)]
+    #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"bet", player.key().as_ref(), &pending_bet.nonce.to_le_bytes()], bump)]
     pub pending_bet: Account<'info, PendingBet>,
     pub system_program: Program<'info, System>,
     /// CHECK
     #[account(address = SYSVAR_INSTRUCTIONS_ID)]
     pub sysvar_instructions: UncheckedAccount<'info>,
+    #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+    /// Funded the pending account at lock; gets its rent back when it closes
+    #[account(mut)] pub rent_payer: SystemAccount<'info>,
+    /// CHECK: fulfilled oracle request (oracle-mode bets only); must match the pending record
+    pub oracle_request: Option<UncheckedAccount<'info>>,
+    /// Resolver key registry (charged with this payout)
+    #[account(mut, seeds=[b"resolvers"], bump=resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+}
+
+#[derive(Accounts)]
+pub struct DiceRefund<'info> {
+    pub player: SystemAccount<'info>,
+    #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
+    #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
+    pub user_vault: Account<'info, UserVault>,
+    #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"bet", player.key().as_ref(), &pending_bet.nonce.to_le_bytes()], bump)]
+    pub pending_bet: Account<'info, PendingBet>,
+    pub system_program: Program<'info, System>,
+    #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+    /// Funded the pending account at lock; gets its rent back when it closes
+    #[account(mut)] pub rent_payer: SystemAccount<'info>,
 }
 
 // mines
 #[derive(Accounts)]
 #[instruction(args: MinesLockArgs)]
 pub struct MinesLock<'info> {
+    /// Must sign, unless a player-signed bet intent precedes this instruction
     pub player: SystemAccount<'info>,
     #[account(mut, signer)] pub fee_payer: SystemAccount<'info>,
     #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
@@ -197,21 +575,56 @@ pub struct MinesLock<'info> {
     /// CHECK
     #[account(address = SYSVAR_INSTRUCTIONS_ID)]
     pub sysvar_instructions: UncheckedAccount<'info>,
+    /// CHECK: SlotHashes sysvar (entropy captured at lock)
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+    #[account(seeds=[b"game_config", GAME_MINES.to_le_bytes().as_ref()], bump=game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
+    /// CHECK: randomness oracle (oracle mode only); must be game_config.randomness.oracle_program
+    pub oracle_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: oracle request PDA for this bet, created by the oracle CPI
+    #[account(mut)]
+    pub oracle_request: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
 pub struct MinesResolve<'info> {
     #[account(mut)] pub player: SystemAccount<'info>,
     #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
-    #[account(seeds=[b"admin"], bump)] pub admin_config: Account<'info, AdminConfig>,
     #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
     pub user_vault: Account<'info, UserVault>,
-    #[account(mut, close=user_vault, seeds=[b"round", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+    #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"round", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
     pub pending: Account<'info, PendingRound>,
     pub system_program: Program<'info, System>,
     /// CHECK
     #[account(address = SYSVAR_INSTRUCTIONS_ID)]
     pub sysvar_instructions: UncheckedAccount<'info>,
+    #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+    /// Funded the pending account at lock; gets its rent back when it closes
+    #[account(mut)] pub rent_payer: SystemAccount<'info>,
+    /// CHECK: fulfilled oracle request (oracle-mode bets only); must match the pending record
+    pub oracle_request: Option<UncheckedAccount<'info>>,
+    /// Resolver key registry (charged with this payout)
+    #[account(mut, seeds=[b"resolvers"], bump=resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+}
+
+#[derive(Accounts)]
+pub struct MinesRefund<'info> {
+    pub player: SystemAccount<'info>,
+    #[account(mut, seeds=[b"vault"], bump)] pub house_vault: SystemAccount<'info>,
+    #[account(mut, seeds=[b"user_vault", player.key().as_ref()], bump=user_vault.bump)]
+    pub user_vault: Account<'info, UserVault>,
+    #[account(mut, has_one=rent_payer, close=rent_payer, seeds=[b"round", player.key().as_ref(), &pending.nonce.to_le_bytes()], bump)]
+    pub pending: Account<'info, PendingRound>,
+    pub system_program: Program<'info, System>,
+    #[account(mut, seeds=[b"game_state"], bump=game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+    /// Funded the pending account at lock; gets its rent back when it closes
+    #[account(mut)] pub rent_payer: SystemAccount<'info>,
 }
 
 // ---- args ----
@@ -222,6 +635,14 @@ pub struct DepositArgs  { pub amount: u64 }
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct WithdrawArgs { pub amount: u64 }
 
+// user vault balance (returned by `vault_balance`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct VaultBalance {
+    pub balance: u64,
+    pub rent_reserve: u64, // rent-exempt minimum every debit leaves in place
+    pub withdrawable: u64, // balance less rent_reserve
+}
+
 // dice
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct DiceLockArgs {
@@ -230,13 +651,13 @@ pub struct DiceLockArgs {
     pub target:  u8,       // 2..98
     pub nonce: u64,
     pub expiry_unix: i64,
-    pub ed25519_instr_index: u8,
+    pub client_seed: [u8; 32],
+    pub expiry_slot: u64, // 0 = unix deadline only
 }
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct DiceResolveArgs {
-    pub roll: u8,
+    pub server_seed: [u8; 32], // opens pending_bet.server_commit (zeroes for oracle-mode bets)
     pub payout: u64,
-    pub ed25519_instr_index: u8,
 }
 
 // mines
@@ -246,13 +667,14 @@ pub struct MinesLockArgs {
     pub rows: u8, pub cols: u8, pub mines: u8,
     pub nonce: u64,
     pub expiry_unix: i64,
-    pub ed25519_instr_index: u8,
+    pub client_seed: [u8; 32],
+    pub expiry_slot: u64, // 0 = unix deadline only
 }
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct MinesResolveArgs {
-    pub checksum: u8,
+    pub server_seed: [u8; 32], // opens pending.server_commit (zeroes for oracle-mode bets)
+    pub picks: u64,            // tiles opened before cash-out, as a bitmask like the layout
     pub payout: u64,
-    pub ed25519_instr_index: u8,
 }
 
 // ---- program ----
@@ -260,11 +682,50 @@ pub struct MinesResolveArgs {
 pub mod casino {
     use super::*;
 
+    /// `admin_pubkey` becomes the resolver, which only posts seed commitments (resolves are
+    /// signed by the resolver set); the signer holds every other role until rotated.
     pub fn init_admin(ctx: Context<InitAdmin>, admin_pubkey: [u8; 32]) -> Result<()> {
-        ctx.accounts.admin_config.admin_pubkey = admin_pubkey; Ok(())
+        require!(
+            deploy::may_initialize(ctx.program_id, &ctx.accounts.authority.key(), ctx.accounts.program_data.as_deref()),
+            CasinoErr::NotDeployer
+        );
+        let roles = Roles::new(Pubkey::new_from_array(admin_pubkey), ctx.accounts.authority.key());
+        ctx.accounts.admin_config.roles = roles;
+        for (role, key) in roles.keys.iter().enumerate() {
+            emit!(RoleChanged { role: role as u8, previous: Pubkey::default(), current: *key });
+        }
+        Ok(())
+    }
+
+    /// Super-admin queues `new_key` for `role` (see `casino_common::roles`); it can be
+    /// applied once `roles::ROLE_TIMELOCK_SECS` have passed.
+    pub fn propose_role(ctx: Context<ManageRole>, role: u8, new_key: Pubkey) -> Result<()> {
+        let roles = &mut ctx.accounts.admin_config.roles;
+        let eta = admin::propose_role(roles, &ctx.accounts.authority.key(), role, new_key)?;
+        emit!(RoleChangeProposed { role, current: roles.key(role), proposed: new_key, eta });
+        Ok(())
+    }
+
+    /// Super-admin drops a pending role change before it is applied.
+    pub fn cancel_role(ctx: Context<ManageRole>, role: u8) -> Result<()> {
+        let roles = &mut ctx.accounts.admin_config.roles;
+        let proposed = admin::cancel_role(roles, &ctx.accounts.authority.key(), role)?;
+        emit!(RoleChangeCancelled { role, proposed });
+        Ok(())
+    }
+
+    /// Permissionless: installs a proposed key once its timelock has elapsed.
+    pub fn apply_role(ctx: Context<ApplyRole>, role: u8) -> Result<()> {
+        let (previous, current) = admin::apply_role(&mut ctx.accounts.admin_config.roles, role)?;
+        emit!(RoleChanged { role, previous, current });
+        Ok(())
     }
 
     pub fn init_house_vault(ctx: Context<InitHouseVault>) -> Result<()> {
+        require!(
+            deploy::may_initialize(ctx.program_id, &ctx.accounts.payer.key(), ctx.accounts.program_data.as_deref()),
+            CasinoErr::NotDeployer
+        );
         let rent = Rent::get()?.minimum_balance(0);
         let bump = ctx.bumps.house_vault;
         let ix = system_instruction::create_account(
@@ -319,28 +780,292 @@ pub mod casino {
         safe_move_lamports(&from, &to, args.amount)
     }
 
+    /// Withdraws everything above the user vault's rent-exempt minimum.
+    pub fn withdraw_all(ctx: Context<WithdrawFromVault>) -> Result<()> {
+        let from = ctx.accounts.user_vault.to_account_info();
+        let to   = ctx.accounts.player.to_account_info();
+        let amount = withdrawable(&from)?;
+        require!(amount > 0, CasinoErr::InsufficientVault);
+        safe_move_lamports(&from, &to, amount)
+    }
+
+    /// Read-only (simulate): the user vault's balance, its rent-exempt reserve and the part of
+    /// it that can be withdrawn or staked.
+    pub fn vault_balance(ctx: Context<QueryUserVault>) -> Result<VaultBalance> {
+        let ai = ctx.accounts.user_vault.to_account_info();
+        let withdrawable = withdrawable(&ai)?;
+        Ok(VaultBalance { balance: ai.lamports(), rent_reserve: ai.lamports() - withdrawable, withdrawable })
+    }
+
+    /// Creates the (unpaused) GameState PDA that every lock and resolve reads.
+    pub fn init_game_state(ctx: Context<InitGameState>) -> Result<()> {
+        admin::require_pauser(&ctx.accounts.admin_config.roles, &ctx.accounts.authority.key())?;
+        let gs = &mut ctx.accounts.game_state;
+        gs.house = House::new(DEFAULT_OUTFLOW_THRESHOLD, DEFAULT_OUTFLOW_WINDOW_HOURS);
+        gs.fee_reimburse = [DEFAULT_FEE_REIMBURSE_LAMPORTS; GAME_COUNT];
+        gs.bump = ctx.bumps.game_state;
+        Ok(())
+    }
+
+    /// Halts new bets for `games_mask` (`1 << GAME_*` bits, or `pause::GLOBAL` for all games),
+    /// and their resolves too when `halt_resolve`. `reason` is an incident code echoed in the
+    /// event. Withdrawals and expired-bet refunds stay open.
+    pub fn pause(ctx: Context<SetPause>, games_mask: u8, halt_resolve: bool, reason: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        require!(games_mask != 0 && games_mask & !(ALL_GAMES_MASK | pause::GLOBAL) == 0, CasinoErr::BadParams);
+        admin::pause(&ctx.accounts.admin_config.roles, &by, &mut ctx.accounts.game_state.house, games_mask, halt_resolve)?;
+        emit!(GamePaused { by, games_mask, halt_resolve, reason });
+        Ok(())
+    }
+
+    pub fn unpause(ctx: Context<SetPause>, games_mask: u8, reason: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        require!(games_mask != 0 && games_mask & !(ALL_GAMES_MASK | pause::GLOBAL) == 0, CasinoErr::BadParams);
+        admin::unpause(&ctx.accounts.admin_config.roles, &by, &mut ctx.accounts.game_state.house, games_mask)?;
+        emit!(GameUnpaused { by, games_mask, reason });
+        Ok(())
+    }
+
+    /// Config manager sets the share of the house vault (bps) one bet may stand to win; every
+    /// game's maximum bet follows from it.
+    pub fn set_max_payout_bps(ctx: Context<SetLimits>, max_payout_bps: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let house = &mut ctx.accounts.game_state.house;
+        let previous_bps = admin::set_max_payout_bps(&ctx.accounts.admin_config.roles, &by, house, max_payout_bps)?;
+        emit!(MaxPayoutChanged { by, previous_bps, current_bps: max_payout_bps });
+        Ok(())
+    }
+
+    /// Config manager sets the net outflow (payouts minus stakes, lamports; 0 disables) over the
+    /// last `window_hours` that trips the circuit breaker and pauses every game.
+    pub fn set_outflow_breaker(ctx: Context<SetLimits>, threshold: u64, window_hours: u8) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let house = &mut ctx.accounts.game_state.house;
+        admin::set_outflow_breaker(&ctx.accounts.admin_config.roles, &by, house, threshold, window_hours)?;
+        emit!(BreakerChanged { by, threshold, window_hours });
+        Ok(())
+    }
+
+    /// Config manager sets the fee reimbursement (lamports, at most MAX_FEE_REIMBURSE_LAMPORTS)
+    /// that a lock of each game in `games_mask` moves from the user vault to the fee payer.
+    pub fn set_fee_reimburse(ctx: Context<SetLimits>, games_mask: u8, lamports: u64) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        require!(games_mask != 0 && games_mask & !ALL_GAMES_MASK == 0, CasinoErr::BadParams);
+        require!(lamports <= MAX_FEE_REIMBURSE_LAMPORTS, CasinoErr::BadParams);
+        let gs = &mut ctx.accounts.game_state;
+        for (game, fee) in gs.fee_reimburse.iter_mut().enumerate() {
+            if games_mask & (1 << game) != 0 {
+                *fee = lamports;
+            }
+        }
+        emit!(FeeReimburseChanged { by, games_mask, lamports });
+        Ok(())
+    }
+
+    /// Config manager waives (VIP) or restores the lock fee reimbursement for one user vault.
+    pub fn set_fee_waiver(ctx: Context<SetFeeWaiver>, waived: bool) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        let uv = &mut ctx.accounts.user_vault;
+        uv.fee_waived = waived;
+        emit!(FeeWaiverChanged { by, owner: uv.owner, waived });
+        Ok(())
+    }
+
+    // ---- per-game config ----
+    /// Config manager creates `game`'s config: bet limits, house edge and whether it takes
+    /// bets. The game's locks fail until it exists.
+    pub fn init_game_config(ctx: Context<InitGameConfig>, game: u8, enabled: bool, limits: GameLimits) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        require!(limits_supported(game, &limits), CasinoErr::BadParams);
+        let cfg = &mut ctx.accounts.game_config;
+        cfg.game = game;
+        cfg.enabled = enabled;
+        cfg.version = 1;
+        cfg.limits = limits;
+        cfg.randomness = RandomnessConfig::default();
+        cfg.bump = ctx.bumps.game_config;
+        emit!(GameConfigChanged { by, game, version: cfg.version, enabled, limits });
+        Ok(())
+    }
+
+    /// Config manager replaces a game's limits and enabled flag and bumps its `version`. Open
+    /// bets keep the exposure recorded at lock.
+    pub fn update_game_config(ctx: Context<UpdateGameConfig>, enabled: bool, limits: GameLimits) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        let cfg = &mut ctx.accounts.game_config;
+        require!(limits_supported(cfg.game, &limits), CasinoErr::BadParams);
+        cfg.enabled = enabled;
+        cfg.limits = limits;
+        cfg.version = cfg.version.saturating_add(1);
+        emit!(GameConfigChanged { by, game: cfg.game, version: cfg.version, enabled, limits });
+        Ok(())
+    }
+
+    /// Super-admin picks a game's randomness source: backend commit/reveal, or an oracle program
+    /// on the build's allow-list (randomness::ORACLE_PROGRAM_IDS) whose requests locks create and
+    /// resolves read. Bets already locked keep the source they were locked with.
+    pub fn set_randomness(ctx: Context<UpdateGameConfig>, randomness: RandomnessConfig) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::SUPER_ADMIN, &by)?;
+        require!(randomness.is_valid(), CasinoErr::BadParams);
+        let cfg = &mut ctx.accounts.game_config;
+        cfg.randomness = randomness;
+        cfg.version = cfg.version.saturating_add(1);
+        emit!(RandomnessChanged { by, game: cfg.game, version: cfg.version, randomness });
+        Ok(())
+    }
+
+    // ---- resolver co-signing ----
+    /// Super-admin registers up to `MAX_COSIGNERS` resolver keys, one per backend signer
+    /// instance, each allowed to sign `epoch_budget` lamports of payouts per epoch (0 = no limit).
+    /// A payout above `threshold` lamports needs `quorum` of them to sign the resolve message.
+    pub fn init_resolver_set(
+        ctx: Context<InitResolverSet>,
+        keys: Vec<Pubkey>,
+        quorum: u8,
+        threshold: u64,
+        epoch_budget: u64,
+    ) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut ctx.accounts.resolver_set;
+        rs.cosigners = admin::new_resolver_set(&ctx.accounts.admin_config.roles, &by, &keys, quorum, threshold, epoch_budget)?;
+        rs.bump = ctx.bumps.resolver_set;
+        emit!(ResolverSetChanged { by, keys, quorum, threshold, epoch_budget });
+        Ok(())
+    }
+
+    /// Super-admin queues a replacement for the resolver keys, quorum, co-signing threshold and
+    /// budgets (every key enabled); `apply_resolver_set` installs it after ROLE_TIMELOCK_SECS.
+    pub fn propose_resolver_set(
+        ctx: Context<SetResolverSet>,
+        keys: Vec<Pubkey>,
+        quorum: u8,
+        threshold: u64,
+        epoch_budget: u64,
+    ) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let roles = &ctx.accounts.admin_config.roles;
+        let pending = &mut ctx.accounts.resolver_set.pending;
+        let eta = admin::propose_resolver_set(roles, &by, pending, &keys, quorum, threshold, epoch_budget)?;
+        emit!(ResolverSetProposed { by, keys, quorum, threshold, epoch_budget, eta });
+        Ok(())
+    }
+
+    /// Super-admin drops the pending resolver-set change (a proposed set or queued key changes).
+    pub fn cancel_resolver_set(ctx: Context<SetResolverSet>) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::cancel_resolver_set(&ctx.accounts.admin_config.roles, &by, &mut ctx.accounts.resolver_set.pending)?;
+        emit!(ResolverSetCancelled { by });
+        Ok(())
+    }
+
+    /// Permissionless: installs the pending resolver set once its timelock has elapsed.
+    pub fn apply_resolver_set(ctx: Context<ApplyResolverSet>) -> Result<()> {
+        let rs = &mut *ctx.accounts.resolver_set;
+        admin::apply_resolver_set(&mut rs.cosigners, &mut rs.pending)?;
+        let cosigners = &rs.cosigners;
+        let keys = cosigners.keys[..cosigners.count as usize].iter().map(|k| k.key).collect();
+        emit!(ResolverSetApplied { keys, quorum: cosigners.quorum, threshold: cosigners.threshold });
+        Ok(())
+    }
+
+    /// Super-admin sets one resolver key's enabled flag and per-epoch budget. Disabling it or
+    /// lowering the budget applies at once; enabling it or raising the budget is queued behind
+    /// the timelock for `apply_resolver_set`.
+    pub fn set_resolver_key(ctx: Context<SetResolverSet>, key: Pubkey, enabled: bool, epoch_budget: u64) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut *ctx.accounts.resolver_set;
+        let roles = &ctx.accounts.admin_config.roles;
+        match admin::set_resolver_key(roles, &by, &mut rs.cosigners, &mut rs.pending, &key, enabled, epoch_budget)? {
+            None => emit!(ResolverKeyChanged { by, key, enabled, epoch_budget }),
+            Some(eta) => emit!(ResolverKeyChangeProposed { by, key, enabled, epoch_budget, eta }),
+        }
+        Ok(())
+    }
+
+    /// Pauser or super-admin disables one resolver key (e.g. a compromised signer instance),
+    /// in the live set and in any pending one.
+    pub fn disable_resolver_key(ctx: Context<SetResolverSet>, key: Pubkey) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut *ctx.accounts.resolver_set;
+        let roles = &ctx.accounts.admin_config.roles;
+        let epoch_budget = admin::disable_resolver_key(roles, &by, &mut rs.cosigners, &mut rs.pending, &key)?;
+        emit!(ResolverKeyChanged { by, key, enabled: false, epoch_budget });
+        Ok(())
+    }
+
+    // ---- bankroll limit views (read-only; simulate to get the returned BetLimits) ----
+    pub fn dice_limits(ctx: Context<QueryLimits>, bet_type: u8, target: u8) -> Result<BetLimits> {
+        let cfg = view_limits(&ctx.accounts.game_config, GAME_DICE)?;
+        require!(bet_type <= 1 && cfg.param_ok(target as u32), CasinoErr::BadParams);
+        ctx.accounts.game_state.house.limits_for(&ctx.accounts.house_vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, cfg.max_bet, |s| exposure::dice(s, bet_type, target))
+    }
+
+    pub fn mines_limits(ctx: Context<QueryLimits>, rows: u8, cols: u8, mines: u8) -> Result<BetLimits> {
+        let cfg = view_limits(&ctx.accounts.game_config, GAME_MINES)?;
+        require!(cfg.param_ok(rows as u32) && cfg.param_ok(cols as u32), CasinoErr::BadParams);
+        require!(mines >= 1 && mines < rows * cols, CasinoErr::BadParams);
+        ctx.accounts.game_state.house.limits_for(&ctx.accounts.house_vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, cfg.max_bet, |s| exposure::mines(s, rows * cols, mines))
+    }
+
+    /// Resolver posts `sha256(server_seed)` for the player's next bet; the next lock consumes it
+    /// and the matching resolve must reveal `server_seed`. Overwriting an unused commitment is allowed.
+    pub fn commit_seed(ctx: Context<CommitSeed>, commitment: [u8; 32]) -> Result<()> {
+        require!(ctx.accounts.resolver.key().to_bytes() == ctx.accounts.admin_config.roles.resolver_key(), CasinoErr::Unauthorized);
+        require!(commitment != fair::EMPTY_COMMITMENT, CasinoErr::BadParams);
+        let uv = &mut ctx.accounts.user_vault;
+        uv.seed_commit = commitment;
+        emit!(SeedCommitted { player: uv.owner, commitment });
+        Ok(())
+    }
+
     // ---- dice ----
     pub fn dice_lock(ctx: Context<DiceLock>, args: DiceLockArgs) -> Result<()> {
-        require!(args.bet_amount >= MIN_BET_LAMPORTS && args.bet_amount <= MAX_BET_LAMPORTS, CasinoErr::BadParams);
-        require!(args.target >= 2 && args.target <= 98, CasinoErr::BadParams);
+        require!(!ctx.accounts.game_state.house.flags.lock_paused(GAME_DICE), CasinoErr::Paused);
+        let limits = lock_limits(&ctx.accounts.game_config)?;
+        require!(limits.bet_ok(args.bet_amount), CasinoErr::BadParams);
+        require!(limits.param_ok(args.target as u32), CasinoErr::BadParams);
         require!(args.bet_type <= 1, CasinoErr::BadParams);
 
-        require_ed25519_present(&ctx.accounts.sysvar_instructions.to_account_info(), args.ed25519_instr_index)?;
+        require_lock_window(args.expiry_unix, DICE_MAX_TTL_SECS, args.expiry_slot, DICE_MAX_TTL_SLOTS)?;
         require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
+        advance_nonce(&mut ctx.accounts.user_vault, args.nonce)?;
+        require_player_consent(
+            &ctx.accounts.sysvar_instructions.to_account_info(), &ctx.accounts.player.to_account_info(), ctx.program_id,
+            GAME_DICE, &dice_params(args.bet_amount, args.bet_type, args.target), &args.client_seed, args.nonce, args.expiry_unix,
+        )?;
+
+        let pending_key = ctx.accounts.pending_bet.key();
+        let (server_commit, oracle_request) = randomness::lock(
+            &ctx.accounts.game_config.randomness,
+            ctx.accounts.oracle_program.as_deref(),
+            ctx.accounts.oracle_request.as_deref(),
+            Some(&mut ctx.accounts.user_vault.seed_commit),
+            &ctx.accounts.fee_payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &pending_key,
+        )?;
+        let slot_hash = fair::latest_slot_hash(&ctx.accounts.slot_hashes.to_account_info()).ok_or(CasinoErr::BadParams)?;
 
-        let uv_bal = **ctx.accounts.user_vault.to_account_info().lamports.borrow();
-        let need = args.bet_amount.saturating_add(FEE_REIMBURSE_LAMPORTS);
-        require!(uv_bal >= need, CasinoErr::InsufficientVault);
+        let fee = lock_fee(&ctx.accounts.game_state, &ctx.accounts.user_vault, GAME_DICE);
+        let need = args.bet_amount.saturating_add(fee);
+        require_debit(&ctx.accounts.user_vault.to_account_info(), need)?;
 
         let uv_ai = ctx.accounts.user_vault.to_account_info();
         let hv_ai = ctx.accounts.house_vault.to_account_info();
         safe_move_lamports(&uv_ai, &hv_ai, args.bet_amount)?;
 
-        if FEE_REIMBURSE_LAMPORTS > 0 {
+        if fee > 0 {
             let fp_ai = ctx.accounts.fee_payer.to_account_info();
-            safe_move_lamports(&uv_ai, &fp_ai, FEE_REIMBURSE_LAMPORTS)?;
+            safe_move_lamports(&uv_ai, &fp_ai, fee)?;
         }
 
+        let reserved = ctx.accounts.game_state.house.reserve(&hv_ai, HOUSE_RESERVE_LAMPORTS, exposure::dice(args.bet_amount, args.bet_type, args.target))?;
+
         let pb = &mut ctx.accounts.pending_bet;
         pb.player = ctx.accounts.player.key();
         pb.amount = args.bet_amount;
@@ -348,29 +1073,59 @@ pub mod casino {
         pb.target = args.target;
         pb.nonce = args.nonce;
         pb.expiry_unix = args.expiry_unix;
+        pb.expiry_slot = args.expiry_slot;
+        pb.server_commit = server_commit;
+        pb.client_seed = args.client_seed;
+        pb.slot_hash = slot_hash;
+        pb.oracle_request = oracle_request;
+        pb.exposure = reserved;
+        pb.rent_payer = ctx.accounts.fee_payer.key();
+        pb.house_edge_bps = limits.house_edge_bps;
         pb.settled = false;
 
-        emit!(DiceLocked { player: pb.player, amount: pb.amount, bet_type: pb.bet_type, target: pb.target, nonce: pb.nonce });
+        emit!(DiceLocked { player: pb.player, amount: pb.amount, bet_type: pb.bet_type, target: pb.target, nonce: pb.nonce, slot_hash: pb.slot_hash });
         Ok(())
     }
 
     pub fn dice_resolve(ctx: Context<DiceResolve>, args: DiceResolveArgs) -> Result<()> {
+        require!(!ctx.accounts.game_state.house.flags.resolve_paused(GAME_DICE), CasinoErr::Paused);
+        let pending_key = ctx.accounts.pending_bet.key();
+        let house_key   = ctx.accounts.house_vault.key();
+        let player_key  = ctx.accounts.player.key();
+
         let pb = &mut ctx.accounts.pending_bet;
         require!(!pb.settled, CasinoErr::BadPending);
 
         let clock = Clock::get()?;
-        require!(clock.unix_timestamp <= pb.expiry_unix, CasinoErr::Expired);
-        require_ed25519_present(&ctx.accounts.sysvar_instructions.to_account_info(), args.ed25519_instr_index)?;
-
-        require!(args.roll >= 1 && args.roll <= 100, CasinoErr::BadParams);
-        let win = match pb.bet_type { 0 => args.roll < pb.target, _ => args.roll > pb.target };
-        if win {
-            require!(args.payout > 0 && args.payout <= MAX_PAYOUT_LAMPORTS, CasinoErr::BadPayout);
-        } else {
-            require!(args.payout == 0, CasinoErr::BadPayout);
-        }
+        require!(!expiry::is_expired(&clock, pb.expiry_unix, pb.expiry_slot), CasinoErr::Expired);
 
-        if win && args.payout > 0 {
+        let seed = randomness::resolve_seed(
+            &pb.oracle_request,
+            ctx.accounts.oracle_request.as_deref(),
+            &pb.server_commit,
+            &args.server_seed,
+        )?;
+        let outcome = fair::outcome_hash(&seed, &pb.client_seed, &pb.slot_hash, pb.nonce);
+        let roll = fair::dice_roll(&outcome);
+
+        let params = dice_params(pb.amount, pb.bet_type, pb.target);
+        let expected = message::resolve_message(
+            DICE_DOMAIN_TAG, ctx.program_id, &house_key, &player_key, &pending_key,
+            &[&params, &[roll]], args.payout, pb.nonce, pb.expiry_unix,
+        );
+        cosign::require_signers(
+            &ctx.accounts.sysvar_instructions.to_account_info(),
+            &mut ctx.accounts.resolver_set.cosigners,
+            &expected,
+            args.payout,
+            Clock::get()?.epoch,
+        )?;
+
+        let win = match pb.bet_type { 0 => roll < pb.target, _ => roll > pb.target };
+        let due = payout::dice(pb.amount, pb.bet_type, pb.target, roll, pb.house_edge_bps);
+        require!(args.payout == due && due <= pb.exposure, CasinoErr::BadPayout);
+
+        if args.payout > 0 {
             let bump_v = ctx.bumps.house_vault;
             let ix = system_instruction::transfer(&ctx.accounts.house_vault.key(), &ctx.accounts.user_vault.key(), args.payout);
             invoke_signed(&ix, &[
@@ -380,35 +1135,72 @@ pub mod casino {
             ], &[&[b"vault", &[bump_v]]])?;
         }
 
+        record_outflow(&mut ctx.accounts.game_state, pb.amount, args.payout)?;
+        ctx.accounts.game_state.house.release(pb.exposure);
         pb.settled = true;
-        emit!(DiceResolved { player: pb.player, win, roll: args.roll, payout: args.payout, nonce: pb.nonce });
+        emit!(DiceResolved { player: pb.player, win, roll, payout: args.payout, nonce: pb.nonce });
+        Ok(())
+    }
+
+    /// Permissionless: once past expiry + grace, return the stake to the player's user_vault.
+    pub fn dice_refund_expired(ctx: Context<DiceRefund>) -> Result<()> {
+        let p = &ctx.accounts.pending_bet;
+        require_refundable(p.settled, p.expiry_unix, p.expiry_slot)?;
+        let amount = p.amount;
+
+        refund_principal(&ctx.accounts.house_vault, &ctx.accounts.user_vault, &ctx.accounts.system_program, ctx.bumps.house_vault, amount)?;
+
+        let p = &mut ctx.accounts.pending_bet;
+        ctx.accounts.game_state.house.release(p.exposure);
+        p.settled = true;
+        emit!(DiceRefunded { player: p.player, amount, nonce: p.nonce });
         Ok(())
     }
 
     // ---- mines ----
     pub fn mines_lock(ctx: Context<MinesLock>, args: MinesLockArgs) -> Result<()> {
-        require!(args.bet_amount >= MIN_BET_LAMPORTS && args.bet_amount <= MAX_BET_LAMPORTS, CasinoErr::BadParams);
-        require!(args.rows >= 2 && args.rows <= 8, CasinoErr::BadParams);
-        require!(args.cols >= 2 && args.cols <= 8, CasinoErr::BadParams);
+        require!(!ctx.accounts.game_state.house.flags.lock_paused(GAME_MINES), CasinoErr::Paused);
+        let limits = lock_limits(&ctx.accounts.game_config)?;
+        require!(limits.bet_ok(args.bet_amount), CasinoErr::BadParams);
+        require!(limits.param_ok(args.rows as u32) && limits.param_ok(args.cols as u32), CasinoErr::BadParams);
         let total = (args.rows as u16) * (args.cols as u16);
         require!(args.mines >= 1 && (args.mines as u16) < total, CasinoErr::BadParams);
 
-        require_ed25519_present(&ctx.accounts.sysvar_instructions.to_account_info(), args.ed25519_instr_index)?;
+        require_lock_window(args.expiry_unix, MINES_MAX_TTL_SECS, args.expiry_slot, MINES_MAX_TTL_SLOTS)?;
         require!(ctx.accounts.user_vault.owner == ctx.accounts.player.key(), CasinoErr::VaultMismatch);
+        advance_nonce(&mut ctx.accounts.user_vault, args.nonce)?;
+        require_player_consent(
+            &ctx.accounts.sysvar_instructions.to_account_info(), &ctx.accounts.player.to_account_info(), ctx.program_id,
+            GAME_MINES, &mines_params(args.bet_amount, args.rows, args.cols, args.mines), &args.client_seed, args.nonce, args.expiry_unix,
+        )?;
+
+        let pending_key = ctx.accounts.pending.key();
+        let (server_commit, oracle_request) = randomness::lock(
+            &ctx.accounts.game_config.randomness,
+            ctx.accounts.oracle_program.as_deref(),
+            ctx.accounts.oracle_request.as_deref(),
+            Some(&mut ctx.accounts.user_vault.seed_commit),
+            &ctx.accounts.fee_payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &pending_key,
+        )?;
+        let slot_hash = fair::latest_slot_hash(&ctx.accounts.slot_hashes.to_account_info()).ok_or(CasinoErr::BadParams)?;
 
-        let uv_bal = **ctx.accounts.user_vault.to_account_info().lamports.borrow();
-        let need = args.bet_amount.saturating_add(FEE_REIMBURSE_LAMPORTS);
-        require!(uv_bal >= need, CasinoErr::InsufficientVault);
+        let fee = lock_fee(&ctx.accounts.game_state, &ctx.accounts.user_vault, GAME_MINES);
+        let need = args.bet_amount.saturating_add(fee);
+        require_debit(&ctx.accounts.user_vault.to_account_info(), need)?;
 
         let uv_ai = ctx.accounts.user_vault.to_account_info();
         let hv_ai = ctx.accounts.house_vault.to_account_info();
         safe_move_lamports(&uv_ai, &hv_ai, args.bet_amount)?;
 
-        if FEE_REIMBURSE_LAMPORTS > 0 {
+        if fee > 0 {
             let fp_ai = ctx.accounts.fee_payer.to_account_info();
-            safe_move_lamports(&uv_ai, &fp_ai, FEE_REIMBURSE_LAMPORTS)?;
+            safe_move_lamports(&uv_ai, &fp_ai, fee)?;
         }
 
+        let reserved = ctx.accounts.game_state.house.reserve(&hv_ai, HOUSE_RESERVE_LAMPORTS, exposure::mines(args.bet_amount, args.rows * args.cols, args.mines))?;
+
         let p = &mut ctx.accounts.pending;
         p.player = ctx.accounts.player.key();
         p.amount = args.bet_amount;
@@ -417,23 +1209,57 @@ pub mod casino {
         p.mines = args.mines;
         p.nonce = args.nonce;
         p.expiry_unix = args.expiry_unix;
+        p.expiry_slot = args.expiry_slot;
+        p.server_commit = server_commit;
+        p.client_seed = args.client_seed;
+        p.slot_hash = slot_hash;
+        p.oracle_request = oracle_request;
+        p.exposure = reserved;
+        p.rent_payer = ctx.accounts.fee_payer.key();
+        p.house_edge_bps = limits.house_edge_bps;
         p.settled = false;
 
-        emit!(MinesLocked { player: p.player, amount: p.amount, rows: p.rows, cols: p.cols, mines: p.mines, nonce: p.nonce });
+        emit!(MinesLocked { player: p.player, amount: p.amount, rows: p.rows, cols: p.cols, mines: p.mines, nonce: p.nonce, slot_hash: p.slot_hash });
         Ok(())
     }
 
     pub fn mines_resolve(ctx: Context<MinesResolve>, args: MinesResolveArgs) -> Result<()> {
+        require!(!ctx.accounts.game_state.house.flags.resolve_paused(GAME_MINES), CasinoErr::Paused);
+        let pending_key = ctx.accounts.pending.key();
+        let house_key   = ctx.accounts.house_vault.key();
+        let player_key  = ctx.accounts.player.key();
+
         let p = &mut ctx.accounts.pending;
         require!(!p.settled, CasinoErr::BadPending);
 
         let clock = Clock::get()?;
-        require!(clock.unix_timestamp <= p.expiry_unix, CasinoErr::Expired);
-        require_ed25519_present(&ctx.accounts.sysvar_instructions.to_account_info(), args.ed25519_instr_index)?;
+        require!(!expiry::is_expired(&clock, p.expiry_unix, p.expiry_slot), CasinoErr::Expired);
+
+        let seed = randomness::resolve_seed(
+            &p.oracle_request,
+            ctx.accounts.oracle_request.as_deref(),
+            &p.server_commit,
+            &args.server_seed,
+        )?;
+        let outcome = fair::outcome_hash(&seed, &p.client_seed, &p.slot_hash, p.nonce);
+        let layout = fair::mines_layout(&outcome, p.rows * p.cols, p.mines);
+
+        let params = mines_params(p.amount, p.rows, p.cols, p.mines);
+        let expected = message::resolve_message(
+            MINES_DOMAIN_TAG, ctx.program_id, &house_key, &player_key, &pending_key,
+            &[&params, &layout.to_le_bytes(), &args.picks.to_le_bytes()], args.payout, p.nonce, p.expiry_unix,
+        );
+        cosign::require_signers(
+            &ctx.accounts.sysvar_instructions.to_account_info(),
+            &mut ctx.accounts.resolver_set.cosigners,
+            &expected,
+            args.payout,
+            Clock::get()?.epoch,
+        )?;
 
-        let expected = ((p.nonce % 251) + 1) as u8;
-        require!(args.checksum == expected, CasinoErr::BadParams);
-        require!(args.payout <= MAX_PAYOUT_LAMPORTS, CasinoErr::BadPayout);
+        let due = payout::mines(p.amount, p.rows * p.cols, p.mines, layout, args.picks, p.house_edge_bps)
+            .ok_or(CasinoErr::BadParams)?;
+        require!(args.payout == due && due <= p.exposure, CasinoErr::BadPayout);
 
         if args.payout > 0 {
             let bump_v = ctx.bumps.house_vault;
@@ -445,8 +1271,25 @@ pub mod casino {
             ], &[&[b"vault", &[bump_v]]])?;
         }
 
+        record_outflow(&mut ctx.accounts.game_state, p.amount, args.payout)?;
+        ctx.accounts.game_state.house.release(p.exposure);
+        p.settled = true;
+        emit!(MinesResolved { player: p.player, payout: args.payout, mines_layout: layout, nonce: p.nonce });
+        Ok(())
+    }
+
+    /// Permissionless: once past expiry + grace, return the stake to the player's user_vault.
+    pub fn mines_refund_expired(ctx: Context<MinesRefund>) -> Result<()> {
+        let p = &ctx.accounts.pending;
+        require_refundable(p.settled, p.expiry_unix, p.expiry_slot)?;
+        let amount = p.amount;
+
+        refund_principal(&ctx.accounts.house_vault, &ctx.accounts.user_vault, &ctx.accounts.system_program, ctx.bumps.house_vault, amount)?;
+
+        let p = &mut ctx.accounts.pending;
+        ctx.accounts.game_state.house.release(p.exposure);
         p.settled = true;
-        emit!(MinesResolved { player: p.player, payout: args.payout, checksum: args.checksum, nonce: p.nonce });
+        emit!(MinesRefunded { player: p.player, amount, nonce: p.nonce });
         Ok(())
     }
 }
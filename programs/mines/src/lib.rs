@@ -1,19 +1,274 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program::{transfer, Transfer};
 use anchor_lang::solana_program::sysvar;
+use casino_common::config::GameLimits;
+use casino_common::cosign::{self, Cosigners, PendingCosigners};
+use casino_common::deploy;
+use casino_common::house::{BetLimits, House};
+use casino_common::roles::{self, Roles};
+use casino_common::{admin, expiry, exposure, fair, message, pause, payout};
 
 declare_id!("EJxuRyEp18PhxmpjxGgBdyRd8NqHMbGkY4ABznTdvqpd"); // ⬅️ REPLACE with your REAL deployed program id
 
+// Canonical domain tag for off-chain signing
+const DOMAIN_TAG: &[u8] = b"MINES_V1";
+// After `expired_at` / `expiry_slot`, before anyone may refund an unresolved round
+const REFUND_GRACE_SECS: i64 = 600;
+const REFUND_GRACE_SLOTS: u64 = 1_500;
+// Longest lock → expiry window (rounds stay open while the player reveals tiles)
+const MAX_TTL_SECS: i64 = 3_600;
+const MAX_TTL_SLOTS: u64 = 9_000;
+// Vault balance never promised to open rounds; default and highest share of the vault (bps)
+// one round may win
+const HOUSE_RESERVE_LAMPORTS: u64 = 100_000_000;
+// Net vault outflow (50 SOL) over the last hour that trips the circuit breaker
+const DEFAULT_OUTFLOW_THRESHOLD: u64 = 50_000_000_000;
+const DEFAULT_OUTFLOW_WINDOW_HOURS: u8 = 1;
+
 #[program]
 pub mod mines {
     use super::*;
 
     /// One-time initializer: creates the Vault PDA (lamports-only account).
     /// Accounts:
-    /// - authority: signer (pays rent)
+    /// - authority: signer (pays rent; upgrade authority or deployer)
     /// - vault: PDA ["vault"]
     /// - system_program
-    pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
+    /// - program_data: optional, this program's ProgramData
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        require!(
+            deploy::may_initialize(ctx.program_id, &ctx.accounts.authority.key(), ctx.accounts.program_data.as_deref()),
+            MinesError::NotDeployer
+        );
+        Ok(())
+    }
+
+    /// One-time: creates the AdminConfig PDA. `admin_pubkey` becomes the resolver, which only
+    /// posts seed commitments (resolves are signed by the resolver set).
+    /// Accounts:
+    /// - authority: signer (pays rent, holds every other role until rotated; upgrade authority or deployer)
+    /// - admin_config: PDA ["admin"]
+    /// - system_program
+    /// - program_data: optional, this program's ProgramData
+    pub fn init_admin(ctx: Context<InitAdmin>, admin_pubkey: [u8; 32]) -> Result<()> {
+        require!(
+            deploy::may_initialize(ctx.program_id, &ctx.accounts.authority.key(), ctx.accounts.program_data.as_deref()),
+            MinesError::NotDeployer
+        );
+        let roles = Roles::new(Pubkey::new_from_array(admin_pubkey), ctx.accounts.authority.key());
+        ctx.accounts.admin_config.roles = roles;
+        for (role, key) in roles.keys.iter().enumerate() {
+            emit!(RoleChanged { role: role as u8, previous: Pubkey::default(), current: *key });
+        }
+        Ok(())
+    }
+
+    /// Super-admin queues `new_key` for `role` (see `casino_common::roles`); it can be
+    /// applied once `roles::ROLE_TIMELOCK_SECS` have passed.
+    pub fn propose_role(ctx: Context<ManageRole>, role: u8, new_key: Pubkey) -> Result<()> {
+        let roles = &mut ctx.accounts.admin_config.roles;
+        let eta = admin::propose_role(roles, &ctx.accounts.authority.key(), role, new_key)?;
+        emit!(RoleChangeProposed { role, current: roles.key(role), proposed: new_key, eta });
+        Ok(())
+    }
+
+    /// Super-admin drops a pending role change before it is applied.
+    pub fn cancel_role(ctx: Context<ManageRole>, role: u8) -> Result<()> {
+        let roles = &mut ctx.accounts.admin_config.roles;
+        let proposed = admin::cancel_role(roles, &ctx.accounts.authority.key(), role)?;
+        emit!(RoleChangeCancelled { role, proposed });
+        Ok(())
+    }
+
+    /// Permissionless: installs a proposed key once its timelock has elapsed.
+    pub fn apply_role(ctx: Context<ApplyRole>, role: u8) -> Result<()> {
+        let (previous, current) = admin::apply_role(&mut ctx.accounts.admin_config.roles, role)?;
+        emit!(RoleChanged { role, previous, current });
+        Ok(())
+    }
+
+    /// Creates the (unpaused) GameState PDA that locks and resolves read.
+    pub fn init_game_state(ctx: Context<InitGameState>) -> Result<()> {
+        admin::require_pauser(&ctx.accounts.admin_config.roles, &ctx.accounts.authority.key())?;
+        let gs = &mut ctx.accounts.game_state;
+        gs.house = House::new(DEFAULT_OUTFLOW_THRESHOLD, DEFAULT_OUTFLOW_WINDOW_HOURS);
+        gs.bump = ctx.bumps.game_state;
+        Ok(())
+    }
+
+    /// Halts new bets, and resolves too when `halt_resolve`; a resolve halt already in place
+    /// stays until `unpause`. `reason` is an incident code echoed in the event. Expired-bet
+    /// refunds stay open.
+    pub fn pause(ctx: Context<SetPause>, halt_resolve: bool, reason: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let house = &mut ctx.accounts.game_state.house;
+        admin::pause(&ctx.accounts.admin_config.roles, &by, house, pause::GLOBAL, halt_resolve)?;
+        emit!(GamePaused { by, halt_resolve, reason });
+        Ok(())
+    }
+
+    pub fn unpause(ctx: Context<SetPause>, reason: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::unpause(&ctx.accounts.admin_config.roles, &by, &mut ctx.accounts.game_state.house, pause::GLOBAL)?;
+        emit!(GameUnpaused { by, reason });
+        Ok(())
+    }
+
+    /// Config manager sets the share of the vault (bps) one round may stand to win; the maximum
+    /// bet for every setting follows from it.
+    pub fn set_max_payout_bps(ctx: Context<SetLimits>, max_payout_bps: u16) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let house = &mut ctx.accounts.game_state.house;
+        let previous_bps = admin::set_max_payout_bps(&ctx.accounts.admin_config.roles, &by, house, max_payout_bps)?;
+        emit!(MaxPayoutChanged { by, previous_bps, current_bps: max_payout_bps });
+        Ok(())
+    }
+
+    /// Config manager sets the net outflow (payouts minus stakes, lamports; 0 disables) over the
+    /// last `window_hours` that trips the circuit breaker and pauses the game.
+    pub fn set_outflow_breaker(ctx: Context<SetLimits>, threshold: u64, window_hours: u8) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let house = &mut ctx.accounts.game_state.house;
+        admin::set_outflow_breaker(&ctx.accounts.admin_config.roles, &by, house, threshold, window_hours)?;
+        emit!(BreakerChanged { by, threshold, window_hours });
+        Ok(())
+    }
+
+    /// Super-admin registers up to `MAX_COSIGNERS` resolver keys, one per backend signer
+    /// instance, each allowed to sign `epoch_budget` lamports of payouts per epoch (0 = no limit).
+    /// A payout above `threshold` lamports needs `quorum` of them to sign the resolve message.
+    pub fn init_resolver_set(
+        ctx: Context<InitResolverSet>,
+        keys: Vec<Pubkey>,
+        quorum: u8,
+        threshold: u64,
+        epoch_budget: u64,
+    ) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut ctx.accounts.resolver_set;
+        rs.cosigners = admin::new_resolver_set(&ctx.accounts.admin_config.roles, &by, &keys, quorum, threshold, epoch_budget)?;
+        rs.bump = ctx.bumps.resolver_set;
+        emit!(ResolverSetChanged { by, keys, quorum, threshold, epoch_budget });
+        Ok(())
+    }
+
+    /// Super-admin queues a replacement for the resolver keys, quorum, co-signing threshold and
+    /// budgets (every key enabled); `apply_resolver_set` installs it after ROLE_TIMELOCK_SECS.
+    pub fn propose_resolver_set(
+        ctx: Context<SetResolverSet>,
+        keys: Vec<Pubkey>,
+        quorum: u8,
+        threshold: u64,
+        epoch_budget: u64,
+    ) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let roles = &ctx.accounts.admin_config.roles;
+        let pending = &mut ctx.accounts.resolver_set.pending;
+        let eta = admin::propose_resolver_set(roles, &by, pending, &keys, quorum, threshold, epoch_budget)?;
+        emit!(ResolverSetProposed { by, keys, quorum, threshold, epoch_budget, eta });
+        Ok(())
+    }
+
+    /// Super-admin drops the pending resolver-set change (a proposed set or queued key changes).
+    pub fn cancel_resolver_set(ctx: Context<SetResolverSet>) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::cancel_resolver_set(&ctx.accounts.admin_config.roles, &by, &mut ctx.accounts.resolver_set.pending)?;
+        emit!(ResolverSetCancelled { by });
+        Ok(())
+    }
+
+    /// Permissionless: installs the pending resolver set once its timelock has elapsed.
+    pub fn apply_resolver_set(ctx: Context<ApplyResolverSet>) -> Result<()> {
+        let rs = &mut *ctx.accounts.resolver_set;
+        admin::apply_resolver_set(&mut rs.cosigners, &mut rs.pending)?;
+        let cosigners = &rs.cosigners;
+        let keys = cosigners.keys[..cosigners.count as usize].iter().map(|k| k.key).collect();
+        emit!(ResolverSetApplied { keys, quorum: cosigners.quorum, threshold: cosigners.threshold });
+        Ok(())
+    }
+
+    /// Super-admin sets one resolver key's enabled flag and per-epoch budget. Disabling it or
+    /// lowering the budget applies at once; enabling it or raising the budget is queued behind
+    /// the timelock for `apply_resolver_set`.
+    pub fn set_resolver_key(ctx: Context<SetResolverSet>, key: Pubkey, enabled: bool, epoch_budget: u64) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut *ctx.accounts.resolver_set;
+        let roles = &ctx.accounts.admin_config.roles;
+        match admin::set_resolver_key(roles, &by, &mut rs.cosigners, &mut rs.pending, &key, enabled, epoch_budget)? {
+            None => emit!(ResolverKeyChanged { by, key, enabled, epoch_budget }),
+            Some(eta) => emit!(ResolverKeyChangeProposed { by, key, enabled, epoch_budget, eta }),
+        }
+        Ok(())
+    }
+
+    /// Pauser or super-admin disables one resolver key (e.g. a compromised signer instance),
+    /// in the live set and in any pending one.
+    pub fn disable_resolver_key(ctx: Context<SetResolverSet>, key: Pubkey) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        let rs = &mut *ctx.accounts.resolver_set;
+        let roles = &ctx.accounts.admin_config.roles;
+        let epoch_budget = admin::disable_resolver_key(roles, &by, &mut rs.cosigners, &mut rs.pending, &key)?;
+        emit!(ResolverKeyChanged { by, key, enabled: false, epoch_budget });
+        Ok(())
+    }
+
+    /// Config manager creates the game config (bet limits, house edge, enabled flag); locks
+    /// fail until it exists.
+    pub fn init_game_config(ctx: Context<InitGameConfig>, enabled: bool, limits: GameLimits) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        require!(limits_supported(&limits), MinesError::BadConfig);
+        let cfg = &mut ctx.accounts.game_config;
+        cfg.enabled = enabled;
+        cfg.version = 1;
+        cfg.limits = limits;
+        cfg.bump = ctx.bumps.game_config;
+        emit!(GameConfigChanged { by, version: cfg.version, enabled, limits });
+        Ok(())
+    }
+
+    /// Config manager replaces the limits and enabled flag and bumps `version`. Open rounds
+    /// keep the exposure recorded at lock.
+    pub fn update_game_config(ctx: Context<UpdateGameConfig>, enabled: bool, limits: GameLimits) -> Result<()> {
+        let by = ctx.accounts.authority.key();
+        admin::require_role(&ctx.accounts.admin_config.roles, roles::CONFIG_MANAGER, &by)?;
+        require!(limits_supported(&limits), MinesError::BadConfig);
+        let cfg = &mut ctx.accounts.game_config;
+        cfg.enabled = enabled;
+        cfg.limits = limits;
+        cfg.version = cfg.version.saturating_add(1);
+        emit!(GameConfigChanged { by, version: cfg.version, enabled, limits });
+        Ok(())
+    }
+
+    /// Read-only (simulate): bankroll limits and the largest stake for the given setting.
+    pub fn bet_limits(ctx: Context<QueryLimits>, rows: u8, cols: u8, mines: u8) -> Result<BetLimits> {
+        let cfg = &ctx.accounts.game_config.limits;
+        require!(cfg.param_ok(rows as u32) && cfg.param_ok(cols as u32), MinesError::BadBoard);
+        require!(mines >= 1 && mines < rows * cols, MinesError::BadMines);
+        ctx.accounts.game_state.house.limits_for(&ctx.accounts.vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, cfg.max_bet, |s| {
+            exposure::mines(s, rows * cols, mines)
+        })
+    }
+
+    /// Resolver posts `sha256(server_seed)` for the player's next round (overwrites an unused one).
+    /// Accounts:
+    /// - resolver: signer (must hold admin_config's resolver role; pays rent on first commit)
+    /// - player: the player the commitment is for
+    /// - admin_config: PDA ["admin"]
+    /// - seed_commit: PDA ["seed", player]
+    /// - system_program
+    pub fn commit_seed(ctx: Context<CommitSeed>, commitment: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.resolver.key().to_bytes() == ctx.accounts.admin_config.roles.resolver_key(),
+            MinesError::Unauthorized
+        );
+        require!(commitment != fair::EMPTY_COMMITMENT, MinesError::NoSeedCommit);
+
+        let sc = &mut ctx.accounts.seed_commit;
+        sc.player = ctx.accounts.player.key();
+        sc.commitment = commitment;
+        sc.bump = ctx.bumps.seed_commit;
         Ok(())
     }
 
@@ -23,6 +278,9 @@ pub mod mines {
     /// - vault: PDA ["vault"] (MUST exist; created via `initialize`)
     /// - pending: PDA ["round", player, nonce_le_u64]
     /// - system_program
+    /// - seed_commit: PDA ["seed", player] (consumed)
+    /// `expiry_slot` is an optional slot deadline (0 = unix deadline only).
+    #[allow(clippy::too_many_arguments)]
     pub fn lock(
         ctx: Context<Lock>,
         bet_lamports: u64,
@@ -31,20 +289,45 @@ pub mod mines {
         mines: u8,
         nonce: u64,
         expiry_unix: i64,
+        expiry_slot: u64,
     ) -> Result<()> {
-        require!(bet_lamports > 0, MinesError::BadBet);
-        require!(rows >= 2 && rows <= 8, MinesError::BadBoard);
-        require!(cols >= 2 && cols <= 8, MinesError::BadBoard);
+        require!(!ctx.accounts.game_state.house.flags.lock_paused(pause::SOLE_GAME), MinesError::Paused);
+        let limits = lock_limits(&ctx.accounts.game_config)?;
+        require!(limits.bet_ok(bet_lamports), MinesError::BadBet);
+        require!(limits.param_ok(rows as u32) && limits.param_ok(cols as u32), MinesError::BadBoard);
 
         let total = (rows as u16) * (cols as u16);
         require!(mines >= 1 && (mines as u16) < total, MinesError::BadMines);
 
+        // deadline must lie in the future and within the max TTL
+        let clock = Clock::get()?;
+        require!(
+            expiry::lock_window_ok(&clock, expiry_unix, MAX_TTL_SECS, expiry_slot, MAX_TTL_SLOTS),
+            MinesError::BadExpiry
+        );
+
+        // Nonces only move forward (a closed pending PDA must never be re-opened)
+        let pn = &mut ctx.accounts.player_nonce;
+        require!(nonce > pn.last_nonce, MinesError::StaleNonce);
+        pn.player = ctx.accounts.player.key();
+        pn.last_nonce = nonce;
+        pn.bump = ctx.bumps.player_nonce;
+
+        // consume the resolver's seed commitment for this round
+        let server_commit = ctx.accounts.seed_commit.commitment;
+        require!(server_commit != fair::EMPTY_COMMITMENT, MinesError::NoSeedCommit);
+        ctx.accounts.seed_commit.commitment = fair::EMPTY_COMMITMENT;
+
         // move stake into vault PDA
         let from = ctx.accounts.player.to_account_info();
         let to = ctx.accounts.vault.to_account_info();
         let sys = ctx.accounts.system_program.to_account_info();
         transfer(CpiContext::new(sys, Transfer { from, to }), bet_lamports)?;
 
+        // reserve the worst-case cash-out against the vault
+        let reserved = exposure::mines(bet_lamports, rows * cols, mines);
+        ctx.accounts.game_state.house.reserve(&ctx.accounts.vault.to_account_info(), HOUSE_RESERVE_LAMPORTS, reserved)?;
+
         // write round
         let pending = &mut ctx.accounts.pending;
         pending.player = ctx.accounts.player.key();
@@ -54,32 +337,86 @@ pub mod mines {
         pending.mines = mines;
         pending.nonce = nonce;
         pending.expired_at = expiry_unix;
+        pending.expiry_slot = expiry_slot;
+        pending.server_commit = server_commit;
+        pending.exposure = reserved;
+        pending.house_edge_bps = limits.house_edge_bps;
         pending.settled = false;
 
         Ok(())
     }
 
-    /// Server-paid resolve: pays from vault PDA to player (backend decides payout).
+    /// Server-paid resolve: pays from vault PDA to player. The payout must be the one the mine
+    /// layout, the tiles the player opened (`picks`) and the recorded edge give.
     /// Accounts:
     /// - player: writable (receiver)
     /// - vault: PDA ["vault"] (signs with seeds to pay)
-    /// - admin: unchecked (kept to match backend account array)
+    /// - admin: resolver wallet (authorizes if it signs and is registered in resolver_set)
     /// - pending: round pda (closed to player)
     /// - system_program
-    /// - instructions sysvar (kept for compatibility with your WS/ed25519 flow; not enforced here)
+    /// - instructions sysvar (ed25519 attestation directly before resolve when `admin` does not sign)
+    /// - game_state: PDA ["game_state"]
+    /// - resolver_set: PDA ["resolvers"] (charged with the payout)
     pub fn resolve(
         ctx: Context<Resolve>,
-        checksum: u8,
+        server_seed: [u8; 32], // opens pending.server_commit
+        picks: u64,            // tiles opened before cash-out, as a bitmask like the layout
         payout: u64,
-        _ed25519_instr_index: u8,
     ) -> Result<()> {
+        require!(!ctx.accounts.game_state.house.flags.resolve_paused(pause::SOLE_GAME), MinesError::Paused);
+        let pending_key = ctx.accounts.pending.key();
+        let vault_key = ctx.accounts.vault.key();
+        let player_key = ctx.accounts.player.key();
+
+        // revealed seed must open the round's commitment; the mine layout follows from it
+        let pending = &ctx.accounts.pending;
+        require!(fair::is_reveal_of(&pending.server_commit, &server_seed), MinesError::BadReveal);
+        let outcome = fair::outcome_hash(&server_seed, player_key.as_ref(), &[], pending.nonce);
+        let layout = fair::mines_layout(&outcome, pending.rows * pending.cols, pending.mines);
+
+        // a registered resolver key signs directly, or the ed25519 run before this instruction
+        // holds registered signature(s) over the canonical message (see casino_common::cosign)
+        let admin_key = ctx.accounts.admin.key();
+        let epoch = Clock::get()?.epoch;
+        let resolvers = &mut ctx.accounts.resolver_set.cosigners;
+        if ctx.accounts.admin.is_signer && resolvers.contains(&admin_key) && !resolvers.requires_quorum(payout) {
+            resolvers.authorize_signer(&admin_key, payout, epoch)?;
+        } else {
+            // game body: bet_lamports | rows | cols | mines | layout | picks (see casino_common::message)
+            let expected = message::resolve_message(
+                DOMAIN_TAG,
+                ctx.program_id,
+                &vault_key,
+                &player_key,
+                &pending_key,
+                &[
+                    &pending.bet_lamports.to_le_bytes(),
+                    &[pending.rows, pending.cols, pending.mines],
+                    &layout.to_le_bytes(),
+                    &picks.to_le_bytes(),
+                ],
+                payout,
+                pending.nonce,
+                pending.expired_at,
+            );
+            cosign::require_signers(
+                &ctx.accounts.instructions.to_account_info(),
+                resolvers,
+                &expected,
+                payout,
+                epoch,
+            )?;
+        }
+
         let pending = &mut ctx.accounts.pending;
 
-        // light sanity checks aligned with backend
-        let expected = ((pending.nonce % 251) + 1) as u8;
-        require!(checksum == expected, MinesError::BadChecksum);
+        // rails: the signed payout must be the one the board gives
         require!(!pending.settled, MinesError::AlreadySettled);
         require!(pending.player == ctx.accounts.player.key(), MinesError::PlayerMismatch);
+        let cells = pending.rows * pending.cols;
+        let due = payout::mines(pending.bet_lamports, cells, pending.mines, layout, picks, pending.house_edge_bps)
+            .ok_or(MinesError::BadPayout)?;
+        require!(payout == due && due <= pending.exposure, MinesError::BadPayout);
 
         // enforce correct vault PDA so signer seeds match
         let (vault_pda, vault_bump) = Pubkey::find_program_address(&[b"vault"], ctx.program_id);
@@ -98,14 +435,230 @@ pub mod mines {
         }
 
         // mark and close (rent refunded to player by `close = player`)
+        record_outflow(&mut ctx.accounts.game_state, pending.bet_lamports, payout)?;
+        ctx.accounts.game_state.house.release(pending.exposure);
+        pending.settled = true;
+        Ok(())
+    }
+
+    /// Permissionless refund of an unresolved round once a deadline + grace has passed.
+    /// Accounts:
+    /// - player: writable (receives stake and pending rent)
+    /// - vault: PDA ["vault"] (program-owned, debited directly)
+    /// - pending: round pda (closed to player)
+    pub fn refund_expired(ctx: Context<RefundExpired>) -> Result<()> {
+        let pending = &ctx.accounts.pending;
+        require!(!pending.settled, MinesError::AlreadySettled);
+        let clock = Clock::get()?;
+        require!(
+            expiry::is_refundable(&clock, pending.expired_at, pending.expiry_slot, REFUND_GRACE_SECS, REFUND_GRACE_SLOTS),
+            MinesError::NotRefundable
+        );
+        let amount = pending.bet_lamports;
+
+        // vault is owned by this program, so lamports move without a system transfer
+        let vault_ai = ctx.accounts.vault.to_account_info();
+        let player_ai = ctx.accounts.player.to_account_info();
+        let vault_balance = vault_ai.lamports();
+        require!(vault_balance >= amount, MinesError::VaultMismatch);
+        **vault_ai.try_borrow_mut_lamports()? = vault_balance - amount;
+        **player_ai.try_borrow_mut_lamports()? = player_ai.lamports() + amount;
+
+        let pending = &mut ctx.accounts.pending;
+        ctx.accounts.game_state.house.release(pending.exposure);
         pending.settled = true;
+        emit!(RoundRefunded { player: pending.player, amount, nonce: pending.nonce });
         Ok(())
     }
 }
 
+#[event]
+pub struct RoundRefunded {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub nonce: u64,
+}
+
+#[event]
+pub struct RoleChangeProposed {
+    pub role: u8,
+    pub current: Pubkey,
+    pub proposed: Pubkey,
+    pub eta: i64,
+}
+
+#[event]
+pub struct RoleChangeCancelled {
+    pub role: u8,
+    pub proposed: Pubkey,
+}
+
+#[event]
+pub struct RoleChanged {
+    pub role: u8,
+    pub previous: Pubkey,
+    pub current: Pubkey,
+}
+
+#[event]
+pub struct GamePaused {
+    pub by: Pubkey,
+    pub halt_resolve: bool,
+    pub reason: u16,
+}
+
+#[event]
+pub struct GameUnpaused {
+    pub by: Pubkey,
+    pub reason: u16,
+}
+
+#[event]
+pub struct MaxPayoutChanged {
+    pub by: Pubkey,
+    pub previous_bps: u16,
+    pub current_bps: u16,
+}
+
+#[event]
+pub struct BreakerChanged {
+    pub by: Pubkey,
+    pub threshold: u64,
+    pub window_hours: u8,
+}
+
+#[event]
+pub struct BreakerTripped {
+    pub net_outflow: u64,
+    pub threshold: u64,
+    pub window_hours: u8,
+}
+
+#[event]
+pub struct ResolverSetChanged {
+    pub by: Pubkey,
+    pub keys: Vec<Pubkey>,
+    pub quorum: u8,
+    pub threshold: u64,
+    pub epoch_budget: u64,
+}
+
+#[event]
+pub struct ResolverKeyChanged {
+    pub by: Pubkey,
+    pub key: Pubkey,
+    pub enabled: bool,
+    pub epoch_budget: u64,
+}
+
+#[event]
+pub struct ResolverSetProposed {
+    pub by: Pubkey,
+    pub keys: Vec<Pubkey>,
+    pub quorum: u8,
+    pub threshold: u64,
+    pub epoch_budget: u64,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ResolverSetCancelled {
+    pub by: Pubkey,
+}
+
+#[event]
+pub struct ResolverSetApplied {
+    pub keys: Vec<Pubkey>,
+    pub quorum: u8,
+    pub threshold: u64,
+}
+
+#[event]
+pub struct ResolverKeyChangeProposed {
+    pub by: Pubkey,
+    pub key: Pubkey,
+    pub enabled: bool,
+    pub epoch_budget: u64,
+    pub eta: i64,
+}
+
+#[event]
+pub struct GameConfigChanged {
+    pub by: Pubkey,
+    pub version: u32,
+    pub enabled: bool,
+    pub limits: GameLimits,
+}
+
 #[account]
 pub struct Vault {} // Discriminator-only; holds lamports
 
+#[account]
+pub struct AdminConfig {
+    pub roles: Roles, // resolver, super-admin, treasurer, pauser, config-manager (+ pending changes)
+}
+impl AdminConfig {
+    pub const SIZE: usize = Roles::SIZE;
+}
+
+#[account]
+pub struct SeedCommit {
+    pub player: Pubkey,       // 32
+    pub commitment: [u8; 32], // 32 (sha256(server_seed); zero once consumed)
+    pub bump: u8,             // 1
+}
+impl SeedCommit {
+    pub const SIZE: usize = 32 + 32 + 1; // 65
+}
+
+/// Highest round nonce the player has locked; new locks must use a strictly larger one
+/// so a closed pending PDA can never be re-opened under an old nonce.
+#[account]
+pub struct PlayerNonce {
+    pub player: Pubkey,  // 32
+    pub last_nonce: u64, // 8
+    pub bump: u8,        // 1
+}
+impl PlayerNonce {
+    pub const SIZE: usize = 32 + 8 + 1; // 41
+}
+
+/// Incident switch read by every lock (and resolve). Expired refunds never check it.
+/// The outflow breaker throws both switches when it trips.
+#[account]
+pub struct GameState {
+    pub house: House,         // House::SIZE (pause bits, open exposure, payout share, outflow breaker)
+    pub bump: u8,             // 1
+}
+impl GameState {
+    pub const SIZE: usize = House::SIZE + 1;
+}
+
+/// Bet limits, house edge and on/off switch read by every lock (see casino_common::config).
+#[account]
+pub struct GameConfig {
+    pub enabled: bool,       // 1 (rejects new locks when false)
+    pub version: u32,        // 4 (counts updates)
+    pub limits: GameLimits,  // GameLimits::SIZE
+    pub bump: u8,            // 1
+}
+impl GameConfig {
+    pub const SIZE: usize = 1 + 4 + GameLimits::SIZE + 1;
+}
+
+/// Resolver key registry, one key per backend signer instance, each with an enabled flag and a
+/// per-epoch payout budget. A payout above the threshold needs K-of-N co-signatures, smaller
+/// payouts any one usable key (see casino_common::cosign).
+#[account]
+pub struct ResolverSet {
+    pub cosigners: Cosigners,       // Cosigners::SIZE
+    pub bump: u8,                   // 1
+    pub pending: PendingCosigners,  // PendingCosigners::SIZE
+}
+impl ResolverSet {
+    pub const SIZE: usize = Cosigners::SIZE + 1 + PendingCosigners::SIZE;
+}
+
 #[account]
 pub struct Pending {
     pub player: Pubkey,     // 32
@@ -115,10 +668,14 @@ pub struct Pending {
     pub mines: u8,          // 1
     pub nonce: u64,         // 8
     pub expired_at: i64,    // 8
+    pub server_commit: [u8; 32], // 32
+    pub expiry_slot: u64,   // 8 (0 = no slot deadline)
+    pub exposure: u64,      // 8 (worst-case payout reserved in GameState at lock)
+    pub house_edge_bps: u16, // 2 (config edge at lock; prices the cash-out)
     pub settled: bool,      // 1
 }
 impl Pending {
-    pub const SIZE: usize = 32 + 8 + 1 + 1 + 1 + 8 + 8 + 1; // 60
+    pub const SIZE: usize = 32 + 8 + 1 + 1 + 1 + 8 + 8 + 32 + 8 + 8 + 2 + 1; // 110
 }
 
 #[derive(Accounts)]
@@ -136,10 +693,185 @@ pub struct Initialize<'info> {
     pub vault: Account<'info, Vault>,
 
     pub system_program: Program<'info, System>,
+
+    /// CHECK: this program's ProgramData (upgrade-authority check); optional when the deployer key signs
+    pub program_data: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
-#[instruction(bet_lamports: u64, rows: u8, cols: u8, mines: u8, nonce: u64, expiry_unix: i64)]
+pub struct InitAdmin<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AdminConfig::SIZE,
+        seeds = [b"admin"],
+        bump
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: this program's ProgramData (upgrade-authority check); optional when the deployer key signs
+    pub program_data: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRole<'info> {
+    /// Current super-admin
+    pub authority: Signer<'info>,
+
+    #[account(mut, seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyRole<'info> {
+    /// Anyone may apply a proposed change once its timelock has elapsed
+    #[account(mut, seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitGameState<'info> {
+    /// Pauser or super-admin; pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(init, payer = authority, space = 8 + GameState::SIZE, seeds = [b"game_state"], bump)]
+    pub game_state: Account<'info, GameState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    /// Pauser or super-admin
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+}
+
+#[derive(Accounts)]
+pub struct SetLimits<'info> {
+    /// Config manager
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+}
+
+/// Read-only: simulate `bet_limits` to read the returned BetLimits
+#[derive(Accounts)]
+pub struct QueryLimits<'info> {
+    #[account(seeds = [b"vault"], bump)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+
+    #[account(seeds = [b"game_config"], bump = game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitGameConfig<'info> {
+    /// Config manager; pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(init, payer = authority, space = 8 + GameConfig::SIZE, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGameConfig<'info> {
+    /// Config manager
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut, seeds = [b"game_config"], bump = game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitResolverSet<'info> {
+    /// Super-admin; pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(init, payer = authority, space = 8 + ResolverSet::SIZE, seeds = [b"resolvers"], bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetResolverSet<'info> {
+    /// Super-admin (pauser may disable a key)
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut, seeds = [b"resolvers"], bump = resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyResolverSet<'info> {
+    /// Anyone may apply a pending resolver-set change once its timelock has elapsed
+    #[account(mut, seeds = [b"resolvers"], bump = resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+}
+
+#[derive(Accounts)]
+pub struct CommitSeed<'info> {
+    #[account(mut)]
+    pub resolver: Signer<'info>,
+
+    /// CHECK: only used as a PDA seed
+    pub player: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"admin"], bump)]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        space = 8 + SeedCommit::SIZE,
+        seeds = [b"seed", player.key().as_ref()],
+        bump
+    )]
+    pub seed_commit: Account<'info, SeedCommit>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_lamports: u64, rows: u8, cols: u8, mines: u8, nonce: u64, expiry_unix: i64, expiry_slot: u64)]
 pub struct Lock<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
@@ -162,6 +894,27 @@ pub struct Lock<'info> {
     pub pending: Account<'info, Pending>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(mut, seeds = [b"seed", player.key().as_ref()], bump = seed_commit.bump)]
+    pub seed_commit: Account<'info, SeedCommit>,
+
+    /// Per-player nonce high-water mark (created on the player's first round)
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerNonce::SIZE,
+        seeds = [b"nonce", player.key().as_ref()],
+        bump
+    )]
+    pub player_nonce: Account<'info, PlayerNonce>,
+
+    /// Pause switches
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+
+    /// Bet limits
+    #[account(seeds = [b"game_config"], bump = game_config.bump)]
+    pub game_config: Account<'info, GameConfig>,
 }
 
 #[derive(Accounts)]
@@ -172,7 +925,7 @@ pub struct Resolve<'info> {
     #[account(mut, seeds = [b"vault"], bump)]
     pub vault: Account<'info, Vault>,
 
-    /// CHECK: kept to match backend account array (unused in this instruction)
+    /// CHECK: resolver wallet; only trusted if it signs and is registered in resolver_set
     pub admin: UncheckedAccount<'info>,
 
     #[account(
@@ -186,9 +939,39 @@ pub struct Resolve<'info> {
 
     pub system_program: Program<'info, System>,
 
-    /// CHECK: instructions sysvar (for optional ed25519 enforcement)
+    /// CHECK: instructions sysvar (ed25519 attestation when `admin` does not sign)
     #[account(address = sysvar::instructions::ID)]
     pub instructions: UncheckedAccount<'info>,
+
+    /// Pause switches
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
+
+    /// Resolver key registry (charged with this payout)
+    #[account(mut, seeds = [b"resolvers"], bump = resolver_set.bump)]
+    pub resolver_set: Account<'info, ResolverSet>,
+}
+
+#[derive(Accounts)]
+pub struct RefundExpired<'info> {
+    #[account(mut)]
+    pub player: SystemAccount<'info>,
+
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        has_one = player,
+        close = player,
+        seeds = [b"round", player.key().as_ref(), &pending.nonce.to_le_bytes()],
+        bump
+    )]
+    pub pending: Account<'info, Pending>,
+
+    /// Open exposure is released here too
+    #[account(mut, seeds = [b"game_state"], bump = game_state.bump)]
+    pub game_state: Account<'info, GameState>,
 }
 
 #[error_code]
@@ -200,4 +983,40 @@ pub enum MinesError {
     #[msg("Round already settled")] AlreadySettled,
     #[msg("Pending player mismatch")] PlayerMismatch,
     #[msg("Vault PDA mismatch")] VaultMismatch,
+    #[msg("Resolver not authorized")] Unauthorized,
+    #[msg("No server seed commitment posted")] NoSeedCommit,
+    #[msg("Server seed does not open the commitment")] BadReveal,
+    #[msg("Round not yet refundable")] NotRefundable,
+    #[msg("Expiry outside the allowed window")] BadExpiry,
+    #[msg("Nonce must exceed the last one used")] StaleNonce,
+    #[msg("Only the upgrade authority or deployer may initialize")] NotDeployer,
+    #[msg("Game is paused")] Paused,
+    #[msg("Payout does not match the round's outcome")] BadPayout,
+    #[msg("Invalid config value")] BadConfig,
+    #[msg("Game is disabled")] GameDisabled,
 }
+
+/// Limits a new round must meet; fails while the game is disabled.
+fn lock_limits(cfg: &GameConfig) -> Result<GameLimits> {
+    require!(cfg.enabled, MinesError::GameDisabled);
+    Ok(cfg.limits)
+}
+
+/// The config's board side range must stay within 2..=8 (at most 64 cells).
+fn limits_supported(limits: &GameLimits) -> bool {
+    limits.is_valid() && limits.params_within(2, 8)
+}
+
+/// Feed a resolved round's stake and payout into the outflow breaker. If it trips, locks and
+/// resolves are paused (this resolve still settles) until a pauser unpauses.
+fn record_outflow(game_state: &mut GameState, staked: u64, paid: u64) -> Result<()> {
+    if let Some(net_outflow) = game_state.house.record_outflow(staked, paid)? {
+        emit!(BreakerTripped {
+            net_outflow,
+            threshold: game_state.house.breaker.threshold,
+            window_hours: game_state.house.breaker.window_hours,
+        });
+    }
+    Ok(())
+}
+
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+declare_id!("3VszGE37nGnEcxPjrTSai77v6w8SFrgEeZdfzB5gAA7S");
+
+// Fulfilled randomness = sha256(DOMAIN_TAG | seed), so local tests can predict every outcome.
+const DOMAIN_TAG: &[u8] = b"MOCK_ORACLE_V1";
+
+#[error_code]
+pub enum OracleError {
+    #[msg("Request already fulfilled")] AlreadyFulfilled,
+}
+
+#[account]
+pub struct RandomnessRequest {
+    pub requester: Pubkey,
+    pub seed: [u8; 32],
+    pub randomness: [u8; 32],
+    pub request_slot: u64,
+    pub fulfilled: bool,
+    pub bump: u8,
+}
+impl RandomnessRequest {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 1 + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(seed: [u8; 32])]
+pub struct RequestRandomness<'info> {
+    #[account(mut, signer)]
+    pub payer: SystemAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = RandomnessRequest::LEN,
+        seeds = [b"request", seed.as_ref()],
+        bump
+    )]
+    pub request: Account<'info, RandomnessRequest>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Fulfill<'info> {
+    #[account(mut, seeds = [b"request", request.seed.as_ref()], bump = request.bump)]
+    pub request: Account<'info, RandomnessRequest>,
+}
+
+#[event]
+pub struct RandomnessRequested {
+    pub request: Pubkey,
+    pub requester: Pubkey,
+    pub seed: [u8; 32],
+}
+
+#[event]
+pub struct RandomnessFulfilled {
+    pub request: Pubkey,
+    pub randomness: [u8; 32],
+}
+
+#[program]
+pub mod mock_oracle {
+    use super::*;
+
+    /// Open a request; game programs call this via CPI at lock time with a per-bet seed.
+    pub fn request_randomness(ctx: Context<RequestRandomness>, seed: [u8; 32]) -> Result<()> {
+        let r = &mut ctx.accounts.request;
+        r.requester = ctx.accounts.payer.key();
+        r.seed = seed;
+        r.randomness = [0u8; 32];
+        r.request_slot = Clock::get()?.slot;
+        r.fulfilled = false;
+        r.bump = ctx.bumps.request;
+
+        emit!(RandomnessRequested { request: r.key(), requester: r.requester, seed });
+        Ok(())
+    }
+
+    /// Permissionless: anyone may fulfil, the value is fixed by the seed.
+    pub fn fulfill(ctx: Context<Fulfill>) -> Result<()> {
+        let r = &mut ctx.accounts.request;
+        require!(!r.fulfilled, OracleError::AlreadyFulfilled);
+        r.randomness = hashv(&[DOMAIN_TAG, &r.seed]).to_bytes();
+        r.fulfilled = true;
+
+        emit!(RandomnessFulfilled { request: r.key(), randomness: r.randomness });
+        Ok(())
+    }
+}
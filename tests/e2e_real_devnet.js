@@ -24,6 +24,10 @@ const RPC =
   "https://api.devnet.solana.com";
 const WALLET = process.env.ANCHOR_WALLET || path.join(process.env.HOME, ".config/solana/id.json");
 const PROGRAM_ID = new PublicKey(process.env.PROGRAM_ID);
+// Set to the oracle program id to take randomness from the oracle instead of commit_seed; the
+// script switches the game config over with set_randomness. mock_oracle is only accepted by
+// programs built with `--features localnet`.
+const ORACLE_PROGRAM_ID = process.env.ORACLE_PROGRAM_ID ? new PublicKey(process.env.ORACLE_PROGRAM_ID) : null;
 
 // Bet params (override with env if you want)
 const BET_AMOUNT_LAMPORTS = Number(process.env.BET_AMOUNT_LAMPORTS || 1_000_000); // 0.001 SOL
@@ -35,16 +39,20 @@ const EXPIRY_SECS = Number(process.env.NONCE_TTL_SECONDS || 300);
 // ---------- Helpers ----------
 const disc = (name) => crypto.createHash("sha256").update(`global:${name}`).digest().slice(0, 8);
 const u64le = (n) => { const b = Buffer.alloc(8); b.writeBigUInt64LE(BigInt(n)); return b; };
+const u32le = (n) => { const b = Buffer.alloc(4); b.writeUInt32LE(n); return b; };
+const u16le = (n) => { const b = Buffer.alloc(2); b.writeUInt16LE(n); return b; };
 const i64le = (n) => { const b = Buffer.alloc(8); b.writeBigInt64LE(BigInt(n)); return b; };
 const nonceBufLE = (n) => { const b = Buffer.alloc(8); b.writeBigUInt64LE(BigInt(n)); return b; };
+const sha256 = (...parts) => crypto.createHash("sha256").update(Buffer.concat(parts)).digest();
 const loadKp = (p) => Keypair.fromSecretKey(Uint8Array.from(JSON.parse(fs.readFileSync(p, "utf8"))));
 
-function canonicalMessage({ programId, vault, player, betAmount, betType, target, roll, payout, nonce, expiryUnix }) {
+function canonicalMessage({ programId, vault, player, pendingBet, betAmount, betType, target, roll, payout, nonce, expiryUnix }) {
   const parts = [];
   parts.push(Buffer.from("DICE_V1"));
   parts.push(Buffer.from(programId)); // 32
   parts.push(Buffer.from(vault));     // 32
   parts.push(Buffer.from(player));    // 32
+  parts.push(Buffer.from(pendingBet)); // 32
   parts.push(u64le(betAmount));
   parts.push(Buffer.from([betType & 0xff]));
   parts.push(Buffer.from([target & 0xff]));
@@ -55,6 +63,15 @@ function canonicalMessage({ programId, vault, player, betAmount, betType, target
   return Buffer.concat(parts);
 }
 
+// Mirrors casino_common::fair: outcome = sha256(server_seed | client_seed | slot_hash | nonce_le),
+// roll = (first 8 bytes LE of sha256(outcome | 0u32_le)) % 100 + 1
+function fairDiceRoll(serverSeed, clientSeed, slotHash, nonce) {
+  const outcome = sha256(serverSeed, clientSeed, slotHash, u64le(nonce));
+  const u32zero = Buffer.alloc(4);
+  const draw = sha256(outcome, u32zero).readBigUInt64LE(0);
+  return Number(draw % 100n) + 1;
+}
+
 function adminSecret64FromEnv() {
   const b = bs58.decode(process.env.ADMIN_PRIVKEY_BASE58 || "");
   if (b.length === 64) return Uint8Array.from(b);
@@ -90,19 +107,28 @@ async function sendTx(conn, kp, instructions, label) {
 
   const [vault] = PublicKey.findProgramAddressSync([Buffer.from("vault")], PROGRAM_ID);
   const [adminPda] = PublicKey.findProgramAddressSync([Buffer.from("admin")], PROGRAM_ID);
+  const [seedPda] = PublicKey.findProgramAddressSync([Buffer.from("seed"), player.publicKey.toBuffer()], PROGRAM_ID);
+  const [playerNoncePda] = PublicKey.findProgramAddressSync([Buffer.from("nonce"), player.publicKey.toBuffer()], PROGRAM_ID);
+  const [gameStatePda] = PublicKey.findProgramAddressSync([Buffer.from("game_state")], PROGRAM_ID);
+  const [resolverSetPda] = PublicKey.findProgramAddressSync([Buffer.from("resolvers")], PROGRAM_ID);
+  const [gameConfigPda] = PublicKey.findProgramAddressSync([Buffer.from("game_config")], PROGRAM_ID);
 
   console.log("Program ID :", PROGRAM_ID.toBase58());
   console.log("Player     :", player.publicKey.toBase58());
   console.log("Vault PDA  :", vault.toBase58());
   console.log("Admin PDA  :", adminPda.toBase58());
 
-  // --- Ensure PDAs are initialized (init_vault + init_admin) ---
+  // --- Ensure PDAs are initialized (init_vault + init_admin + init_game_state) ---
+  // initializers only accept the deployer key or the upgrade authority (checked via ProgramData)
+  const BPF_LOADER_UPGRADEABLE = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+  const [programData] = PublicKey.findProgramAddressSync([PROGRAM_ID.toBuffer()], BPF_LOADER_UPGRADEABLE);
   const ixVault = new TransactionInstruction({
     programId: PROGRAM_ID,
     keys: [
       { pubkey: player.publicKey, isSigner: true, isWritable: true },
       { pubkey: vault, isSigner: false, isWritable: true },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: programData, isSigner: false, isWritable: false },
     ],
     data: disc("init_vault"),
   });
@@ -114,20 +140,100 @@ async function sendTx(conn, kp, instructions, label) {
       { pubkey: player.publicKey, isSigner: true, isWritable: true }, // authority
       { pubkey: adminPda, isSigner: false, isWritable: true },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: programData, isSigner: false, isWritable: false },
     ],
     data: Buffer.concat([disc("init_admin"), adminPk]),
   });
 
+  // init_admin makes the signer super-admin (and pauser), which may create the pause switches
+  const ixGameState = new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [
+      { pubkey: player.publicKey, isSigner: true, isWritable: true }, // authority
+      { pubkey: adminPda, isSigner: false, isWritable: false },
+      { pubkey: gameStatePda, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: disc("init_game_state"),
+  });
+
   // try combined, then fall back silently
-  try { await sendTx(conn, player, [ixVault, ixAdmin], "init_vault + init_admin"); }
+  try { await sendTx(conn, player, [ixVault, ixAdmin, ixGameState], "init_vault + init_admin + init_game_state"); }
   catch (e) {
     const s = String(e.message || e);
     if (!s.includes("already in use")) {
       try { await sendTx(conn, player, [ixVault], "init_vault"); } catch {}
       try { await sendTx(conn, player, [ixAdmin], "init_admin"); } catch {}
     }
+    try { await sendTx(conn, player, [ixGameState], "init_game_state"); } catch {}
   }
 
+  // single-key resolver registry (no epoch budget): the resolver alone (quorum 1) settles any payout
+  const ixResolverSet = new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [
+      { pubkey: player.publicKey, isSigner: true, isWritable: true }, // super-admin
+      { pubkey: adminPda, isSigner: false, isWritable: false },
+      { pubkey: resolverSetPda, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: Buffer.concat([disc("init_resolver_set"), u32le(1), adminPk, Buffer.from([1]), u64le(0), u64le(0)]),
+  });
+  try { await sendTx(conn, player, [ixResolverSet], "init_resolver_set"); } catch {}
+
+  // enabled game config (init_admin also made the signer config manager):
+  // 0.00005..5 SOL stakes, edge matching RTP_BPS, targets 2..98
+  const gameLimits = Buffer.concat([
+    u64le(50_000), u64le(5_000_000_000), u16le(10_000 - RTP_BPS), u32le(2), u32le(98), u16le(0),
+  ]);
+  const ixGameConfig = new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [
+      { pubkey: player.publicKey, isSigner: true, isWritable: true }, // config manager
+      { pubkey: adminPda, isSigner: false, isWritable: false },
+      { pubkey: gameConfigPda, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: Buffer.concat([disc("init_game_config"), Buffer.from([1]), gameLimits]),
+  });
+  try { await sendTx(conn, player, [ixGameConfig], "init_game_config"); } catch {}
+
+  // randomness source (super admin): mode 1 = oracle, 0 = backend commit/reveal
+  const ixRandomness = new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [
+      { pubkey: player.publicKey, isSigner: true, isWritable: false }, // super admin
+      { pubkey: adminPda, isSigner: false, isWritable: false },
+      { pubkey: gameConfigPda, isSigner: false, isWritable: true },
+    ],
+    data: Buffer.concat([
+      disc("set_randomness"),
+      Buffer.from([ORACLE_PROGRAM_ID ? 1 : 0]),
+      ORACLE_PROGRAM_ID ? ORACLE_PROGRAM_ID.toBuffer() : Buffer.alloc(32),
+    ]),
+  });
+  await sendTx(conn, player, [ixRandomness], "set_randomness");
+
+  // --- Step 0: resolver commits sha256(server_seed) for the player's next bet (backend mode) ---
+  const adminSecret64 = adminSecret64FromEnv();
+  const resolver = Keypair.fromSecretKey(adminSecret64);
+  await airdropIfNeeded(conn, resolver.publicKey, 10_000_000); // seed PDA rent + fees
+
+  // oracle mode reveals nothing: server_seed is sent as zeroes
+  const serverSeed = ORACLE_PROGRAM_ID ? Buffer.alloc(32) : crypto.randomBytes(32);
+  const ixCommit = new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [
+      { pubkey: resolver.publicKey, isSigner: true, isWritable: true },
+      { pubkey: player.publicKey, isSigner: false, isWritable: false },
+      { pubkey: adminPda, isSigner: false, isWritable: false },
+      { pubkey: seedPda, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: Buffer.concat([disc("commit_seed"), sha256(serverSeed)]),
+  });
+  if (!ORACLE_PROGRAM_ID) await sendTx(conn, resolver, [ixCommit], "commit_seed");
+
   // --- Step 1: place_bet_lock ---
   if (TARGET_NUMBER < 2 || TARGET_NUMBER > 98) throw new Error("TARGET_NUMBER must be 2..98");
   if (BET_TYPE_NUM !== 0 && BET_TYPE_NUM !== 1) throw new Error("BET_TYPE_NUM must be 0 or 1");
@@ -141,6 +247,8 @@ async function sendTx(conn, kp, instructions, label) {
   )[0];
 
   const preBal = await conn.getBalance(player.publicKey, "confirmed");
+  const clientSeed = crypto.randomBytes(32);
+  const SYSVAR_SLOT_HASHES = new PublicKey("SysvarS1otHashes111111111111111111111111111");
 
   const dataLock = Buffer.concat([
     disc("place_bet_lock"),
@@ -149,31 +257,67 @@ async function sendTx(conn, kp, instructions, label) {
     Buffer.from([TARGET_NUMBER & 0xff]),
     u64le(nonce),
     i64le(expiryUnix),
+    clientSeed,
+    u64le(0), // expiry_slot: unix deadline only
   ]);
 
-  const ixLock = new TransactionInstruction({
-    programId: PROGRAM_ID,
-    keys: [
-      { pubkey: player.publicKey, isSigner: true, isWritable: true },
-      { pubkey: vault, isSigner: false, isWritable: true },
-      { pubkey: pendingBetPda, isSigner: false, isWritable: true },
-      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-    ],
-    data: dataLock,
-  });
+  // oracle request PDA is seeded by the pending bet address
+  const requestPda = ORACLE_PROGRAM_ID
+    ? PublicKey.findProgramAddressSync([Buffer.from("request"), pendingBetPda.toBuffer()], ORACLE_PROGRAM_ID)[0]
+    : null;
+
+  const lockKeys = [
+    { pubkey: player.publicKey, isSigner: true, isWritable: true },
+    { pubkey: vault, isSigner: false, isWritable: true },
+    { pubkey: pendingBetPda, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    // optional seed_commit: the program id stands in for "none" in oracle mode
+    { pubkey: ORACLE_PROGRAM_ID ? PROGRAM_ID : seedPda, isSigner: false, isWritable: !ORACLE_PROGRAM_ID },
+    { pubkey: SYSVAR_SLOT_HASHES, isSigner: false, isWritable: false },
+    // per-player nonce high-water mark; Date.now() nonces keep increasing across runs
+    { pubkey: playerNoncePda, isSigner: false, isWritable: true },
+    { pubkey: gameStatePda, isSigner: false, isWritable: true },
+    { pubkey: gameConfigPda, isSigner: false, isWritable: false },
+  ];
+  if (ORACLE_PROGRAM_ID) {
+    lockKeys.push(
+      { pubkey: ORACLE_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: requestPda, isSigner: false, isWritable: true },
+    );
+  }
+  const ixLock = new TransactionInstruction({ programId: PROGRAM_ID, keys: lockKeys, data: dataLock });
 
   await sendTx(conn, player, [ixLock], "place_bet_lock");
 
-  // --- Step 2: resolve_bet (with ed25519 pre-instruction signed by backend) ---
-  const roll = crypto.randomInt(1, 101);
+  // mock_oracle fulfils permissionlessly with sha256("MOCK_ORACLE_V1" | seed)
+  let betSeed = serverSeed;
+  if (ORACLE_PROGRAM_ID) {
+    const ixFulfill = new TransactionInstruction({
+      programId: ORACLE_PROGRAM_ID,
+      keys: [{ pubkey: requestPda, isSigner: false, isWritable: true }],
+      data: disc("fulfill"),
+    });
+    await sendTx(conn, player, [ixFulfill], "oracle fulfill");
+    betSeed = sha256(Buffer.from("MOCK_ORACLE_V1"), pendingBetPda.toBuffer());
+  }
+
+  // --- Step 2: resolve_bet (reveal server seed + ed25519 pre-instruction signed by backend) ---
+  // PendingBet: disc 8 | player 32 | amount 8 | bet_type 1 | target 1 | nonce 8 | expiry 8 |
+  //             server_commit 32 | client_seed 32 | slot_hash 32 | oracle_request 32 |
+  //             expiry_slot 8 | exposure 8 | house_edge_bps 2 | settled 1
+  const pendingInfo = await conn.getAccountInfo(pendingBetPda, "confirmed");
+  const slotHash = pendingInfo.data.subarray(130, 162);
+  const roll = fairDiceRoll(betSeed, clientSeed, slotHash, nonce);
   const odds = BET_TYPE_NUM === 0 ? (TARGET_NUMBER - 1) : (100 - TARGET_NUMBER);
   const win = BET_TYPE_NUM === 0 ? (roll < TARGET_NUMBER) : (roll > TARGET_NUMBER);
+  // must equal the program's own derivation (casino_common::payout::dice), edge = 1 - RTP
   const payout = win ? Number((BigInt(BET_AMOUNT_LAMPORTS) * BigInt(RTP_BPS)) / (100n * BigInt(odds))) : 0;
 
   const message = canonicalMessage({
     programId: PROGRAM_ID.toBuffer(),
     vault: vault.toBuffer(),
     player: player.publicKey.toBuffer(),
+    pendingBet: pendingBetPda.toBuffer(),
     betAmount: BET_AMOUNT_LAMPORTS,
     betType: BET_TYPE_NUM,
     target: TARGET_NUMBER,
@@ -183,7 +327,6 @@ async function sendTx(conn, kp, instructions, label) {
     expiryUnix,
   });
 
-  const adminSecret64 = adminSecret64FromEnv();
   const adminSig = nacl.sign.detached(Uint8Array.from(message), adminSecret64);
   const adminPub = adminSecret64.slice(32);
 
@@ -192,28 +335,25 @@ async function sendTx(conn, kp, instructions, label) {
     message: Buffer.from(message),
     signature: Buffer.from(adminSig),
   });
-  const edIndex = 1; // we put ComputeBudget at [0], ed25519 at [1]
 
   const SYSVAR_INSTRUCTIONS = new PublicKey("Sysvar1nstructions1111111111111111111111111");
   const dataResolve = Buffer.concat([
     disc("resolve_bet"),
-    Buffer.from([roll & 0xff]),
+    serverSeed,
     u64le(payout),
-    Buffer.from([edIndex & 0xff]),
   ]);
 
-  const ixResolve = new TransactionInstruction({
-    programId: PROGRAM_ID,
-    keys: [
-      { pubkey: player.publicKey, isSigner: false, isWritable: true },
-      { pubkey: vault, isSigner: false, isWritable: true },
-      { pubkey: adminPda, isSigner: false, isWritable: false },
-      { pubkey: pendingBetPda, isSigner: false, isWritable: true },
-      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-      { pubkey: SYSVAR_INSTRUCTIONS, isSigner: false, isWritable: false },
-    ],
-    data: dataResolve,
-  });
+  const resolveKeys = [
+    { pubkey: player.publicKey, isSigner: false, isWritable: true },
+    { pubkey: vault, isSigner: false, isWritable: true },
+    { pubkey: pendingBetPda, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_INSTRUCTIONS, isSigner: false, isWritable: false },
+    { pubkey: gameStatePda, isSigner: false, isWritable: true },
+    { pubkey: resolverSetPda, isSigner: false, isWritable: true },
+  ];
+  if (ORACLE_PROGRAM_ID) resolveKeys.push({ pubkey: requestPda, isSigner: false, isWritable: false });
+  const ixResolve = new TransactionInstruction({ programId: PROGRAM_ID, keys: resolveKeys, data: dataResolve });
 
   await sendTx(conn, player, [edIx, ixResolve], "resolve_bet");
 
@@ -34,7 +34,7 @@ const path = require("path");
     console.log("House vault PDA:", houseVault.toBase58());
 
     // --- destination (where you want to receive SOL) ---
-    // you can change this to any address you want
+    // must be on the treasury allow-list (add_destination, usable 48h after adding)
     const destination = adminKeypair.publicKey;
     console.log("Destination:", destination.toBase58());
 
@@ -53,18 +53,67 @@ const path = require("path");
 
     const amount = new anchor.BN(amountLamports);
 
-    // --- call house_withdraw ---
+    // --- house withdrawals go through the M-of-N multisig: [b"multisig"], [b"withdraw", id] ---
+    const [multisig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("multisig")],
+      program.programId
+    );
+    const [treasury] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury")],
+      program.programId
+    );
+    const ms = await program.account.multisig.fetch(multisig);
+    const id = ms.nextProposal;
+    const [proposal] = PublicKey.findProgramAddressSync(
+      [Buffer.from("withdraw"), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    console.log(`Multisig ${ms.threshold}-of-${ms.ownerCount}, proposal #${id.toString()}:`, proposal.toBase58());
+
+    // --- propose (counts as the proposer's approval) ---
+    const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+    const proposeSig = await program.methods
+      .proposeHouseWithdraw({ amount, destination, expiresAt })
+      .accounts({
+        proposer: adminKeypair.publicKey,
+        multisig,
+        proposal,
+        systemProgram: SystemProgram.programId,
+        treasury,
+      })
+      .signers([adminKeypair])
+      .rpc();
+    console.log("proposed, tx:", proposeSig);
+
+    if (ms.threshold > 1) {
+      console.log(
+        `Needs ${ms.threshold - 1} more approval(s) (approve_house_withdraw) before house_withdraw can execute.`
+      );
+      return;
+    }
+
+    // --- execute once the threshold is reached ---
     const txSig = await program.methods
-      .houseWithdraw({ amount })
+      .houseWithdraw()
       .accounts({
-        admin: adminKeypair.publicKey,
+        owner: adminKeypair.publicKey,
+        multisig,
+        proposal,
+        proposer: adminKeypair.publicKey,
         houseVault,
         destination,
         systemProgram: SystemProgram.programId,
+        treasury,
       })
-      .signers([adminKeypair]) // IMPORTANT: admin signs
+      .signers([adminKeypair]) // IMPORTANT: a multisig owner signs
       .rpc();
 
+    // over the rolling 24h limit the proposal is queued instead (re-run house_withdraw after release_at)
+    const queued = await program.account.withdrawProposal.fetchNullable(proposal);
+    if (queued) {
+      console.log("⏳ over the daily limit, queued until", queued.releaseAt.toString(), "tx:", txSig);
+      return;
+    }
     console.log("✅ house_withdraw success, tx:", txSig);
   } catch (err) {
     console.error("❌ house_withdraw failed:");
@@ -0,0 +1,45 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+
+const { Keypair, LAMPORTS_PER_SOL, PublicKey } = anchor.web3;
+
+// Initializers may only be run by the upgrade authority (via ProgramData) or the
+// compile-time deployer key; a random signer must not be able to claim the admin config.
+const BPF_LOADER_UPGRADEABLE = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+
+const PROGRAMS = ["dice", "crashProgram", "plinko", "dicemines", "allgame", "mines", "coinflip"];
+
+describe("init guard", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const stranger = Keypair.generate();
+
+  before(async () => {
+    const sig = await provider.connection.requestAirdrop(stranger.publicKey, 2 * LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig, "confirmed");
+  });
+
+  for (const name of PROGRAMS) {
+    it(`${name}: random signer cannot init_admin`, async () => {
+      const program = anchor.workspace[name] as Program;
+      const [programData] = PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        BPF_LOADER_UPGRADEABLE
+      );
+
+      try {
+        await program.methods
+          .initAdmin(Array.from(stranger.publicKey.toBytes()))
+          .accountsPartial({ authority: stranger.publicKey, programData })
+          .signers([stranger])
+          .rpc();
+        assert.fail("init_admin accepted a signer that is neither upgrade authority nor deployer");
+      } catch (err) {
+        assert.instanceOf(err, anchor.AnchorError);
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "NotDeployer");
+      }
+    });
+  }
+});
@@ -0,0 +1,269 @@
+// tests/oracle_mock_localnet.js
+// Oracle-mode round trip on a local validator: dice built with `--features localnet` and
+// mock_oracle deployed. Switches the dice config to mock_oracle, locks a bet (which CPIs
+// request_randomness), checks that resolve is refused before `fulfill` and with a payout other
+// than the derived one, then fulfils and resolves.
+//
+//   anchor build -p dice -- --features localnet && anchor build -p mock_oracle
+//   solana-test-validator --bpf-program <dice id> target/deploy/dice.so \
+//                         --bpf-program 3VszGE37nGnEcxPjrTSai77v6w8SFrgEeZdfzB5gAA7S target/deploy/mock_oracle.so
+//   PROGRAM_ID=<dice id> ADMIN_PUBKEY_BASE58=... ADMIN_PRIVKEY_BASE58=... node tests/oracle_mock_localnet.js
+require("dotenv").config();
+const assert = require("assert");
+const fs = require("fs");
+const path = require("path");
+const crypto = require("crypto");
+const bs58 = require("bs58");
+const nacl = require("tweetnacl");
+const {
+  Connection,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+  ComputeBudgetProgram,
+  Ed25519Program,
+  sendAndConfirmTransaction,
+} = require("@solana/web3.js");
+
+// ---------- Config ----------
+const RPC = process.env.ANCHOR_PROVIDER_URL || "http://127.0.0.1:8899";
+const WALLET = process.env.ANCHOR_WALLET || path.join(process.env.HOME, ".config/solana/id.json");
+const PROGRAM_ID = new PublicKey(process.env.PROGRAM_ID);
+const MOCK_ORACLE_ID = new PublicKey("3VszGE37nGnEcxPjrTSai77v6w8SFrgEeZdfzB5gAA7S");
+
+const BET_AMOUNT_LAMPORTS = 1_000_000; // 0.001 SOL
+const BET_TYPE_NUM = 1; // over
+const TARGET_NUMBER = 50;
+const RTP_BPS = 9900;
+const EXPIRY_SECS = 300;
+
+// ---------- Helpers ----------
+const disc = (name) => crypto.createHash("sha256").update(`global:${name}`).digest().slice(0, 8);
+const u64le = (n) => { const b = Buffer.alloc(8); b.writeBigUInt64LE(BigInt(n)); return b; };
+const u32le = (n) => { const b = Buffer.alloc(4); b.writeUInt32LE(n); return b; };
+const u16le = (n) => { const b = Buffer.alloc(2); b.writeUInt16LE(n); return b; };
+const i64le = (n) => { const b = Buffer.alloc(8); b.writeBigInt64LE(BigInt(n)); return b; };
+const sha256 = (...parts) => crypto.createHash("sha256").update(Buffer.concat(parts)).digest();
+const loadKp = (p) => Keypair.fromSecretKey(Uint8Array.from(JSON.parse(fs.readFileSync(p, "utf8"))));
+
+// DICE_V1 resolve message (see casino_common::message)
+function canonicalMessage({ vault, player, pendingBet, roll, payout, nonce, expiryUnix }) {
+  return Buffer.concat([
+    Buffer.from("DICE_V1"),
+    PROGRAM_ID.toBuffer(),
+    vault.toBuffer(),
+    player.toBuffer(),
+    pendingBet.toBuffer(),
+    u64le(BET_AMOUNT_LAMPORTS),
+    Buffer.from([BET_TYPE_NUM, TARGET_NUMBER, roll]),
+    u64le(payout),
+    u64le(nonce),
+    i64le(expiryUnix),
+  ]);
+}
+
+// casino_common::fair::dice_roll of outcome_hash(seed, client_seed, slot_hash, nonce)
+function fairDiceRoll(seed, clientSeed, slotHash, nonce) {
+  const outcome = sha256(seed, clientSeed, slotHash, u64le(nonce));
+  return Number(sha256(outcome, Buffer.alloc(4)).readBigUInt64LE(0) % 100n) + 1;
+}
+
+// casino_common::payout::dice with edge = 1 - RTP
+function dicePayout(roll) {
+  const win = BET_TYPE_NUM === 0 ? roll < TARGET_NUMBER : roll > TARGET_NUMBER;
+  const odds = BET_TYPE_NUM === 0 ? TARGET_NUMBER - 1 : 100 - TARGET_NUMBER;
+  return win ? Number((BigInt(BET_AMOUNT_LAMPORTS) * BigInt(RTP_BPS)) / (100n * BigInt(odds))) : 0;
+}
+
+function adminSecret64FromEnv() {
+  const b = bs58.decode(process.env.ADMIN_PRIVKEY_BASE58 || "");
+  if (b.length === 64) return Uint8Array.from(b);
+  if (b.length === 32) return nacl.sign.keyPair.fromSeed(Uint8Array.from(b)).secretKey;
+  throw new Error("ADMIN_PRIVKEY_BASE58 must decode to 32 or 64 bytes");
+}
+
+async function airdropIfNeeded(conn, pubkey, minLamports) {
+  const bal = await conn.getBalance(pubkey, "confirmed");
+  if (bal >= minLamports) return;
+  const sig = await conn.requestAirdrop(pubkey, 2_000_000_000);
+  await conn.confirmTransaction(sig, "confirmed");
+}
+
+async function sendTx(conn, kp, instructions, label) {
+  const tx = new Transaction();
+  tx.add(ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }), ...instructions);
+  tx.feePayer = kp.publicKey;
+  const sig = await sendAndConfirmTransaction(conn, tx, [kp], { commitment: "confirmed" });
+  console.log(`✅ ${label}: ${sig}`);
+  return sig;
+}
+
+// The transaction must fail with Anchor error `code` (matched against the program logs)
+async function expectFail(conn, kp, instructions, label, code) {
+  try {
+    await sendTx(conn, kp, instructions, label);
+  } catch (e) {
+    const logs = (e.logs || (e.transactionLogs || [])).join("\n") + String(e.message || e);
+    assert(logs.includes(code), `${label}: expected ${code}, got\n${logs}`);
+    console.log(`✅ ${label} refused with ${code}`);
+    return;
+  }
+  throw new Error(`${label} should have failed with ${code}`);
+}
+
+(async () => {
+  if (!process.env.PROGRAM_ID) throw new Error("PROGRAM_ID missing");
+  if (!process.env.ADMIN_PUBKEY_BASE58) throw new Error("ADMIN_PUBKEY_BASE58 missing");
+
+  const conn = new Connection(RPC, "confirmed");
+  const player = loadKp(WALLET);
+  const adminSecret64 = adminSecret64FromEnv();
+  const adminPk = Buffer.from(bs58.decode(process.env.ADMIN_PUBKEY_BASE58));
+  await airdropIfNeeded(conn, player.publicKey, 1_000_000_000);
+
+  const pda = (...seeds) => PublicKey.findProgramAddressSync(seeds, PROGRAM_ID)[0];
+  const vault = pda(Buffer.from("vault"));
+  const adminPda = pda(Buffer.from("admin"));
+  const playerNoncePda = pda(Buffer.from("nonce"), player.publicKey.toBuffer());
+  const gameStatePda = pda(Buffer.from("game_state"));
+  const resolverSetPda = pda(Buffer.from("resolvers"));
+  const gameConfigPda = pda(Buffer.from("game_config"));
+  const BPF_LOADER_UPGRADEABLE = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+  const programData = PublicKey.findProgramAddressSync([PROGRAM_ID.toBuffer()], BPF_LOADER_UPGRADEABLE)[0];
+  const SYSVAR_SLOT_HASHES = new PublicKey("SysvarS1otHashes111111111111111111111111111");
+  const SYSVAR_INSTRUCTIONS = new PublicKey("Sysvar1nstructions1111111111111111111111111");
+  const ix = (data, keys, programId = PROGRAM_ID) => new TransactionInstruction({ programId, keys, data });
+  const acc = (pubkey, isWritable = false, isSigner = false) => ({ pubkey, isSigner, isWritable });
+  const signer = acc(player.publicKey, true, true);
+
+  // --- Setup (each step is a no-op when the account already exists) ---
+  const limits = Buffer.concat([
+    u64le(50_000), u64le(5_000_000_000), u16le(10_000 - RTP_BPS), u32le(2), u32le(98), u16le(0),
+  ]);
+  const setup = [
+    ["init_vault", ix(disc("init_vault"), [signer, acc(vault, true), acc(SystemProgram.programId), acc(programData)])],
+    ["init_admin", ix(Buffer.concat([disc("init_admin"), adminPk]), [signer, acc(adminPda, true), acc(SystemProgram.programId), acc(programData)])],
+    ["init_game_state", ix(disc("init_game_state"), [signer, acc(adminPda), acc(gameStatePda, true), acc(SystemProgram.programId)])],
+    ["init_resolver_set", ix(
+      Buffer.concat([disc("init_resolver_set"), u32le(1), adminPk, Buffer.from([1]), u64le(0), u64le(0)]),
+      [signer, acc(adminPda), acc(resolverSetPda, true), acc(SystemProgram.programId)],
+    )],
+    ["init_game_config", ix(
+      Buffer.concat([disc("init_game_config"), Buffer.from([1]), limits]),
+      [signer, acc(adminPda), acc(gameConfigPda, true), acc(SystemProgram.programId)],
+    )],
+  ];
+  for (const [label, i] of setup) {
+    try { await sendTx(conn, player, [i], label); } catch {}
+  }
+  // a rerun may find the vault drained below the reserve; top it up
+  await sendTx(conn, player, [SystemProgram.transfer({ fromPubkey: player.publicKey, toPubkey: vault, lamports: 100_000_000 })], "fund vault");
+
+  const setRandomness = (mode, oracle) => ix(
+    Buffer.concat([disc("set_randomness"), Buffer.from([mode]), oracle.toBuffer()]),
+    [signer, acc(adminPda), acc(gameConfigPda, true)],
+  );
+
+  // --- 1. only allow-listed oracles are accepted ---
+  await expectFail(conn, player, [setRandomness(1, Keypair.generate().publicKey)], "set_randomness(unlisted oracle)", "BadParams");
+  await sendTx(conn, player, [setRandomness(1, MOCK_ORACLE_ID)], "set_randomness(mock_oracle)");
+
+  // --- 2. lock: the program CPIs request_randomness seeded by the pending bet ---
+  const nonce = BigInt(Date.now());
+  const expiryUnix = Math.floor(Date.now() / 1000) + EXPIRY_SECS;
+  const pendingBetPda = pda(Buffer.from("bet"), player.publicKey.toBuffer(), u64le(nonce));
+  const requestPda = PublicKey.findProgramAddressSync([Buffer.from("request"), pendingBetPda.toBuffer()], MOCK_ORACLE_ID)[0];
+  const clientSeed = crypto.randomBytes(32);
+
+  const ixLock = ix(
+    Buffer.concat([
+      disc("place_bet_lock"),
+      u64le(BET_AMOUNT_LAMPORTS),
+      Buffer.from([BET_TYPE_NUM, TARGET_NUMBER]),
+      u64le(nonce),
+      i64le(expiryUnix),
+      clientSeed,
+      u64le(0), // expiry_slot: unix deadline only
+    ]),
+    [
+      signer,
+      acc(vault, true),
+      acc(pendingBetPda, true),
+      acc(SystemProgram.programId),
+      acc(PROGRAM_ID), // no seed_commit in oracle mode
+      acc(SYSVAR_SLOT_HASHES),
+      acc(playerNoncePda, true),
+      acc(gameStatePda, true),
+      acc(gameConfigPda),
+      acc(MOCK_ORACLE_ID),
+      acc(requestPda, true),
+    ],
+  );
+  await sendTx(conn, player, [ixLock], "place_bet_lock");
+  const request = await conn.getAccountInfo(requestPda, "confirmed");
+  assert(request && request.owner.equals(MOCK_ORACLE_ID), "lock did not open the oracle request");
+
+  // PendingBet: disc 8 | player 32 | amount 8 | bet_type 1 | target 1 | nonce 8 | expiry 8 |
+  //             server_commit 32 | client_seed 32 | slot_hash 32 | oracle_request 32 | ...
+  const pending = (await conn.getAccountInfo(pendingBetPda, "confirmed")).data;
+  assert(pending.subarray(162, 194).equals(requestPda.toBuffer()), "pending bet does not record the request");
+  const slotHash = pending.subarray(130, 162);
+
+  // mock_oracle fulfils with sha256("MOCK_ORACLE_V1" | seed), the seed being the pending bet key
+  const roll = fairDiceRoll(sha256(Buffer.from("MOCK_ORACLE_V1"), pendingBetPda.toBuffer()), clientSeed, slotHash, nonce);
+  const payout = dicePayout(roll);
+
+  const resolve = (signedPayout) => {
+    const message = canonicalMessage({
+      vault,
+      player: player.publicKey,
+      pendingBet: pendingBetPda,
+      roll,
+      payout: signedPayout,
+      nonce,
+      expiryUnix,
+    });
+    const edIx = Ed25519Program.createInstructionWithPublicKey({
+      publicKey: Buffer.from(adminSecret64.slice(32)),
+      message,
+      signature: Buffer.from(nacl.sign.detached(Uint8Array.from(message), adminSecret64)),
+    });
+    // oracle mode reveals nothing: server_seed is sent as zeroes
+    const ixResolve = ix(Buffer.concat([disc("resolve_bet"), Buffer.alloc(32), u64le(signedPayout)]), [
+      acc(player.publicKey, true),
+      acc(vault, true),
+      acc(pendingBetPda, true),
+      acc(SystemProgram.programId),
+      acc(SYSVAR_INSTRUCTIONS),
+      acc(gameStatePda, true),
+      acc(resolverSetPda, true),
+      acc(requestPda),
+    ]);
+    return [edIx, ixResolve];
+  };
+
+  // --- 3. resolve waits for the oracle ---
+  await expectFail(conn, player, resolve(payout), "resolve_bet before fulfill", "RandomnessUnavailable");
+
+  await sendTx(conn, player, [ix(disc("fulfill"), [acc(requestPda, true)], MOCK_ORACLE_ID)], "oracle fulfill");
+
+  // --- 4. a signed payout other than the derived one is refused ---
+  await expectFail(conn, player, resolve(payout + 1), "resolve_bet with payout + 1", "BadPayout");
+
+  // --- 5. resolve pays exactly the derived payout ---
+  const vaultBefore = await conn.getBalance(vault, "confirmed");
+  await sendTx(conn, player, resolve(payout), "resolve_bet");
+  const vaultAfter = await conn.getBalance(vault, "confirmed");
+  assert.strictEqual(vaultBefore - vaultAfter, payout, "vault outflow differs from the derived payout");
+  assert.strictEqual(await conn.getAccountInfo(pendingBetPda, "confirmed"), null, "pending bet not closed");
+
+  // leave the game on backend randomness for the other scripts
+  await sendTx(conn, player, [setRandomness(0, PublicKey.default)], "set_randomness(backend)");
+
+  console.log(`\n=== PASS === roll=${roll} payout=${payout}`);
+})().catch((e) => {
+  console.error("oracle round trip failed:", e);
+  process.exit(1);
+});